@@ -0,0 +1,57 @@
+use std::env::args;
+use std::fs;
+use std::path::Path;
+
+use serialport::SerialPortSettings;
+
+use odrive_rs::commands::ODrive;
+use odrive_rs::config::ODriveConfig;
+
+/// Usage: `config_backup <port> backup|restore <file>`
+///
+/// The configuration is stored as TOML if the file name ends with `.toml`, and as JSON otherwise.
+fn main() {
+    // Get CLI args
+    let args: Vec<String> = args().collect();
+    let command = &args[2];
+    let file = Path::new(&args[3]);
+    let is_toml = file
+        .extension()
+        .is_some_and(|extension| extension == "toml");
+
+    // Create serial port settings, ODrive uses 115200 baud
+    let settings = SerialPortSettings {
+        baud_rate: 115_200,
+        ..Default::default()
+    };
+
+    // Create serial port
+    let serial = serialport::posix::TTYPort::open(Path::new(&args[1]), &settings)
+        .expect("Failed to open port");
+
+    // Create odrive connection
+    let mut odrive = ODrive::new(serial);
+    odrive.detect_firmware().unwrap();
+
+    match command.as_str() {
+        "backup" => {
+            let config = odrive.backup_config().unwrap();
+            let text = if is_toml {
+                toml::to_string(&config).unwrap()
+            } else {
+                serde_json::to_string_pretty(&config).unwrap()
+            };
+            fs::write(file, text).expect("Failed to write file");
+        }
+        "restore" => {
+            let text = fs::read_to_string(file).expect("Failed to read file");
+            let config: ODriveConfig = if is_toml {
+                toml::from_str(&text).unwrap()
+            } else {
+                serde_json::from_str(&text).unwrap()
+            };
+            odrive.restore_config(&config, true).unwrap();
+        }
+        _ => panic!("Unknown command {}", command),
+    }
+}
@@ -0,0 +1,63 @@
+use std::env::args;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+
+use serialport::SerialPortSettings;
+
+use odrive_rs::commands::ODrive;
+use odrive_rs::config::{ODriveConfig, OdrivetoolConfig, Tolerance};
+
+/// Usage: `config_diff <port> <file> [relative tolerance]`
+///
+/// The file is an `ODriveConfig` in TOML or JSON, as written by the `config_backup` example, or a
+/// file of `odrivetool backup-config`. Prints the properties which differ and exits with 1 if
+/// there are any.
+fn main() {
+    // Get CLI args
+    let args: Vec<String> = args().collect();
+    let file = Path::new(&args[2]);
+    let tolerance = args
+        .get(3)
+        .map(|tolerance| Tolerance::relative(tolerance.parse().expect("Invalid tolerance")))
+        .unwrap_or_default();
+
+    // Create serial port settings, ODrive uses 115200 baud
+    let settings = SerialPortSettings {
+        baud_rate: 115_200,
+        ..Default::default()
+    };
+
+    // Create serial port
+    let serial = serialport::posix::TTYPort::open(Path::new(&args[1]), &settings)
+        .expect("Failed to open port");
+
+    // Create odrive connection
+    let mut odrive = ODrive::new(serial);
+    let capabilities = odrive.detect_firmware().unwrap();
+
+    let text = fs::read_to_string(file).expect("Failed to read file");
+    let expected = if file
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        let config: ODriveConfig = toml::from_str(&text).unwrap();
+        config.properties(&capabilities).unwrap()
+    } else if let Ok(config) = serde_json::from_str::<ODriveConfig>(&text) {
+        config.properties(&capabilities).unwrap()
+    } else {
+        let config = OdrivetoolConfig::from_json(&text).unwrap();
+        for key in &config.unknown_keys {
+            eprintln!("Skipping unknown key {}", key);
+        }
+        config.properties
+    };
+
+    let differences = odrive.diff_config(&expected, tolerance).unwrap();
+    for difference in &differences {
+        println!("{}", difference);
+    }
+    if !differences.is_empty() {
+        exit(1);
+    }
+}
@@ -1,21 +1,20 @@
 use std::env::args;
-use std::io::Write;
 use std::path::Path;
 
 use serialport::SerialPortSettings;
 
-use odrive_rs::commands::ODrive;
+use odrive_rs::commands::{ODrive, Transition};
 use odrive_rs::enumerations::{AxisID, AxisState};
 
 fn main() {
     // Get CLI args
     let args: Vec<String> = args().collect();
 
-    // Create serial port settings
-    let mut settings = SerialPortSettings::default();
-
-    // ODrive uses 115200 baud
-    settings.baud_rate = 115_200;
+    // Create serial port settings, ODrive uses 115200 baud
+    let settings = SerialPortSettings {
+        baud_rate: 115_200,
+        ..Default::default()
+    };
 
     // Create serial port
     let serial = serialport::posix::TTYPort::open(Path::new(&args[1]), &settings)
@@ -25,10 +24,18 @@ fn main() {
     let mut odrive = ODrive::new(serial);
 
     odrive
-        .run_state(AxisID::Zero, AxisState::MotorCalibration, true)
+        .run_state(
+            AxisID::Zero,
+            AxisState::MotorCalibration,
+            Transition::for_state(AxisState::MotorCalibration),
+        )
         .unwrap();
     odrive
-        .run_state(AxisID::One, AxisState::MotorCalibration, true)
+        .run_state(
+            AxisID::One,
+            AxisState::MotorCalibration,
+            Transition::for_state(AxisState::MotorCalibration),
+        )
         .unwrap();
 
     // set motor pre calibrated
@@ -36,10 +43,18 @@ fn main() {
     odrive.set_motor_pre_calibrated(AxisID::One, true).unwrap();
 
     odrive
-        .run_state(AxisID::Zero, AxisState::EncoderOffsetCalibration, true)
+        .run_state(
+            AxisID::Zero,
+            AxisState::EncoderOffsetCalibration,
+            Transition::for_state(AxisState::EncoderOffsetCalibration),
+        )
         .unwrap();
     odrive
-        .run_state(AxisID::One, AxisState::EncoderOffsetCalibration, true)
+        .run_state(
+            AxisID::One,
+            AxisState::EncoderOffsetCalibration,
+            Transition::for_state(AxisState::EncoderOffsetCalibration),
+        )
         .unwrap();
 
     odrive
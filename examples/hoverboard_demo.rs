@@ -1,23 +1,22 @@
 use std::env::args;
-use std::io::Write;
 use std::path::Path;
 use std::thread::sleep;
 use std::time::Duration;
 
 use serialport::SerialPortSettings;
 
-use odrive_rs::commands::ODrive;
+use odrive_rs::commands::{ODrive, Transition};
 use odrive_rs::enumerations::{AxisID, AxisState, ControlMode};
 
 fn main() {
     // Get CLI args
     let args: Vec<String> = args().collect();
 
-    // Create serial port settings
-    let mut settings = SerialPortSettings::default();
-
-    // ODrive uses 115200 baud
-    settings.baud_rate = 115_200;
+    // Create serial port settings, ODrive uses 115200 baud
+    let settings = SerialPortSettings {
+        baud_rate: 115_200,
+        ..Default::default()
+    };
 
     // Create serial port
     let serial = serialport::posix::TTYPort::open(Path::new(&args[1]), &settings)
@@ -27,10 +26,18 @@ fn main() {
     let mut odrive = ODrive::new(serial);
 
     odrive
-        .run_state(AxisID::Zero, AxisState::ClosedLoopControl, false)
+        .run_state(
+            AxisID::Zero,
+            AxisState::ClosedLoopControl,
+            Transition::no_wait(),
+        )
         .unwrap();
     odrive
-        .run_state(AxisID::One, AxisState::ClosedLoopControl, false)
+        .run_state(
+            AxisID::One,
+            AxisState::ClosedLoopControl,
+            Transition::no_wait(),
+        )
         .unwrap();
 
     odrive
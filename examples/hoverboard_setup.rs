@@ -4,7 +4,8 @@ use std::path::Path;
 use serialport::SerialPortSettings;
 
 use odrive_rs::commands::ODrive;
-use odrive_rs::enumerations::{AxisID, ControlMode, EncoderMode};
+use odrive_rs::config::{ConfigProperty, ConfigValue};
+use odrive_rs::enumerations::{ControlMode, EncoderMode};
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -12,11 +13,11 @@ fn main() {
     // Get CLI args
     let args: Vec<String> = args().collect();
 
-    // Create serial port settings
-    let mut settings = SerialPortSettings::default();
-
-    // ODrive uses 115200 baud
-    settings.baud_rate = 115_200;
+    // Create serial port settings, ODrive uses 115200 baud
+    let settings = SerialPortSettings {
+        baud_rate: 115_200,
+        ..Default::default()
+    };
 
     // Create serial port
     let serial = serialport::posix::TTYPort::open(Path::new(&args[1]), &settings)
@@ -28,60 +29,52 @@ fn main() {
     sleep(Duration::from_millis(200));
     odrive.erase_configuration().unwrap();
 
-    odrive.set_motor_pole_pairs(AxisID::Zero, 15).unwrap();
-    odrive.set_motor_pole_pairs(AxisID::One, 15).unwrap();
-    odrive
-        .set_motor_resistance_calib_max_voltage(AxisID::Zero, 4.0)
-        .unwrap();
-    odrive
-        .set_motor_resistance_calib_max_voltage(AxisID::One, 4.0)
-        .unwrap();
-    odrive
-        .set_motor_requested_current_range(AxisID::Zero, 25.0)
-        .unwrap();
-    odrive
-        .set_motor_requested_current_range(AxisID::One, 25.0)
-        .unwrap();
-    odrive
-        .set_motor_current_control_bandwidth(AxisID::Zero, 100.0)
-        .unwrap();
-    odrive
-        .set_motor_current_control_bandwidth(AxisID::One, 100.0)
-        .unwrap();
-
-    odrive
-        .set_encoder_mode(AxisID::Zero, EncoderMode::EncoderModeHall)
-        .unwrap();
-    odrive
-        .set_encoder_mode(AxisID::One, EncoderMode::EncoderModeHall)
-        .unwrap();
-    odrive.set_encoder_cpr(AxisID::Zero, 90).unwrap();
-    odrive.set_encoder_cpr(AxisID::One, 90).unwrap();
-    odrive.set_encoder_bandwidth(AxisID::Zero, 100.0).unwrap();
-    odrive.set_encoder_bandwidth(AxisID::One, 100.0).unwrap();
-
-    odrive.set_position_gain(AxisID::Zero, 1.0).unwrap();
-    odrive.set_position_gain(AxisID::One, 1.0).unwrap();
-
-    odrive.set_velocity_gain(AxisID::Zero, 0.02).unwrap();
-    odrive.set_velocity_gain(AxisID::One, 0.02).unwrap();
-
-    odrive
-        .set_velocity_integrator_gain(AxisID::Zero, 0.1)
-        .unwrap();
-    odrive
-        .set_velocity_integrator_gain(AxisID::One, 0.1)
-        .unwrap();
-
-    odrive.set_velocity_limit(AxisID::Zero, 1000.0).unwrap();
-    odrive.set_velocity_limit(AxisID::One, 1000.0).unwrap();
-
-    odrive
-        .set_control_mode(AxisID::Zero, ControlMode::VelocityControl)
-        .unwrap();
-    odrive
-        .set_control_mode(AxisID::One, ControlMode::VelocityControl)
-        .unwrap();
-
-    odrive.save_configuration().unwrap();
+    // Only the properties which differ from the defaults of the board are written
+    let capabilities = odrive.capabilities();
+    let velocity_control = capabilities.control_mode_value(ControlMode::VelocityControl);
+    let mut properties = Vec::new();
+    for axis in &["axis0", "axis1"] {
+        let settings = [
+            ("motor.config.pole_pairs", ConfigValue::Int(15)),
+            (
+                "motor.config.resistance_calib_max_voltage",
+                ConfigValue::Float(4.0),
+            ),
+            (
+                "motor.config.requested_current_range",
+                ConfigValue::Float(25.0),
+            ),
+            (
+                "motor.config.current_control_bandwidth",
+                ConfigValue::Float(100.0),
+            ),
+            (
+                "encoder.config.mode",
+                ConfigValue::Int(EncoderMode::EncoderModeHall as i64),
+            ),
+            ("encoder.config.cpr", ConfigValue::Int(90)),
+            ("encoder.config.bandwidth", ConfigValue::Float(100.0)),
+            ("controller.config.pos_gain", ConfigValue::Float(1.0)),
+            ("controller.config.vel_gain", ConfigValue::Float(0.02)),
+            (
+                "controller.config.vel_integrator_gain",
+                ConfigValue::Float(0.1),
+            ),
+            ("controller.config.vel_limit", ConfigValue::Float(1000.0)),
+            (
+                "controller.config.control_mode",
+                ConfigValue::Int(velocity_control.into()),
+            ),
+        ];
+        for (path, value) in settings.iter() {
+            properties.push(ConfigProperty {
+                path: format!("{}.{}", axis, path),
+                value: *value,
+            });
+        }
+    }
+
+    // Either every property is written and saved, or the board is left as it was
+    let report = odrive.apply_config(&properties, true).unwrap();
+    println!("Applied {} properties", report.applied.len());
 }
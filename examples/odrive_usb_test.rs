@@ -7,18 +7,18 @@ use std::time::{Duration, Instant};
 
 use serialport::SerialPortSettings;
 
-use odrive_rs::commands::ODrive;
+use odrive_rs::commands::{ODrive, Transition};
 use odrive_rs::enumerations::{AxisID, AxisState};
 
 fn main() {
     // Get CLI args
     let args: Vec<String> = args().collect();
 
-    // Create serial port settings
-    let mut settings = SerialPortSettings::default();
-
-    // ODrive uses 115200 baud
-    settings.baud_rate = 115_200;
+    // Create serial port settings, ODrive uses 115200 baud
+    let settings = SerialPortSettings {
+        baud_rate: 115_200,
+        ..Default::default()
+    };
 
     // Create serial port
     let serial = serialport::posix::TTYPort::open(Path::new(&args[1]), &settings)
@@ -37,8 +37,15 @@ fn main() {
     // You can of course set them different if you want.
     // See the documentation or play around in odrivetool to see the available parameters
     for axis in 0..2 {
-        writeln!(odrive, "w axis{}.controller.config.vel_limit 22000.0", axis).unwrap();
-        writeln!(odrive, "w axis{}.motor.config.current_lim 11.0", axis).unwrap();
+        odrive
+            .write_property(
+                &format!("axis{}.controller.config.vel_limit", axis),
+                22000.0,
+            )
+            .unwrap();
+        odrive
+            .write_property(&format!("axis{}.motor.config.current_lim", axis), 11.0)
+            .unwrap();
     }
 
     println!("Ready!");
@@ -56,32 +63,56 @@ fn main() {
         if trimmed == "!exit" {
             break;
         } else {
-            if let Some(first) = trimmed.chars().nth(0) {
+            if let Some(first) = trimmed.chars().next() {
                 match first {
                     // Run calibration sequence
                     'c' => {
                         println!("Requesting state {:?}", AxisState::MotorCalibration);
                         odrive
-                            .run_state(AxisID::Zero, AxisState::MotorCalibration, true)
+                            .run_state(
+                                AxisID::Zero,
+                                AxisState::MotorCalibration,
+                                Transition::for_state(AxisState::MotorCalibration),
+                            )
                             .unwrap();
                         odrive
-                            .run_state(AxisID::One, AxisState::MotorCalibration, true)
+                            .run_state(
+                                AxisID::One,
+                                AxisState::MotorCalibration,
+                                Transition::for_state(AxisState::MotorCalibration),
+                            )
                             .unwrap();
 
                         println!("Requesting state {:?}", AxisState::EncoderOffsetCalibration);
                         odrive
-                            .run_state(AxisID::Zero, AxisState::EncoderOffsetCalibration, true)
+                            .run_state(
+                                AxisID::Zero,
+                                AxisState::EncoderOffsetCalibration,
+                                Transition::for_state(AxisState::EncoderOffsetCalibration),
+                            )
                             .unwrap();
                         odrive
-                            .run_state(AxisID::One, AxisState::EncoderOffsetCalibration, true)
+                            .run_state(
+                                AxisID::One,
+                                AxisState::EncoderOffsetCalibration,
+                                Transition::for_state(AxisState::EncoderOffsetCalibration),
+                            )
                             .unwrap();
 
                         println!("Requesting state {:?}", AxisState::ClosedLoopControl);
                         odrive
-                            .run_state(AxisID::Zero, AxisState::ClosedLoopControl, false)
+                            .run_state(
+                                AxisID::Zero,
+                                AxisState::ClosedLoopControl,
+                                Transition::no_wait(),
+                            )
                             .unwrap();
                         odrive
-                            .run_state(AxisID::One, AxisState::ClosedLoopControl, false)
+                            .run_state(
+                                AxisID::One,
+                                AxisState::ClosedLoopControl,
+                                Transition::no_wait(),
+                            )
                             .unwrap();
                     }
                     '0' | '1' => {
@@ -97,7 +128,11 @@ fn main() {
                             AxisState::MotorCalibration
                         );
                         odrive
-                            .run_state(motor_num, AxisState::MotorCalibration, true)
+                            .run_state(
+                                motor_num,
+                                AxisState::MotorCalibration,
+                                Transition::for_state(AxisState::MotorCalibration),
+                            )
                             .unwrap();
 
                         println!(
@@ -106,7 +141,11 @@ fn main() {
                             AxisState::EncoderOffsetCalibration
                         );
                         odrive
-                            .run_state(motor_num, AxisState::EncoderOffsetCalibration, true)
+                            .run_state(
+                                motor_num,
+                                AxisState::EncoderOffsetCalibration,
+                                Transition::for_state(AxisState::EncoderOffsetCalibration),
+                            )
                             .unwrap();
 
                         println!(
@@ -115,7 +154,11 @@ fn main() {
                             AxisState::ClosedLoopControl
                         );
                         odrive
-                            .run_state(motor_num, AxisState::ClosedLoopControl, false)
+                            .run_state(
+                                motor_num,
+                                AxisState::ClosedLoopControl,
+                                Transition::no_wait(),
+                            )
                             .unwrap();
                     }
                     // Sinusoidal test move
@@ -142,8 +185,8 @@ fn main() {
                     }
                     // Read bus voltage
                     'b' => {
-                        writeln!(odrive, "r vbus_voltage").unwrap();
-                        println!("Vbus voltage: {}", odrive.read_float().unwrap().unwrap());
+                        let vbus_voltage: f32 = odrive.read_property("vbus_voltage").unwrap();
+                        println!("Vbus voltage: {}", vbus_voltage);
                     }
                     // print motor positions in a 10s loop
                     'p' => {
@@ -0,0 +1,318 @@
+#!/usr/bin/env python3
+"""Generates src/properties/tree.rs from the interface definitions of the firmware.
+
+Usage:
+    generate_property_tree.py <odrive-0.4.12.json> <odrive-interface-0.5.6.yaml> > src/properties/tree.rs
+
+The first file is the JSON definition which firmware 0.4.12 sends on endpoint 0, as cached by
+odrivetool or read with `odrv0._json_data`. The second is `Firmware/odrive-interface.yaml` of the
+0.5.6 release. Requires PyYAML.
+
+Both trees are merged: a member found in only one firmware is tagged `@ v0_4` or `@ v0_5`.
+Objects which share a structure, such as `axis0` and `axis1`, share one Rust type, named by
+OBJECT_NAMES. Functions and endpoint references are left out, because the ASCII protocol can only
+read and write values.
+"""
+
+import json
+import re
+import sys
+from collections import OrderedDict
+
+import yaml
+
+# The Rust type of each object, by its path with the axis and mapping numbers removed
+OBJECT_NAMES = {
+    "": "Root",
+    "config": "Config",
+    "config.mapping": "Mapping",
+    "system_stats": "SystemStats",
+    "system_stats.usb": "UsbStats",
+    "system_stats.i2c": "I2cStats",
+    "can": "Can",
+    "can.config": "CanConfig",
+    "axis": "Axis",
+    "axis.config": "AxisConfig",
+    "axis.config.calibration_lockin": "CalibrationLockin",
+    "axis.config.sensorless_ramp": "LockinConfig",
+    "axis.config.general_lockin": "LockinConfig",
+    "axis.config.can": "AxisCanConfig",
+    "axis.motor": "Motor",
+    "axis.motor.current_control": "CurrentControl",
+    "axis.motor.gate_driver": "GateDriver",
+    "axis.motor.fet_thermistor": "FetThermistor",
+    "axis.motor.fet_thermistor.config": "FetThermistorConfig",
+    "axis.motor.motor_thermistor": "MotorThermistor",
+    "axis.motor.motor_thermistor.config": "MotorThermistorConfig",
+    "axis.motor.config": "MotorConfig",
+    "axis.encoder": "Encoder",
+    "axis.encoder.config": "EncoderConfig",
+    "axis.sensorless_estimator": "SensorlessEstimator",
+    "axis.sensorless_estimator.config": "SensorlessEstimatorConfig",
+    "axis.controller": "Controller",
+    "axis.controller.config": "ControllerConfig",
+    "axis.controller.config.anticogging": "Anticogging",
+    "axis.controller.autotuning": "Autotuning",
+    "axis.trap_traj": "TrapTraj",
+    "axis.trap_traj.config": "TrapTrajConfig",
+    "axis.endstop": "Endstop",
+    "axis.endstop.config": "EndstopConfig",
+    "axis.mechanical_brake": "MechanicalBrake",
+    "axis.mechanical_brake.config": "MechanicalBrakeConfig",
+}
+
+OBJECT_DOCS = {
+    "Root": "The root of the ODrive object tree.",
+    "Config": "The board configuration, stored by `save_configuration`.",
+    "Mapping": "The range a PWM or analog input is mapped to. The endpoint the input drives is a "
+    "reference,\nwhich cannot be read or written over the ASCII protocol.",
+    "Axis": "One of the two axes.",
+    "CalibrationLockin": "The open loop spin of the encoder offset calibration.",
+    "LockinConfig": "An open loop spin, as used by the index search and the sensorless ramp.",
+    "AxisCanConfig": "The CAN Simple settings of an axis, which replace `can_node_id` and its "
+    "siblings in 0.5.x.",
+}
+
+# The Rust type of the properties which are enumerations or error flags of this crate
+PROPERTY_TYPES = {
+    "axis.error": "AxisErrors",
+    "axis.motor.error": "MotorErrors",
+    "axis.encoder.error": "EncoderErrors",
+    "axis.controller.error": "ControllerErrors",
+    "axis.current_state": "AxisState",
+    "axis.requested_state": "AxisState",
+    "axis.motor.config.motor_type": "MotorType",
+    "axis.encoder.config.mode": "EncoderMode",
+    "axis.controller.config.control_mode": "ControlMode",
+    "axis.controller.config.input_mode": "InputMode",
+}
+
+# Read-only in 0.5.x, and their paths are translated to `input_pos` and `input_vel`
+SKIPPED_0_5 = {"axis.controller.pos_setpoint", "axis.controller.vel_setpoint"}
+
+SCALAR_TYPES = {
+    "bool": "bool",
+    "float": "f32",
+    "float32": "f32",
+    "int8": "i8",
+    "int16": "i16",
+    "int32": "i32",
+    "int64": "i64",
+    "uint8": "u8",
+    "uint16": "u16",
+    "uint32": "u32",
+    "uint64": "u64",
+}
+
+# The underlying type of the enumerations and flags of odrive-interface.yaml
+VALUETYPE_DEFAULTS = {"flags": "u32", "values": "u8"}
+
+
+class Node:
+    """An object of one firmware: its child objects and its properties, in order."""
+
+    def __init__(self):
+        self.children = OrderedDict()
+        self.properties = OrderedDict()
+
+
+def pattern(path):
+    path = re.sub(r"\baxis\d\b", "axis", path)
+    path = re.sub(r"\bgpio\d+_(pwm|analog)_mapping\b", "mapping", path)
+    return re.sub(r"\b(min|max)_endstop\b", "endstop", path)
+
+
+def join(path, name):
+    return name if not path else path + "." + name
+
+
+def add_property(node, path, name, rust_type, access, firmware):
+    full = pattern(join(path, name))
+    if firmware == "v0_5" and full in SKIPPED_0_5:
+        return
+    node.properties[name] = (PROPERTY_TYPES.get(full, rust_type), access)
+
+
+def read_0_4(path):
+    with open(path) as file:
+        members = json.load(file)
+    root = Node()
+
+    def walk(node, path, members):
+        for member in members:
+            name, kind = member["name"], member["type"]
+            if kind == "object":
+                child = Node()
+                node.children[name] = child
+                walk(child, join(path, name), member["members"])
+            elif kind in SCALAR_TYPES:
+                access = "rw" if "w" in member.get("access", "r") else "ro"
+                add_property(node, path, name, SCALAR_TYPES[kind], access, "v0_4")
+            # Functions, endpoint references and the JSON endpoint itself are not properties
+
+    walk(root, "", members)
+    return root
+
+
+def read_0_5(path):
+    with open(path) as file:
+        definition = yaml.safe_load(file)
+    interfaces = definition["interfaces"]
+    valuetypes = definition.get("valuetypes", {})
+
+    def resolve(names, scope, name):
+        """Finds `name` from the scope of an interface, like the firmware's code generator."""
+        parts = scope.split(".")
+        for end in range(len(parts), -1, -1):
+            candidate = ".".join(parts[:end] + [name])
+            if candidate in names:
+                return candidate
+        return None
+
+    def walk(node, path, interface, scope):
+        for name, attribute in (interface.get("attributes") or {}).items():
+            if isinstance(attribute, dict) and "attributes" in attribute:
+                child = Node()
+                node.children[name] = child
+                walk(child, join(path, name), attribute, scope + "." + snake_to_camel(name))
+                continue
+            type_name = attribute["type"] if isinstance(attribute, dict) else attribute
+            access = "rw"
+            if type_name.startswith("readonly "):
+                access, type_name = "ro", type_name[len("readonly "):]
+            if type_name in SCALAR_TYPES:
+                add_property(node, path, name, SCALAR_TYPES[type_name], access, "v0_5")
+                continue
+            interface_name = resolve(interfaces, scope, type_name)
+            if interface_name is not None:
+                child = Node()
+                node.children[name] = child
+                walk(child, join(path, name), interfaces[interface_name], interface_name)
+                continue
+            valuetype_name = resolve(valuetypes, scope, type_name)
+            if valuetype_name is not None:
+                valuetype = valuetypes[valuetype_name]
+                kind = "flags" if "flags" in valuetype else "values"
+                add_property(node, path, name, VALUETYPE_DEFAULTS[kind], access, "v0_5")
+                continue
+            if type_name != "endpoint_ref":
+                print("skipped {} of unknown type {}".format(join(path, name), type_name),
+                      file=sys.stderr)
+
+    root = Node()
+    walk(root, "", interfaces["ODrive"], "ODrive")
+    return root
+
+
+def snake_to_camel(name):
+    return "".join(part.capitalize() for part in name.split("_"))
+
+
+def wider(first, second):
+    """The integer type which holds the values of both, for members whose type changed."""
+    if first == second:
+        return first
+    integers = re.compile(r"^[iu]\d+$")
+    if not (integers.match(first) and integers.match(second)):
+        raise ValueError("incompatible types {} and {}".format(first, second))
+    bits = max(int(first[1:]), int(second[1:]))
+    signed = "i" if "i" in (first[0], second[0]) else "u"
+    return "{}{}".format(signed, bits)
+
+
+class Object:
+    """A Rust type of the merged tree, with the firmwares each member exists in."""
+
+    def __init__(self):
+        self.children = OrderedDict()
+        self.properties = OrderedDict()
+
+
+def merge(objects, node, path, firmware):
+    name = OBJECT_NAMES.get(pattern(path))
+    if name is None:
+        name = snake_to_camel(pattern(path).replace(".", "_"))
+        print("no name for {}, using {}".format(pattern(path), name), file=sys.stderr)
+    merged = objects.setdefault(name, Object())
+    for child_name, child in node.children.items():
+        child_type = merge(objects, child, join(path, child_name), firmware)
+        entry = merged.children.setdefault(child_name, [child_type, set()])
+        entry[1].add(firmware)
+    for property_name, (rust_type, access) in node.properties.items():
+        entry = merged.properties.setdefault(property_name, [rust_type, access, set()])
+        if entry[0] != rust_type:
+            entry[0] = wider(entry[0], rust_type)
+        if access == "rw":
+            entry[1] = "rw"
+        entry[2].add(firmware)
+    return name
+
+
+def tag(firmwares):
+    if len(firmwares) == 2:
+        return ""
+    return " @ " + next(iter(firmwares))
+
+
+def render_list(name, entries):
+    if not entries:
+        return ["        {}: [],".format(name)]
+    return (["        {}: [".format(name)]
+            + ["            " + entry for entry in entries]
+            + ["        ],"])
+
+
+def render(objects):
+    lines = [
+        "//! The property trees of firmware 0.4.12 and 0.5.6, merged into one.",
+        "//!",
+        "//! Generated by `scripts/generate_property_tree.py` from the JSON definition of 0.4.12"
+        " and",
+        "//! `Firmware/odrive-interface.yaml` of 0.5.6. Do not edit it by hand, change the script"
+        " instead.",
+        "//!",
+        "//! Properties renamed in 0.5.0 are listed under their name in each firmware, and",
+        "//! `Capabilities::property_path` translates either name for the connected board. The"
+        " read-only",
+        "//! `controller.pos_setpoint` and `controller.vel_setpoint` of 0.5.x are not listed,"
+        " because their",
+        "//! paths are translated to `controller.input_pos` and `controller.input_vel`.",
+        "",
+        "use super::*;",
+        "use crate::enumerations::errors::{AxisErrors, ControllerErrors, EncoderErrors,"
+        " MotorErrors};",
+        "",
+        "property_tree! {",
+    ]
+    for index, (name, merged) in enumerate(objects.items()):
+        if index > 0:
+            lines.append("")
+        for doc_line in OBJECT_DOCS.get(name, "").splitlines():
+            lines.append("    /// " + doc_line)
+        lines.append("    {} {{".format(name))
+        children = [
+            "{}: {}{},".format(child_name, child_type, tag(firmwares))
+            for child_name, (child_type, firmwares) in merged.children.items()
+        ]
+        properties = [
+            "{}: {} = {}{},".format(property_name, rust_type, access, tag(firmwares))
+            for property_name, (rust_type, access, firmwares) in merged.properties.items()
+        ]
+        lines.extend(render_list("objects", children))
+        lines.extend(render_list("properties", properties))
+        lines.append("    }")
+    lines.append("}")
+    return "\n".join(lines) + "\n"
+
+
+def main():
+    if len(sys.argv) != 3:
+        sys.exit(__doc__)
+    objects = OrderedDict()
+    merge(objects, read_0_4(sys.argv[1]), "", "v0_4")
+    merge(objects, read_0_5(sys.argv[2]), "", "v0_5")
+    sys.stdout.write(render(objects))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,326 @@
+use super::*;
+use crate::commands::{Capabilities, FirmwareVersion, Transition};
+use crate::enumerations::errors::AxisError;
+use crate::simulator::{SimulatedODrive, Value};
+use crate::units::{AxisScaling, Counts, ScaledFeedback, Turns};
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use tokio::io::{duplex, AsyncReadExt, DuplexStream};
+
+/// Returns an `AsyncODrive` and the other end of its stream.
+fn init_odrive() -> (AsyncODrive<DuplexStream>, DuplexStream) {
+    let (client, device) = duplex(1024);
+    (AsyncODrive::new(client), device)
+}
+
+/// Connects an `AsyncODrive` to a simulated ODrive running in a background task.
+fn init_simulated_odrive() -> (AsyncODrive<DuplexStream>, Arc<Mutex<SimulatedODrive>>) {
+    let (client, mut device) = duplex(1024);
+    let simulator = Arc::new(Mutex::new(SimulatedODrive::new()));
+    let shared = simulator.clone();
+    tokio::spawn(async move {
+        let mut buffer = [0; 256];
+        while let Ok(count) = device.read(&mut buffer).await {
+            if count == 0 {
+                break;
+            }
+            let mut output = Vec::new();
+            {
+                let mut simulator = shared.lock().unwrap();
+                simulator.write_all(&buffer[..count]).unwrap();
+                simulator.read_to_end(&mut output).unwrap();
+            }
+            if device.write_all(&output).await.is_err() {
+                break;
+            }
+        }
+    });
+    (AsyncODrive::new(client), simulator)
+}
+
+async fn read_written(device: &mut DuplexStream, expected: &[u8]) {
+    let mut written = vec![0; expected.len()];
+    device.read_exact(&mut written).await.unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(expected),
+        String::from_utf8_lossy(&written)
+    );
+}
+
+#[tokio::test]
+async fn test_set_velocity() {
+    let (mut odrive, mut device) = init_odrive();
+    odrive
+        .set_velocity(AxisID::One, 10.0, Some(0.5))
+        .await
+        .unwrap();
+    read_written(&mut device, b"v 1 10 0.5\n").await;
+}
+
+#[tokio::test]
+async fn test_motion_commands() {
+    let (mut odrive, mut device) = init_odrive();
+    odrive
+        .set_position_p(AxisID::Zero, 100.0, None, Some(1.0))
+        .await
+        .unwrap();
+    odrive
+        .set_position_q(AxisID::Zero, 5.0, Some(20.0), None)
+        .await
+        .unwrap();
+    odrive.set_current(AxisID::One, 2.5).await.unwrap();
+    odrive.set_trajectory(AxisID::One, -3.0).await.unwrap();
+    odrive.feed_watchdog(AxisID::Zero).await.unwrap();
+    read_written(
+        &mut device,
+        b"p 0 100 0 1\nq 0 5 20 0\nc 1 2.5\nt 1 -3\nu 0\n",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_read_string_split_message() {
+    let (mut odrive, mut device) = init_odrive();
+    device.write_all(b"hel").await.unwrap();
+    assert_eq!(
+        None,
+        odrive
+            .read_string_timeout(Duration::from_millis(5))
+            .await
+            .unwrap()
+    );
+
+    device.write_all(b"lo\r\n").await.unwrap();
+    assert_eq!(
+        Some("hello".to_owned()),
+        odrive.read_string().await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_read_odrive_response_no_message() {
+    let (client, _device) = duplex(64);
+    let mut odrive = AsyncODrive::with_timeout(client, Duration::from_millis(5));
+    match odrive.read_odrive_response().await {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_read_odrive_response_closed() {
+    let (mut odrive, device) = init_odrive();
+    drop(device);
+    match odrive.read_odrive_response().await {
+        Err(ODriveError::Io(error)) => assert_eq!(io::ErrorKind::UnexpectedEof, error.kind()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_read_config_property() {
+    let (mut odrive, mut device) = init_odrive();
+    device.write_all(b"0.25\n").await.unwrap();
+    assert_eq!(
+        0.25,
+        odrive
+            .read_velocity_integrator_gain(AxisID::One)
+            .await
+            .unwrap()
+    );
+    read_written(
+        &mut device,
+        b"r axis1.controller.config.vel_integrator_gain\n",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_read_invalid_flag() {
+    let (mut odrive, mut device) = init_odrive();
+    device.write_all(b"2\n").await.unwrap();
+    match odrive.read_motor_pre_calibrated(AxisID::Zero).await {
+        Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!("2", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_detect_firmware() {
+    let (mut odrive, _simulator) = init_simulated_odrive();
+    let capabilities = odrive.detect_firmware().await.unwrap();
+    assert_eq!(FirmwareVersion::new(0, 4, 12), capabilities.firmware());
+    assert_eq!(capabilities, odrive.capabilities());
+}
+
+#[tokio::test]
+async fn test_detect_unsupported_firmware() {
+    let (mut odrive, simulator) = init_simulated_odrive();
+    simulator
+        .lock()
+        .unwrap()
+        .set_property("fw_version_minor", Value::Int(6));
+    match odrive.detect_firmware().await {
+        Err(ODriveError::Unsupported(what)) => assert_eq!("firmware 0.6.12", what),
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert_eq!(Capabilities::default(), odrive.capabilities());
+}
+
+#[tokio::test]
+async fn test_firmware_0_5() {
+    let (mut odrive, mut device) = init_odrive();
+    odrive.set_capabilities(Capabilities::new(FirmwareVersion::new(0, 5, 6), None));
+    odrive
+        .set_control_mode(AxisID::Zero, ControlMode::TrajectoryControl)
+        .await
+        .unwrap();
+    read_written(
+        &mut device,
+        b"w axis0.controller.config.control_mode 3\nw axis0.controller.config.input_mode 5\n",
+    )
+    .await;
+
+    device.write_all(b"4\n").await.unwrap();
+    assert!(odrive.read_control_mode(AxisID::Zero).await.is_err());
+    read_written(&mut device, b"r axis0.controller.config.control_mode\n").await;
+}
+
+#[tokio::test]
+async fn test_configuration_round_trip() {
+    let (mut odrive, _simulator) = init_simulated_odrive();
+    odrive.set_motor_pole_pairs(AxisID::One, 15).await.unwrap();
+    odrive
+        .set_encoder_mode(AxisID::One, EncoderMode::EncoderModeHall)
+        .await
+        .unwrap();
+    odrive
+        .set_startup_closed_loop_control(AxisID::One, true)
+        .await
+        .unwrap();
+    odrive
+        .set_control_mode(AxisID::One, ControlMode::CurrentControl)
+        .await
+        .unwrap();
+
+    assert_eq!(15, odrive.read_motor_pole_pairs(AxisID::One).await.unwrap());
+    assert_eq!(
+        EncoderMode::EncoderModeHall,
+        odrive.read_encoder_mode(AxisID::One).await.unwrap()
+    );
+    assert!(odrive
+        .read_startup_closed_loop_control(AxisID::One)
+        .await
+        .unwrap());
+    assert_eq!(
+        ControlMode::CurrentControl,
+        odrive.read_control_mode(AxisID::One).await.unwrap()
+    );
+    assert_eq!(
+        WatchdogConfig::disabled(),
+        odrive.read_watchdog_config(AxisID::One).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_disabling_watchdog_keeps_timeout() {
+    let (mut odrive, _simulator) = init_simulated_odrive();
+    let timeout = Duration::from_millis(250);
+    odrive
+        .configure_watchdog(AxisID::Zero, WatchdogConfig::enabled(timeout))
+        .await
+        .unwrap();
+    odrive
+        .configure_watchdog(AxisID::Zero, WatchdogConfig::disabled())
+        .await
+        .unwrap();
+    assert_eq!(
+        WatchdogConfig {
+            enabled: false,
+            timeout
+        },
+        odrive.read_watchdog_config(AxisID::Zero).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_run_state_and_velocity_control() {
+    let (mut odrive, simulator) = init_simulated_odrive();
+    for state in [
+        AxisState::MotorCalibration,
+        AxisState::EncoderOffsetCalibration,
+        AxisState::ClosedLoopControl,
+    ]
+    .iter()
+    {
+        odrive
+            .run_state(AxisID::Zero, *state, Transition::for_state(*state))
+            .await
+            .unwrap();
+    }
+
+    odrive
+        .set_velocity(AxisID::Zero, 1000.0, None)
+        .await
+        .unwrap();
+    // Reading a property makes sure the command has reached the simulator
+    assert_eq!(
+        ControlMode::VelocityControl,
+        odrive.read_control_mode(AxisID::Zero).await.unwrap()
+    );
+    simulator
+        .lock()
+        .unwrap()
+        .advance(Duration::from_millis(500));
+    let velocity = odrive.get_velocity(AxisID::Zero).await.unwrap();
+    assert!((velocity - 1000.0).abs() < 1.0, "velocity {}", velocity);
+    let feedback = odrive.get_feedback(AxisID::Zero).await.unwrap();
+    assert!(feedback.position > 0.0);
+
+    let scaling = AxisScaling::new(8192.0);
+    let velocity: Turns = odrive
+        .get_scaled_velocity(AxisID::Zero, &scaling)
+        .await
+        .unwrap();
+    assert!(
+        (velocity.0 - 1000.0 / 8192.0).abs() < 1e-3,
+        "{:?}",
+        velocity
+    );
+    let feedback: ScaledFeedback<Counts> = odrive
+        .get_scaled_feedback(AxisID::Zero, &scaling)
+        .await
+        .unwrap();
+    assert!(feedback.position.0 > 0.0);
+}
+
+#[tokio::test]
+async fn test_run_state_rejected() {
+    let (mut odrive, _simulator) = init_simulated_odrive();
+    let state = AxisState::ClosedLoopControl;
+    match odrive
+        .run_state(AxisID::One, state, Transition::for_state(state))
+        .await
+    {
+        Err(ODriveError::StateTransition(error)) => {
+            assert!(!error.timed_out);
+            assert!(error.errors.axis.contains(AxisError::ErrorInvalidState));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    odrive.clear_errors().await.unwrap();
+    assert!(odrive.read_all_errors().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_read_all_errors() {
+    let (mut odrive, simulator) = init_simulated_odrive();
+    simulator
+        .lock()
+        .unwrap()
+        .set_property("axis1.motor.error", Value::Int(0x1));
+    let report = odrive.read_all_errors().await.unwrap();
+    assert!(report.axis0.is_empty());
+    assert_eq!(0x1, report.axis1.motor.bits());
+}
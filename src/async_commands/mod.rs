@@ -0,0 +1,607 @@
+use std::fmt::Display;
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::time::{sleep, timeout, Instant};
+
+use crate::commands::{
+    parse_bool, parse_enum, parse_response, transition_error, Capabilities, Feedback,
+    FirmwareVersion, HardwareVersion, Transition, TransitionEnd, TransitionStep, TransitionWatch,
+    WatchdogConfig, DEFAULT_TIMEOUT,
+};
+use crate::enumerations::errors::{AxisErrorReport, ErrorReport, ODriveError, ODriveResult};
+use crate::enumerations::{AxisID, AxisState, ControlMode, EncoderMode, InputMode};
+use crate::units::{AxisScaling, ScaledFeedback, Unit};
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod async_tests;
+
+/// The time to wait for stale messages when flushing the input.
+const FLUSH_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// Generates a setter and a getter for each axis property.
+/// `value` properties are written and parsed as they are, `flag` properties are sent as `0` or
+/// `1`, and `enumeration` properties are sent as integers.
+macro_rules! axis_properties {
+    ($($setter:ident, $getter:ident: $path:literal => $kind:ident $type:ty;)*) => {
+        $(
+            pub async fn $setter(&mut self, axis: AxisID, value: $type) -> ODriveResult<()> {
+                self.set_axis_property(axis, $path, axis_properties!(@encode $kind value))
+                    .await
+            }
+
+            pub async fn $getter(&mut self, axis: AxisID) -> ODriveResult<$type> {
+                let response = self.get_axis_property(axis, $path).await?;
+                axis_properties!(@decode $kind response)
+            }
+        )*
+    };
+    (@encode value $value:ident) => { $value };
+    (@encode flag $value:ident) => { $value as u8 };
+    (@encode enumeration $value:ident) => { $value as u8 };
+    (@decode value $response:ident) => { parse_response($response) };
+    (@decode flag $response:ident) => { parse_bool($response) };
+    (@decode enumeration $response:ident) => { parse_enum($response) };
+}
+
+/// The `AsyncODrive` struct manages a connection with an ODrive over the ASCII protocol, like
+/// `commands::ODrive`, but without blocking the executor.
+///
+/// It works with any `AsyncRead + AsyncWrite` stream, for example a serial port from
+/// `tokio-serial` or one end of `tokio::io::duplex`. Timeouts use tokio timers, so it must be used
+/// within a tokio runtime with the time driver enabled.
+///
+/// Like `commands::ODrive`, it assumes firmware 0.4.12 until `detect_firmware` is called, and then
+/// translates property paths and enumeration values with the `Capabilities` of the board.
+#[derive(Debug)]
+pub struct AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    io_stream: BufReader<T>,
+    timeout: Duration,
+    /// Holds the start of a message whose end has not been received yet.
+    line_buffer: Vec<u8>,
+    capabilities: Capabilities,
+}
+
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    pub fn new(io_stream: T) -> Self {
+        Self::with_timeout(io_stream, DEFAULT_TIMEOUT)
+    }
+
+    /// Creates an `AsyncODrive` which waits up to `timeout` for responses.
+    pub fn with_timeout(io_stream: T, timeout: Duration) -> Self {
+        Self {
+            io_stream: BufReader::new(io_stream),
+            timeout,
+            line_buffer: Vec::new(),
+            capabilities: Capabilities::default(),
+        }
+    }
+
+    /// The time to wait for a response from the ODrive.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// What the firmware of the ODrive understands, see `detect_firmware`.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Sets the capabilities of the ODrive without reading its version, for example when it is
+    /// already known.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &T {
+        self.io_stream.get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying stream.
+    /// Reading from it directly may discard buffered messages.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.io_stream.get_mut()
+    }
+
+    /// Reads the next line, waiting up to `timeout` for it to be completed.
+    /// A partially received line is kept until the next call.
+    async fn read_line(&mut self, duration: Duration) -> io::Result<Option<String>> {
+        let read = self.io_stream.read_until(b'\n', &mut self.line_buffer);
+        match timeout(duration, read).await {
+            Err(_elapsed) => Ok(None),
+            Ok(Err(error)) => Err(error),
+            Ok(Ok(_)) if self.line_buffer.last() == Some(&b'\n') => {
+                let line = String::from_utf8_lossy(&self.line_buffer).trim().to_owned();
+                self.line_buffer.clear();
+                Ok(Some(line))
+            }
+            Ok(Ok(_)) => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the ODrive closed the connection",
+            )),
+        }
+    }
+
+    /// Reads the next message sent by the ODrive as a string, or `None` if there is no message.
+    pub async fn read_string(&mut self) -> io::Result<Option<String>> {
+        self.read_string_timeout(self.timeout).await
+    }
+
+    /// Same as `read_string`, but waits up to `timeout` instead of the timeout of the connection.
+    pub async fn read_string_timeout(&mut self, timeout: Duration) -> io::Result<Option<String>> {
+        self.read_line(timeout).await
+    }
+
+    /// Reads the next message sent by the ODrive.
+    /// Returns `NoMessageReceived` if no message arrives before the timeout of the connection.
+    pub async fn read_odrive_response(&mut self) -> ODriveResult<String> {
+        self.read_odrive_response_timeout(self.timeout).await
+    }
+
+    /// Same as `read_odrive_response`, but waits up to `timeout` instead of the timeout of the
+    /// connection.
+    pub async fn read_odrive_response_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> ODriveResult<String> {
+        self.read_line(timeout)
+            .await
+            .map_err(ODriveError::Io)?
+            .ok_or(ODriveError::NoMessageReceived)
+    }
+
+    /// Discards all messages which have already been sent by the ODrive.
+    async fn flush_input(&mut self) -> ODriveResult<()> {
+        while self
+            .read_line(FLUSH_TIMEOUT)
+            .await
+            .map_err(ODriveError::Io)?
+            .is_some()
+        {}
+        self.line_buffer.clear();
+        Ok(())
+    }
+
+    /// Sends a command, followed by a line terminator, and flushes the stream.
+    async fn write_command(&mut self, command: &str) -> io::Result<()> {
+        let stream = self.io_stream.get_mut();
+        stream
+            .write_all(format!("{}\n", command).as_bytes())
+            .await?;
+        stream.flush().await
+    }
+}
+
+/// Motion commands, see the methods of the same name on `commands::ODrive`.
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Move the motor to a position. Use this command if you have a real-time controller which
+    /// is streaming setpoints and tracking a trajectory.
+    /// If `None` is supplied for a feed forward input, zero will be provided as a default.
+    pub async fn set_position_p(
+        &mut self,
+        axis: AxisID,
+        position: f32,
+        velocity_feed_forward: Option<f32>,
+        current_feed_forward: Option<f32>,
+    ) -> io::Result<()> {
+        let command = format!(
+            "p {} {} {} {}",
+            axis as u8,
+            position,
+            velocity_feed_forward.unwrap_or_default(),
+            current_feed_forward.unwrap_or_default()
+        );
+        self.write_command(&command).await
+    }
+
+    /// Move the motor to a position. Use this command if you are sending one setpoint at a time.
+    /// If `None` is supplied for a limit, zero will be provided as a default.
+    pub async fn set_position_q(
+        &mut self,
+        axis: AxisID,
+        position: f32,
+        velocity_limit: Option<f32>,
+        current_limit: Option<f32>,
+    ) -> io::Result<()> {
+        let command = format!(
+            "q {} {} {} {}",
+            axis as u8,
+            position,
+            velocity_limit.unwrap_or_default(),
+            current_limit.unwrap_or_default()
+        );
+        self.write_command(&command).await
+    }
+
+    /// Specifies a velocity setpoint for the motor.
+    /// If `None` is supplied for a feed forward input, zero will be provided as a default.
+    pub async fn set_velocity(
+        &mut self,
+        axis: AxisID,
+        velocity: f32,
+        current_feed_forward: Option<f32>,
+    ) -> io::Result<()> {
+        let command = format!(
+            "v {} {} {}",
+            axis as u8,
+            velocity,
+            current_feed_forward.unwrap_or_default()
+        );
+        self.write_command(&command).await
+    }
+
+    /// Specifies a current setpoint for the motor, in amps.
+    pub async fn set_current(&mut self, axis: AxisID, current: f32) -> io::Result<()> {
+        self.write_command(&format!("c {} {}", axis as u8, current))
+            .await
+    }
+
+    /// Moves a motor to a given position, in encoder counts.
+    pub async fn set_trajectory(&mut self, axis: AxisID, position: f32) -> io::Result<()> {
+        self.write_command(&format!("t {} {}", axis as u8, position))
+            .await
+    }
+
+    /// Feeds the watchdog of an axis, which restarts its `watchdog_timeout`.
+    pub async fn feed_watchdog(&mut self, axis: AxisID) -> io::Result<()> {
+        self.write_command(&format!("u {}", axis as u8)).await
+    }
+
+    /// Requests the position and velocity of an axis and waits for the reply.
+    pub async fn get_feedback(&mut self, axis: AxisID) -> ODriveResult<Feedback> {
+        self.write_command(&format!("f {}", axis as u8))
+            .await
+            .map_err(ODriveError::Io)?;
+        self.read_odrive_response().await?.parse()
+    }
+
+    /// Retrieves the velocity of a motor, in counts per second for firmware 0.4.x and in turns per
+    /// second since firmware 0.5.0.
+    pub async fn get_velocity(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "encoder.vel_estimate").await?;
+        parse_response(response)
+    }
+
+    /// Same as `get_feedback`, but converts the feedback with `scaling`, see `units`.
+    pub async fn get_scaled_feedback<U: Unit>(
+        &mut self,
+        axis: AxisID,
+        scaling: &AxisScaling,
+    ) -> ODriveResult<ScaledFeedback<U>> {
+        let feedback = self.get_feedback(axis).await?;
+        ScaledFeedback::from_native(feedback, scaling, self.capabilities.position_unit())
+    }
+
+    /// Same as `get_velocity`, but converts the velocity with `scaling`, see `units`.
+    pub async fn get_scaled_velocity<U: Unit>(
+        &mut self,
+        axis: AxisID,
+        scaling: &AxisScaling,
+    ) -> ODriveResult<U> {
+        let velocity = self.get_velocity(axis).await?;
+        scaling.from_native(velocity, self.capabilities.position_unit())
+    }
+}
+
+/// Axis states, see the methods of the same name on `commands::ODrive`.
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Retrieves the current state of an axis.
+    pub async fn current_state(&mut self, axis: AxisID) -> ODriveResult<AxisState> {
+        let response = self.get_axis_property(axis, "current_state").await?;
+        parse_enum(response)
+    }
+
+    /// Requests a new state for an axis without waiting for it.
+    pub async fn request_state(
+        &mut self,
+        axis: AxisID,
+        requested_state: AxisState,
+    ) -> ODriveResult<()> {
+        self.set_axis_property(axis, "requested_state", requested_state as u8)
+            .await
+    }
+
+    /// Changes the state of an axis and waits as configured by `transition`.
+    ///
+    /// A transition fails with `ODriveError::StateTransition` if it does not complete before the
+    /// timeout, or if the axis is back in `Idle` with errors. The error contains the last observed
+    /// state and the errors read from the axis.
+    pub async fn run_state(
+        &mut self,
+        axis: AxisID,
+        requested_state: AxisState,
+        transition: Transition,
+    ) -> ODriveResult<()> {
+        let deadline = Instant::now() + transition.timeout;
+        self.request_state(axis, requested_state).await?;
+        if transition.end == TransitionEnd::NoWait {
+            return Ok(());
+        }
+
+        let mut watch = TransitionWatch::new(transition.end);
+        loop {
+            let state = self.current_state(axis).await?;
+            let errors = if TransitionWatch::needs_errors(state) {
+                self.read_axis_errors(axis).await?
+            } else {
+                AxisErrorReport::default()
+            };
+            match watch.step(state, &errors) {
+                TransitionStep::Complete => return Ok(()),
+                TransitionStep::Failed => {
+                    return Err(transition_error(
+                        axis,
+                        requested_state,
+                        state,
+                        errors,
+                        false,
+                    ))
+                }
+                TransitionStep::Continue => (),
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                let errors = self.read_axis_errors(axis).await?;
+                return Err(transition_error(axis, requested_state, state, errors, true));
+            }
+            sleep(transition.poll_interval.min(deadline - now)).await;
+        }
+    }
+}
+
+/// Firmware version, see `commands::ODrive::detect_firmware`.
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Reads the firmware and hardware versions of the ODrive, and adapts the following commands
+    /// to them. Returns `Unsupported` for firmware other than 0.4.x and 0.5.x.
+    pub async fn detect_firmware(&mut self) -> ODriveResult<Capabilities> {
+        let firmware = FirmwareVersion {
+            major: self.read_version("fw_version_major").await?,
+            minor: self.read_version("fw_version_minor").await?,
+            revision: self.read_version("fw_version_revision").await?,
+        };
+        let hardware = HardwareVersion {
+            major: self.read_version("hw_version_major").await?,
+            minor: self.read_version("hw_version_minor").await?,
+            variant: self.read_version("hw_version_variant").await?,
+        };
+
+        let capabilities = Capabilities::new(firmware, Some(hardware));
+        if !capabilities.is_supported() {
+            return Err(ODriveError::Unsupported(format!("firmware {}", firmware)));
+        }
+        self.capabilities = capabilities;
+        Ok(capabilities)
+    }
+
+    async fn read_version(&mut self, param: &str) -> ODriveResult<u8> {
+        let response = self.get_config_property(param).await?;
+        parse_response(response)
+    }
+}
+
+/// System commands and errors.
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Saves the current configuration of properties to the ODrives non-volatile memory.
+    pub async fn save_configuration(&mut self) -> ODriveResult<()> {
+        self.write_command("ss").await.map_err(ODriveError::Io)
+    }
+
+    /// Reset the current configuration to the factory default settings.
+    pub async fn erase_configuration(&mut self) -> ODriveResult<()> {
+        self.write_command("se").await.map_err(ODriveError::Io)
+    }
+
+    /// Clear errors.
+    pub async fn clear_errors(&mut self) -> ODriveResult<()> {
+        self.write_command("sc").await.map_err(ODriveError::Io)
+    }
+
+    /// Reboot ODrive.
+    pub async fn reboot(&mut self) -> ODriveResult<()> {
+        self.write_command("sr").await.map_err(ODriveError::Io)
+    }
+
+    async fn read_error_property(&mut self, param: &str) -> ODriveResult<u64> {
+        let response = self.get_config_property(param).await?;
+        parse_response(response)
+    }
+
+    /// Read the errors of an axis and its motor, encoder and controller.
+    pub async fn read_axis_errors(&mut self, axis: AxisID) -> ODriveResult<AxisErrorReport> {
+        let axis = axis as u8;
+        let axis_error = self
+            .read_error_property(&format!("axis{}.error", axis))
+            .await?;
+        let motor = self
+            .read_error_property(&format!("axis{}.motor.error", axis))
+            .await?;
+        let encoder = self
+            .read_error_property(&format!("axis{}.encoder.error", axis))
+            .await?;
+        let controller = self
+            .read_error_property(&format!("axis{}.controller.error", axis))
+            .await?;
+
+        Ok(AxisErrorReport::for_firmware(
+            axis_error,
+            motor,
+            encoder,
+            controller,
+            &self.capabilities,
+        ))
+    }
+
+    /// Read all errors reported by the ODrive.
+    pub async fn read_all_errors(&mut self) -> ODriveResult<ErrorReport> {
+        self.flush_input().await?;
+
+        Ok(ErrorReport {
+            system: self.read_error_property("error").await?,
+            axis0: self.read_axis_errors(AxisID::Zero).await?,
+            axis1: self.read_axis_errors(AxisID::One).await?,
+        })
+    }
+}
+
+/// Configuration, see the methods of the same name on `commands::ODrive`.
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    axis_properties! {
+        set_startup_motor_calibration, read_startup_motor_calibration:
+            "config.startup_motor_calibration" => flag bool;
+        set_startup_encoder_index_search, read_startup_encoder_index_search:
+            "config.startup_encoder_index_search" => flag bool;
+        set_startup_encoder_offset_calibration, read_startup_encoder_offset_calibration:
+            "config.startup_encoder_offset_calibration" => flag bool;
+        set_startup_closed_loop_control, read_startup_closed_loop_control:
+            "config.startup_closed_loop_control" => flag bool;
+        set_startup_sensorless_control, read_startup_sensorless_control:
+            "config.startup_sensorless_control" => flag bool;
+
+        set_motor_pole_pairs, read_motor_pole_pairs:
+            "motor.config.pole_pairs" => value u16;
+        set_motor_resistance_calib_max_voltage, read_motor_resistance_calib_max_voltage:
+            "motor.config.resistance_calib_max_voltage" => value f32;
+        set_motor_requested_current_range, read_motor_requested_current_range:
+            "motor.config.requested_current_range" => value f32;
+        set_motor_current_control_bandwidth, read_motor_current_control_bandwidth:
+            "motor.config.current_control_bandwidth" => value f32;
+        set_motor_pre_calibrated, read_motor_pre_calibrated:
+            "motor.config.pre_calibrated" => flag bool;
+
+        set_encoder_mode, read_encoder_mode:
+            "encoder.config.mode" => enumeration EncoderMode;
+        set_encoder_cpr, read_encoder_cpr:
+            "encoder.config.cpr" => value i32;
+        set_encoder_bandwidth, read_encoder_bandwidth:
+            "encoder.config.bandwidth" => value f32;
+        set_encoder_pre_calibrated, read_encoder_pre_calibrated:
+            "encoder.config.pre_calibrated" => flag bool;
+
+        set_position_gain, read_position_gain:
+            "controller.config.pos_gain" => value f32;
+        set_velocity_gain, read_velocity_gain:
+            "controller.config.vel_gain" => value f32;
+        set_velocity_integrator_gain, read_velocity_integrator_gain:
+            "controller.config.vel_integrator_gain" => value f32;
+        set_velocity_limit, read_velocity_limit:
+            "controller.config.vel_limit" => value f32;
+
+        set_watchdog_timeout, read_watchdog_timeout:
+            "config.watchdog_timeout" => value f32;
+        set_watchdog_enabled, read_watchdog_enabled:
+            "config.enable_watchdog" => flag bool;
+    }
+
+    /// Since firmware 0.5.0, `TrajectoryControl` selects position control with the trapezoidal
+    /// trajectory input mode.
+    pub async fn set_control_mode(&mut self, axis: AxisID, mode: ControlMode) -> ODriveResult<()> {
+        let value = self.capabilities.control_mode_value(mode);
+        self.set_axis_property(axis, "controller.config.control_mode", value)
+            .await?;
+        if mode == ControlMode::TrajectoryControl && self.capabilities.has_input_modes() {
+            self.set_axis_property(
+                axis,
+                "controller.config.input_mode",
+                InputMode::TrapTraj as u8,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn read_control_mode(&mut self, axis: AxisID) -> ODriveResult<ControlMode> {
+        let response = self
+            .get_axis_property(axis, "controller.config.control_mode")
+            .await?;
+        let value = parse_response(response)?;
+        self.capabilities.control_mode_from_value(value)
+    }
+
+    /// Writes the watchdog configuration of an axis.
+    /// The watchdog is fed before it is enabled, so that it does not expire right away. Disabling
+    /// the watchdog only writes `enable_watchdog`, so the timeout of the axis is kept.
+    pub async fn configure_watchdog(
+        &mut self,
+        axis: AxisID,
+        config: WatchdogConfig,
+    ) -> ODriveResult<()> {
+        if !config.enabled {
+            return self.set_watchdog_enabled(axis, false).await;
+        }
+        self.set_watchdog_timeout(axis, config.timeout.as_secs_f32())
+            .await?;
+        self.feed_watchdog(axis).await.map_err(ODriveError::Io)?;
+        self.set_watchdog_enabled(axis, true).await
+    }
+
+    pub async fn read_watchdog_config(&mut self, axis: AxisID) -> ODriveResult<WatchdogConfig> {
+        let enabled = self.read_watchdog_enabled(axis).await?;
+        let timeout = self.read_watchdog_timeout(axis).await?;
+        WatchdogConfig::from_properties(enabled, timeout)
+    }
+}
+
+// Implement private helper methods
+impl<T> AsyncODrive<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    async fn set_config_property<D: Display>(&mut self, param: &str, value: D) -> ODriveResult<()> {
+        let param = self.capabilities.property_path(param);
+        self.write_command(&format!("w {} {}", param, value))
+            .await
+            .map_err(ODriveError::Io)
+    }
+
+    async fn get_config_property(&mut self, param: &str) -> ODriveResult<String> {
+        let param = self.capabilities.property_path(param);
+        self.write_command(&format!("r {}", param))
+            .await
+            .map_err(ODriveError::Io)?;
+        self.read_odrive_response().await
+    }
+
+    async fn set_axis_property<D: Display>(
+        &mut self,
+        axis: AxisID,
+        property: &str,
+        value: D,
+    ) -> ODriveResult<()> {
+        let config = format!("axis{}.{}", axis as u8, property);
+        self.set_config_property(&config, value).await
+    }
+
+    async fn get_axis_property(&mut self, axis: AxisID, property: &str) -> ODriveResult<String> {
+        let config = format!("axis{}.{}", axis as u8, property);
+        self.get_config_property(&config).await
+    }
+}
@@ -0,0 +1,499 @@
+//! A command line tool for the ODrive, built with the `cli` feature.
+//!
+//! Every command prints its result as a JSON object on stdout. Failures are printed as a JSON
+//! object on stderr, and the exit code tells what failed: `EXIT_DEVICE_ERROR` for errors reported
+//! by the ODrive, `EXIT_USAGE` for invalid arguments and `EXIT_IO` for the serial port and files.
+
+use std::env::args;
+use std::fs;
+use std::io::{Read, Write};
+use std::process::exit;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use serialport::SerialPortSettings;
+
+use odrive_rs::commands::{ODrive, Transition};
+use odrive_rs::config::{ODriveConfig, OdrivetoolConfig};
+use odrive_rs::enumerations::errors::{AxisErrorReport, ErrorFlag, ErrorFlags, ODriveError};
+use odrive_rs::enumerations::{AxisID, AxisState};
+
+const USAGE: &str = "Usage: odrive [--port <path>] [--baud <rate>] <command>
+       odrive --help
+
+Commands:
+    get <path>              Read a property
+    set <path> <value>      Write a property
+    state <axis> <state>    Run a state, such as closed_loop_control, and wait for it
+    calibrate <axis>        Run the full calibration sequence
+    errors                  Read the errors of both axes, exiting with 1 if there are any
+    clear-errors            Clear the errors of both axes
+    save                    Save the configuration
+    erase                   Erase the configuration
+    reboot                  Reboot the ODrive
+    backup <file>           Write the configuration to a JSON file
+    restore <file>          Apply and save a configuration file, or roll it back on failure.
+                            Files of odrivetool backup-config are accepted too.";
+
+const DEFAULT_PORT: &str = "/dev/ttyACM0";
+const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+/// The time the full calibration sequence may take, including the encoder offset calibration.
+const CALIBRATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+const EXIT_DEVICE_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_IO: i32 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Get(String),
+    Set(String, String),
+    State(AxisID, AxisState),
+    Calibrate(AxisID),
+    Errors,
+    ClearErrors,
+    Save,
+    Erase,
+    Reboot,
+    Backup(String),
+    Restore(String),
+    /// Prints the usage on stdout, without connecting to the ODrive.
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    port: String,
+    baud_rate: u32,
+    command: Command,
+}
+
+#[derive(Debug)]
+enum Failure {
+    Usage(String),
+    Io(String),
+    Device(ODriveError),
+    /// The command succeeded but found errors on the ODrive, described by the value.
+    DeviceErrors(Value),
+}
+
+impl From<ODriveError> for Failure {
+    fn from(error: ODriveError) -> Self {
+        match error {
+            ODriveError::Io(error) => Failure::Io(error.to_string()),
+            error => Failure::Device(error),
+        }
+    }
+}
+
+impl Failure {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Usage(_) => EXIT_USAGE,
+            Failure::Io(_) => EXIT_IO,
+            Failure::Device(_) | Failure::DeviceErrors(_) => EXIT_DEVICE_ERROR,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            // The usage itself is only printed as text
+            Failure::Usage(message) if message == USAGE => json!({"error": "usage"}),
+            Failure::Usage(message) => json!({"error": "usage", "message": message}),
+            Failure::Io(message) => json!({"error": "io", "message": message}),
+            Failure::Device(ODriveError::ConfigApply(error)) => json!({
+                "error": "device",
+                "message": error.to_string(),
+                "path": error.path,
+                "applied": paths(&error.report.applied),
+                "rolled_back": paths(&error.report.rolled_back),
+                "not_rolled_back": error
+                    .rollback_errors
+                    .iter()
+                    .map(|(property, _)| property.path.as_str())
+                    .collect::<Vec<_>>(),
+            }),
+            Failure::Device(error) => json!({"error": "device", "message": error.to_string()}),
+            Failure::DeviceErrors(errors) => errors.clone(),
+        }
+    }
+}
+
+fn paths(properties: &[odrive_rs::config::ConfigProperty]) -> Vec<&str> {
+    properties
+        .iter()
+        .map(|property| property.path.as_str())
+        .collect()
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, Failure> {
+    let mut port = DEFAULT_PORT.to_owned();
+    let mut baud_rate = DEFAULT_BAUD_RATE;
+    let mut words = Vec::new();
+    let mut help = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" | "-p" => port = option_value(&mut args, &arg)?,
+            "--baud" | "-b" => {
+                let value = option_value(&mut args, &arg)?;
+                baud_rate = value
+                    .parse()
+                    .map_err(|_| Failure::Usage(format!("invalid baud rate {}", value)))?;
+            }
+            "--help" | "-h" => help = true,
+            _ => words.push(arg),
+        }
+    }
+
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let command = match words.as_slice() {
+        _ if help => Command::Help,
+        ["get", path] => Command::Get((*path).to_owned()),
+        ["set", path, value] => Command::Set((*path).to_owned(), (*value).to_owned()),
+        ["state", axis, state] => Command::State(parse_word(axis)?, parse_word(state)?),
+        ["calibrate", axis] => Command::Calibrate(parse_word(axis)?),
+        ["errors"] => Command::Errors,
+        ["clear-errors"] => Command::ClearErrors,
+        ["save"] => Command::Save,
+        ["erase"] => Command::Erase,
+        ["reboot"] => Command::Reboot,
+        ["backup", file] => Command::Backup((*file).to_owned()),
+        ["restore", file] => Command::Restore((*file).to_owned()),
+        _ => return Err(Failure::Usage(USAGE.to_owned())),
+    };
+
+    Ok(Options {
+        port,
+        baud_rate,
+        command,
+    })
+}
+
+fn option_value<I: Iterator<Item = String>>(args: &mut I, option: &str) -> Result<String, Failure> {
+    args.next()
+        .ok_or_else(|| Failure::Usage(format!("missing value for {}", option)))
+}
+
+fn parse_word<V: std::str::FromStr<Err = ODriveError>>(word: &str) -> Result<V, Failure> {
+    word.parse()
+        .map_err(|error: ODriveError| Failure::Usage(error.to_string()))
+}
+
+/// Converts a response of the ODrive to a JSON number if it is one.
+fn response_value(response: String) -> Value {
+    match serde_json::from_str(&response) {
+        Ok(Value::Number(number)) => Value::Number(number),
+        _ => Value::String(response),
+    }
+}
+
+fn flags_json<E: ErrorFlag + std::fmt::Debug>(flags: ErrorFlags<E>) -> Value {
+    let names: Vec<String> = flags.iter().map(|flag| format!("{:?}", flag)).collect();
+    json!({"bits": flags.bits(), "flags": names})
+}
+
+fn axis_errors_json(report: &AxisErrorReport) -> Value {
+    json!({
+        "axis": flags_json(report.axis),
+        "motor": flags_json(report.motor),
+        "encoder": flags_json(report.encoder),
+        "controller": flags_json(report.controller),
+    })
+}
+
+fn read_config_file(file: &str) -> Result<ConfigFile, Failure> {
+    let text = fs::read_to_string(file).map_err(|error| Failure::Io(error.to_string()))?;
+    if let Ok(config) = serde_json::from_str::<ODriveConfig>(&text) {
+        return Ok(ConfigFile::ODrive(config));
+    }
+    Ok(ConfigFile::Odrivetool(OdrivetoolConfig::from_json(&text)?))
+}
+
+enum ConfigFile {
+    ODrive(ODriveConfig),
+    Odrivetool(OdrivetoolConfig),
+}
+
+/// The sequence can return to Idle without errors and still leave the axis unusable, so the
+/// calibration is only reported once the motor is calibrated and the encoder is ready.
+fn calibration_result(
+    axis: AxisID,
+    motor_calibrated: bool,
+    encoder_ready: bool,
+) -> Result<Value, Failure> {
+    let result = json!({
+        "axis": axis as u8,
+        "calibrated": motor_calibrated && encoder_ready,
+        "motor_calibrated": motor_calibrated,
+        "encoder_ready": encoder_ready,
+    });
+    if motor_calibrated && encoder_ready {
+        Ok(result)
+    } else {
+        Err(Failure::DeviceErrors(result))
+    }
+}
+
+/// Runs a command and returns its result.
+fn run<T: Read + Write>(odrive: &mut ODrive<T>, command: &Command) -> Result<Value, Failure> {
+    let result = match command {
+        Command::Get(path) => {
+            let response: String = odrive.read_property(path)?;
+            json!({"path": path, "value": response_value(response)})
+        }
+        Command::Set(path, value) => {
+            let response: String = odrive.write_property_read_back(path, value)?;
+            json!({"path": path, "value": response_value(response)})
+        }
+        Command::State(axis, state) => {
+            odrive.run_state(*axis, *state, Transition::for_state(*state))?;
+            let current = odrive.current_state(*axis)?;
+            json!({"axis": *axis as u8, "state": format!("{:?}", current)})
+        }
+        Command::Calibrate(axis) => {
+            let state = AxisState::FullCalibrationSequence;
+            let transition = Transition::for_state(state).with_timeout(CALIBRATION_TIMEOUT);
+            odrive.run_state(*axis, state, transition)?;
+            let motor_calibrated = odrive.axis(*axis).motor().is_calibrated().get()?;
+            let encoder_ready = odrive.axis(*axis).encoder().is_ready().get()?;
+            calibration_result(*axis, motor_calibrated, encoder_ready)?
+        }
+        Command::Errors => {
+            let report = odrive.read_all_errors()?;
+            let result = json!({
+                "system": report.system,
+                "axis0": axis_errors_json(&report.axis0),
+                "axis1": axis_errors_json(&report.axis1),
+            });
+            if !report.is_empty() {
+                return Err(Failure::DeviceErrors(result));
+            }
+            result
+        }
+        Command::ClearErrors => {
+            odrive.clear_errors()?;
+            json!({})
+        }
+        Command::Save => {
+            odrive.save_configuration()?;
+            json!({})
+        }
+        Command::Erase => {
+            odrive.erase_configuration()?;
+            json!({})
+        }
+        Command::Reboot => {
+            odrive.reboot()?;
+            json!({})
+        }
+        Command::Backup(file) => {
+            let config = odrive.backup_config()?;
+            let text = serde_json::to_string_pretty(&config)
+                .map_err(|error| Failure::Io(error.to_string()))?;
+            fs::write(file, text).map_err(|error| Failure::Io(error.to_string()))?;
+            json!({"file": file})
+        }
+        Command::Restore(file) => {
+            let properties = match read_config_file(file)? {
+                ConfigFile::ODrive(config) => config.properties(&odrive.capabilities())?,
+                ConfigFile::Odrivetool(config) => {
+                    if !config.unknown_keys.is_empty() {
+                        return Err(Failure::Device(ODriveError::InvalidConfig(format!(
+                            "unknown keys {}",
+                            config.unknown_keys.join(", ")
+                        ))));
+                    }
+                    config.properties
+                }
+            };
+            let report = odrive.apply_config(&properties, true)?;
+            json!({"file": file, "applied": paths(&report.applied)})
+        }
+        Command::Help => json!({"usage": USAGE}),
+    };
+    Ok(result)
+}
+
+fn open(options: &Options) -> Result<ODrive<Box<dyn serialport::SerialPort>>, Failure> {
+    let settings = SerialPortSettings {
+        baud_rate: options.baud_rate,
+        ..Default::default()
+    };
+    let serial = serialport::open_with_settings(&options.port, &settings)
+        .map_err(|error| Failure::Io(format!("{}: {}", options.port, error)))?;
+
+    let mut odrive = ODrive::new(serial);
+    odrive.detect_firmware()?;
+    Ok(odrive)
+}
+
+fn main() {
+    let result = parse_args(args().skip(1)).and_then(|options| {
+        if options.command == Command::Help {
+            return Ok(None);
+        }
+        let mut odrive = open(&options)?;
+        run(&mut odrive, &options.command).map(Some)
+    });
+
+    match result {
+        Ok(Some(value)) => println!("{}", value),
+        Ok(None) => println!("{}", USAGE),
+        Err(failure) => {
+            if let Failure::Usage(message) = &failure {
+                eprintln!("{}", message);
+            }
+            eprintln!("{}", failure.to_json());
+            exit(failure.exit_code());
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "simulator")]
+mod tests {
+    use super::*;
+    use odrive_rs::simulator::SimulatedODrive;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(str::to_owned)
+    }
+
+    fn run_line(odrive: &mut ODrive<SimulatedODrive>, line: &str) -> Result<Value, Failure> {
+        let options = parse_args(args(line))?;
+        run(odrive, &options.command)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            Options {
+                port: "/dev/ttyUSB1".to_owned(),
+                baud_rate: 921_600,
+                command: Command::State(AxisID::One, AxisState::ClosedLoopControl),
+            },
+            parse_args(args(
+                "--port /dev/ttyUSB1 state 1 closed_loop_control -b 921600"
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            Command::Set("axis0.motor.config.pole_pairs".to_owned(), "15".to_owned()),
+            parse_args(args("set axis0.motor.config.pole_pairs 15"))
+                .unwrap()
+                .command
+        );
+        assert_eq!(DEFAULT_PORT, parse_args(args("errors")).unwrap().port);
+        for line in &["--help", "-p /dev/ttyUSB1 get -h", "save --help"] {
+            assert_eq!(Command::Help, parse_args(args(line)).unwrap().command);
+        }
+
+        for line in &[
+            "",
+            "get",
+            "state 2 idle",
+            "state 0 running",
+            "--baud fast save",
+        ] {
+            match parse_args(args(line)) {
+                Err(failure @ Failure::Usage(_)) => assert_eq!(EXIT_USAGE, failure.exit_code()),
+                result => panic!("unexpected result {:?} for {:?}", result, line),
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let mut odrive = ODrive::new(SimulatedODrive::new());
+        assert_eq!(
+            json!({"path": "vbus_voltage", "value": 24.0}),
+            run_line(&mut odrive, "get vbus_voltage").unwrap()
+        );
+        assert_eq!(
+            json!({"path": "axis0.motor.config.pole_pairs", "value": 15}),
+            run_line(&mut odrive, "set axis0.motor.config.pole_pairs 15").unwrap()
+        );
+        // The value is read back once, as part of the write
+        let reads = odrive
+            .get_ref()
+            .commands()
+            .iter()
+            .filter(|command| *command == "r axis0.motor.config.pole_pairs")
+            .count();
+        assert_eq!(1, reads);
+
+        let failure = run_line(&mut odrive, "get axis0.missing").unwrap_err();
+        assert_eq!(EXIT_DEVICE_ERROR, failure.exit_code());
+        assert_eq!("device", failure.to_json()["error"]);
+    }
+
+    #[test]
+    fn test_calibrate_and_errors() {
+        let mut odrive = ODrive::new(SimulatedODrive::new());
+        assert_eq!(
+            json!({"axis": 0, "calibrated": true, "motor_calibrated": true, "encoder_ready": true}),
+            run_line(&mut odrive, "calibrate 0").unwrap()
+        );
+        let failure = calibration_result(AxisID::One, true, false).unwrap_err();
+        assert_eq!(EXIT_DEVICE_ERROR, failure.exit_code());
+        assert_eq!(json!(false), failure.to_json()["calibrated"]);
+        assert_eq!(json!(false), failure.to_json()["encoder_ready"]);
+        assert_eq!(
+            json!({"axis": 0, "state": "ClosedLoopControl"}),
+            run_line(&mut odrive, "state 0 closed_loop_control").unwrap()
+        );
+        let errors = run_line(&mut odrive, "errors").unwrap();
+        assert_eq!(json!([]), errors["axis0"]["motor"]["flags"]);
+
+        // Closed loop control fails on the uncalibrated axis
+        let failure = run_line(&mut odrive, "state 1 closed_loop_control").unwrap_err();
+        assert_eq!(EXIT_DEVICE_ERROR, failure.exit_code());
+        let failure = run_line(&mut odrive, "errors").unwrap_err();
+        assert_eq!(EXIT_DEVICE_ERROR, failure.exit_code());
+        assert_eq!(
+            json!(["ErrorInvalidState"]),
+            failure.to_json()["axis1"]["axis"]["flags"]
+        );
+
+        run_line(&mut odrive, "clear-errors").unwrap();
+        run_line(&mut odrive, "errors").unwrap();
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let file =
+            std::env::temp_dir().join(format!("odrive-cli-test-{}.json", std::process::id()));
+        let file = file.to_str().unwrap();
+
+        let mut source = ODrive::new(SimulatedODrive::new());
+        run_line(&mut source, "set axis1.encoder.config.cpr 90").unwrap();
+        run_line(&mut source, &format!("backup {}", file)).unwrap();
+
+        let mut target = ODrive::new(SimulatedODrive::new());
+        let result = run_line(&mut target, &format!("restore {}", file)).unwrap();
+        assert_eq!(66, result["applied"].as_array().unwrap().len());
+        assert_eq!(
+            source.backup_config().unwrap(),
+            target.backup_config().unwrap()
+        );
+        assert!(target
+            .get_ref()
+            .commands()
+            .iter()
+            .any(|command| command == "ss"));
+
+        fs::write(
+            file,
+            r#"{"axis0.motor.config.pole_pairs": 15, "axis0.unknown": 1}"#,
+        )
+        .unwrap();
+        let failure = run_line(&mut target, &format!("restore {}", file)).unwrap_err();
+        assert_eq!(EXIT_DEVICE_ERROR, failure.exit_code());
+        fs::remove_file(file).unwrap();
+
+        let failure = run_line(&mut target, &format!("restore {}", file)).unwrap_err();
+        assert_eq!(EXIT_IO, failure.exit_code());
+    }
+}
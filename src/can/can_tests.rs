@@ -0,0 +1,482 @@
+use super::*;
+use crate::commands::{Transition, TransitionEnd};
+use crate::enumerations::errors::AxisError;
+use crate::enumerations::{AxisID, AxisState, ControlMode, InputMode};
+use crate::interface::ODriveInterface;
+use crate::units::{AxisScaling, Counts, ScaledFeedback};
+use std::collections::VecDeque;
+
+/// A CAN bus which records sent frames and returns queued frames one at a time.
+#[derive(Debug, Default)]
+struct MockCanBus {
+    sent: Vec<CanFrame>,
+    received: VecDeque<CanFrame>,
+}
+
+impl MockCanBus {
+    fn push(&mut self, node_id: u8, message: CanMessage) {
+        let frame = message.encode(NodeId::new(node_id).unwrap());
+        self.received.push_back(frame);
+    }
+
+    fn sent_messages(&self) -> Vec<(u8, CanMessage)> {
+        self.sent
+            .iter()
+            .map(|frame| {
+                let (node_id, message) = CanMessage::decode(frame).unwrap();
+                (node_id.value(), message)
+            })
+            .collect()
+    }
+}
+
+impl CanTransport for MockCanBus {
+    fn send(&mut self, frame: &CanFrame) -> io::Result<()> {
+        self.sent.push(*frame);
+        Ok(())
+    }
+
+    fn receive(&mut self, _timeout: Duration) -> io::Result<Option<CanFrame>> {
+        Ok(self.received.pop_front())
+    }
+}
+
+fn init_odrive() -> CanODrive<MockCanBus> {
+    CanODrive::new(MockCanBus::default())
+}
+
+#[test]
+fn test_node_id_range() {
+    assert_eq!(Some(63), NodeId::new(63).map(NodeId::value));
+    assert_eq!(None, NodeId::new(64));
+    match NodeId::try_from(64) {
+        Err(ODriveError::InvalidEnumValue { enumeration, value }) => {
+            assert_eq!("NodeId", enumeration);
+            assert_eq!("64", value);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_arbitration_id() {
+    let node_id = NodeId::new(3).unwrap();
+    assert_eq!(0x06D, arbitration_id(node_id, CommandId::SetInputVel));
+    assert_eq!(
+        (node_id, CommandId::SetInputVel),
+        split_arbitration_id(0x06D).unwrap()
+    );
+    assert!(split_arbitration_id(0x07F).is_err());
+}
+
+#[test]
+fn test_command_id_conversion() {
+    assert_eq!(
+        CommandId::GetControllerError,
+        CommandId::try_from(0x1D).unwrap()
+    );
+    assert!(CommandId::try_from(0).is_err());
+    assert!(CommandId::try_from(0x1E).is_err());
+}
+
+#[test]
+fn test_frame_limits() {
+    assert!(CanFrame::new(0x7FF, &[0; 8]).is_some());
+    assert!(CanFrame::new(0x800, &[]).is_none());
+    assert!(CanFrame::new(0x001, &[0; 9]).is_none());
+    let frame = CanFrame::remote(0x009).unwrap();
+    assert!(frame.is_remote());
+    assert!(frame.data().is_empty());
+}
+
+#[test]
+fn test_encode_set_input_vel() {
+    let frame = CanMessage::SetInputVel {
+        velocity: 1.5,
+        torque_feed_forward: -0.25,
+    }
+    .encode(NodeId::new(1).unwrap());
+    assert_eq!(0x02D, frame.id());
+    assert!(!frame.is_remote());
+    assert_eq!(
+        &[0x00, 0x00, 0xC0, 0x3F, 0x00, 0x00, 0x80, 0xBE],
+        frame.data()
+    );
+}
+
+#[test]
+fn test_encode_set_input_pos_feed_forward() {
+    let frame = CanMessage::SetInputPos {
+        position: 2.0,
+        velocity_feed_forward: 1.5,
+        torque_feed_forward: -100.0,
+    }
+    .encode(NodeId::new(0).unwrap());
+    assert_eq!(
+        &[0x00, 0x00, 0x00, 0x40, 0xDC, 0x05, 0x00, 0x80],
+        frame.data()
+    );
+}
+
+#[test]
+fn test_encode_requests() {
+    let frame = CanMessage::Request(CommandId::GetEncoderEstimates).encode(NodeId::new(2).unwrap());
+    assert_eq!(0x049, frame.id());
+    assert!(frame.is_remote());
+
+    let frame = CanMessage::ClearErrors.encode(NodeId::new(2).unwrap());
+    assert_eq!(0x058, frame.id());
+    assert!(!frame.is_remote());
+    assert!(frame.data().is_empty());
+}
+
+#[test]
+fn test_message_round_trip() {
+    let messages = [
+        CanMessage::Request(CommandId::GetVbusVoltage),
+        CanMessage::Heartbeat {
+            axis_error: 0x800,
+            axis_state: AxisState::ClosedLoopControl,
+        },
+        CanMessage::SetAxisState(AxisState::FullCalibrationSequence),
+        CanMessage::SetControllerModes {
+            control_mode: ControlMode::VelocityControl,
+            input_mode: InputMode::VelRamp,
+        },
+        CanMessage::EncoderEstimates {
+            position: 10.25,
+            velocity: -3.5,
+        },
+        CanMessage::SetInputPos {
+            position: -1.0,
+            velocity_feed_forward: 0.5,
+            torque_feed_forward: 0.125,
+        },
+        CanMessage::SetInputVel {
+            velocity: 4.0,
+            torque_feed_forward: 0.0,
+        },
+        CanMessage::SetInputTorque(0.75),
+        CanMessage::SetLimits {
+            velocity_limit: 20.0,
+            current_limit: 10.0,
+        },
+        CanMessage::VbusVoltage(24.5),
+        CanMessage::MotorError(0x1_0000_0001),
+        CanMessage::EncoderError(0x4),
+        CanMessage::ControllerError(0x1),
+        CanMessage::ClearErrors,
+        CanMessage::Reboot,
+    ];
+    let node_id = NodeId::new(7).unwrap();
+    for message in messages.iter() {
+        let frame = message.encode(node_id);
+        assert_eq!((node_id, *message), CanMessage::decode(&frame).unwrap());
+    }
+}
+
+#[test]
+fn test_decode_short_frame() {
+    let frame = CanFrame::new(0x009, &[0, 0, 0, 0]).unwrap();
+    match CanMessage::decode(&frame) {
+        Err(ODriveError::InvalidMessageReceived(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_32_bit_motor_error() {
+    let frame = CanFrame::new(0x003, &[0x01, 0, 0, 0]).unwrap();
+    assert_eq!(
+        CanMessage::MotorError(1),
+        CanMessage::decode(&frame).unwrap().1
+    );
+}
+
+#[test]
+fn test_decode_unsupported_command() {
+    let frame = CanFrame::new(0x014, &[0; 8]).unwrap();
+    assert!(CanMessage::decode(&frame).is_err());
+}
+
+#[test]
+fn test_set_velocity_sends_control_mode_once() {
+    let mut odrive = init_odrive();
+    ODriveInterface::set_velocity(&mut odrive, AxisID::One, 2.0, None).unwrap();
+    ODriveInterface::set_velocity(&mut odrive, AxisID::One, 3.0, Some(0.5)).unwrap();
+    assert_eq!(
+        vec![
+            (
+                1,
+                CanMessage::SetControllerModes {
+                    control_mode: ControlMode::VelocityControl,
+                    input_mode: InputMode::Passthrough,
+                }
+            ),
+            (
+                1,
+                CanMessage::SetInputVel {
+                    velocity: 2.0,
+                    torque_feed_forward: 0.0,
+                }
+            ),
+            (
+                1,
+                CanMessage::SetInputVel {
+                    velocity: 3.0,
+                    torque_feed_forward: 0.5,
+                }
+            ),
+        ],
+        odrive.get_ref().sent_messages()
+    );
+}
+
+#[test]
+fn test_set_current_switches_control_mode() {
+    let mut odrive = CanODrive::with_node_ids(
+        MockCanBus::default(),
+        [NodeId::new(4).unwrap(), NodeId::new(5).unwrap()],
+    );
+    odrive.set_input_vel(AxisID::Zero, 1.0, 0.0).unwrap();
+    ODriveInterface::set_current(&mut odrive, AxisID::Zero, 0.3).unwrap();
+    let sent = odrive.get_ref().sent_messages();
+    assert_eq!(4, sent.len());
+    assert_eq!(
+        (
+            4,
+            CanMessage::SetControllerModes {
+                control_mode: ControlMode::CurrentControl,
+                input_mode: InputMode::Passthrough,
+            }
+        ),
+        sent[2]
+    );
+    assert_eq!((4, CanMessage::SetInputTorque(0.3)), sent[3]);
+}
+
+#[test]
+fn test_setpoint_keeps_input_mode() {
+    let mut odrive = init_odrive();
+    odrive
+        .set_controller_modes(
+            AxisID::Zero,
+            ControlMode::PositionControl,
+            InputMode::TrapTraj,
+        )
+        .unwrap();
+    odrive.set_input_pos(AxisID::Zero, 2.0, 0.0, 0.0).unwrap();
+    odrive.set_input_vel(AxisID::Zero, 1.0, 0.0).unwrap();
+    let sent = odrive.get_ref().sent_messages();
+    assert_eq!(
+        vec![
+            (
+                0,
+                CanMessage::SetControllerModes {
+                    control_mode: ControlMode::PositionControl,
+                    input_mode: InputMode::TrapTraj,
+                }
+            ),
+            (
+                0,
+                CanMessage::SetInputPos {
+                    position: 2.0,
+                    velocity_feed_forward: 0.0,
+                    torque_feed_forward: 0.0,
+                }
+            ),
+            (
+                0,
+                CanMessage::SetControllerModes {
+                    control_mode: ControlMode::VelocityControl,
+                    input_mode: InputMode::Passthrough,
+                }
+            ),
+            (
+                0,
+                CanMessage::SetInputVel {
+                    velocity: 1.0,
+                    torque_feed_forward: 0.0,
+                }
+            ),
+        ],
+        sent
+    );
+}
+
+#[test]
+fn test_request_skips_other_frames() {
+    let mut odrive = init_odrive();
+    let bus = odrive.get_mut();
+    bus.push(1, CanMessage::VbusVoltage(12.0));
+    bus.push(
+        0,
+        CanMessage::Heartbeat {
+            axis_error: 0,
+            axis_state: AxisState::Idle,
+        },
+    );
+    bus.received
+        .push_back(CanFrame::new(0x7E0, &[1, 2, 3]).unwrap());
+    bus.push(0, CanMessage::VbusVoltage(24.0));
+
+    assert_eq!(24.0, odrive.read_vbus_voltage(AxisID::Zero).unwrap());
+    assert_eq!(
+        vec![(0, CanMessage::Request(CommandId::GetVbusVoltage))],
+        odrive.get_ref().sent_messages()
+    );
+}
+
+#[test]
+fn test_request_timeout() {
+    let mut odrive = init_odrive();
+    match odrive.read_encoder_estimates(AxisID::Zero) {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_feedback() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().push(
+        1,
+        CanMessage::EncoderEstimates {
+            position: 1.25,
+            velocity: 0.5,
+        },
+    );
+    let feedback = ODriveInterface::get_feedback(&mut odrive, AxisID::One).unwrap();
+    assert_eq!((1.25, 0.5), (feedback.position, feedback.velocity));
+}
+
+#[test]
+fn test_get_scaled_feedback() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().push(
+        0,
+        CanMessage::EncoderEstimates {
+            position: 1.25,
+            velocity: 0.5,
+        },
+    );
+    // Positions are always in turns over CAN
+    let feedback: ScaledFeedback<Counts> = odrive
+        .get_scaled_feedback(AxisID::Zero, &AxisScaling::new(8192.0))
+        .unwrap();
+    assert_eq!(Counts(10240.0), feedback.position);
+    assert_eq!(Counts(4096.0), feedback.velocity);
+}
+
+#[test]
+fn test_read_axis_errors() {
+    let mut odrive = init_odrive();
+    let bus = odrive.get_mut();
+    bus.push(
+        1,
+        CanMessage::Heartbeat {
+            axis_error: 0x800,
+            axis_state: AxisState::Idle,
+        },
+    );
+    bus.push(1, CanMessage::MotorError(0x2));
+    bus.push(1, CanMessage::EncoderError(0x0));
+    bus.push(1, CanMessage::ControllerError(0x1));
+
+    let errors = ODriveInterface::read_axis_errors(&mut odrive, AxisID::One).unwrap();
+    // The bits of firmware 0.5 are not decoded with the flags of 0.4
+    assert_eq!(0x800, errors.axis.bits());
+    assert!(!errors.axis.contains(AxisError::ErrorWatchdogTimerExpired));
+    assert_eq!(0x800, errors.axis.unknown_bits());
+    assert_eq!(0x2, errors.motor.bits());
+    assert!(errors.encoder.is_empty());
+    assert_eq!(0x1, errors.controller.bits());
+}
+
+#[test]
+fn test_run_state() {
+    let mut odrive = init_odrive();
+    let heartbeat = |axis_state| CanMessage::Heartbeat {
+        axis_error: 0,
+        axis_state,
+    };
+    let bus = odrive.get_mut();
+    bus.push(0, heartbeat(AxisState::Idle));
+    // The errors are read while the axis is still idle
+    bus.push(0, heartbeat(AxisState::Idle));
+    bus.push(0, CanMessage::MotorError(0));
+    bus.push(0, CanMessage::EncoderError(0));
+    bus.push(0, CanMessage::ControllerError(0));
+    bus.push(0, heartbeat(AxisState::ClosedLoopControl));
+
+    let transition = Transition::new(TransitionEnd::State(AxisState::ClosedLoopControl))
+        .with_poll_interval(Duration::from_millis(1));
+    odrive
+        .run_state(AxisID::Zero, AxisState::ClosedLoopControl, transition)
+        .unwrap();
+    assert_eq!(
+        (0, CanMessage::SetAxisState(AxisState::ClosedLoopControl)),
+        odrive.get_ref().sent_messages()[0]
+    );
+}
+
+#[test]
+fn test_clear_errors_and_reboot() {
+    let mut odrive = init_odrive();
+    ODriveInterface::clear_errors(&mut odrive).unwrap();
+    odrive.reboot(AxisID::One).unwrap();
+    assert_eq!(
+        vec![
+            (0, CanMessage::ClearErrors),
+            (1, CanMessage::ClearErrors),
+            (1, CanMessage::Reboot),
+        ],
+        odrive.get_ref().sent_messages()
+    );
+}
+
+/// These tests need a virtual CAN interface:
+/// `ip link add dev vcan0 type vcan && ip link set up vcan0`
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+mod socketcan_tests {
+    use super::*;
+
+    #[test]
+    #[ignore]
+    fn test_vcan_round_trip() {
+        let mut sender = SocketCan::open("vcan0").unwrap();
+        let mut receiver = SocketCan::open("vcan0").unwrap();
+        let frames = [
+            CanMessage::SetInputTorque(0.5).encode(NodeId::new(3).unwrap()),
+            CanMessage::Request(CommandId::Heartbeat).encode(NodeId::new(3).unwrap()),
+        ];
+        for frame in frames.iter() {
+            sender.send(frame).unwrap();
+            assert_eq!(
+                Some(*frame),
+                receiver.receive(Duration::from_secs(1)).unwrap()
+            );
+        }
+        assert_eq!(None, receiver.receive(Duration::from_millis(10)).unwrap());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_vcan_request() {
+        let mut odrive = CanODrive::new(SocketCan::open("vcan0").unwrap());
+        let mut device = SocketCan::open("vcan0").unwrap();
+        let handle = std::thread::spawn(move || {
+            let request = device.receive(Duration::from_secs(1)).unwrap().unwrap();
+            assert!(request.is_remote());
+            let reply = CanMessage::VbusVoltage(24.0).encode(NodeId::new(0).unwrap());
+            device.send(&reply).unwrap();
+        });
+        assert_eq!(24.0, odrive.read_vbus_voltage(AxisID::Zero).unwrap());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_open_missing_interface() {
+        assert!(SocketCan::open("nonexistent0").is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use std::time::{Duration, Instant};
+
+use crate::can::{CanMessage, CanTransport, CommandId, NodeId};
+use crate::commands::{Feedback, PositionUnit, DEFAULT_TIMEOUT};
+use crate::enumerations::errors::{
+    AxisErrorReport, AxisErrors, ControllerErrors, EncoderErrors, MotorErrors, ODriveError,
+    ODriveResult,
+};
+use crate::enumerations::{AxisID, AxisState, ControlMode, InputMode};
+use crate::interface::ODriveInterface;
+use crate::units::{AxisScaling, ScaledFeedback, Unit};
+
+/// The `CanODrive` struct manages both axes of an ODrive over the CAN Simple protocol.
+///
+/// Each axis is addressed by its node ID, which is 0 for axis 0 and 1 for axis 1 unless
+/// configured otherwise with `with_node_ids`.
+#[derive(Debug)]
+pub struct CanODrive<T>
+where
+    T: CanTransport,
+{
+    transport: T,
+    node_ids: [NodeId; 2],
+    timeout: Duration,
+    /// The control mode last set on each axis, so that it is only sent when it changes.
+    control_modes: [Option<ControlMode>; 2],
+}
+
+impl<T> CanODrive<T>
+where
+    T: CanTransport,
+{
+    pub fn new(transport: T) -> Self {
+        Self::with_node_ids(transport, [NodeId(0), NodeId(1)])
+    }
+
+    /// Creates a `CanODrive` whose axes use the given node IDs, in the order axis 0, axis 1.
+    pub fn with_node_ids(transport: T, node_ids: [NodeId; 2]) -> Self {
+        Self {
+            transport,
+            node_ids,
+            timeout: DEFAULT_TIMEOUT,
+            control_modes: [None, None],
+        }
+    }
+
+    pub fn node_id(&self, axis: AxisID) -> NodeId {
+        self.node_ids[axis as usize]
+    }
+
+    /// The time to wait for the reply to a request.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Gets a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Gets a mutable reference to the underlying transport.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Sends a message to an axis.
+    pub fn send(&mut self, axis: AxisID, message: &CanMessage) -> ODriveResult<()> {
+        let frame = message.encode(self.node_id(axis));
+        self.transport.send(&frame).map_err(ODriveError::Io)
+    }
+
+    /// Requests a message from an axis and waits for the reply.
+    /// Frames of other nodes and commands received in the meantime are discarded.
+    pub fn request(&mut self, axis: AxisID, command: CommandId) -> ODriveResult<CanMessage> {
+        let node_id = self.node_id(axis);
+        self.send(axis, &CanMessage::Request(command))?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let frame = match self.transport.receive(remaining) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Err(ODriveError::NoMessageReceived),
+                Err(error) => return Err(ODriveError::Io(error)),
+            };
+
+            if let Ok((id, message)) = CanMessage::decode(&frame) {
+                if id == node_id && message.command() == command {
+                    if let CanMessage::Request(_) = message {
+                        continue;
+                    }
+                    return Ok(message);
+                }
+            }
+        }
+    }
+
+    /// Reads the axis error and the current state from the heartbeat of an axis.
+    /// The heartbeat is requested, but also sent periodically by the ODrive, so firmware which
+    /// ignores the request still answers within the heartbeat interval as long as the timeout is
+    /// longer than `<axis>.config.can_heartbeat_rate_ms`.
+    pub fn read_heartbeat(&mut self, axis: AxisID) -> ODriveResult<(u32, AxisState)> {
+        match self.request(axis, CommandId::Heartbeat)? {
+            CanMessage::Heartbeat {
+                axis_error,
+                axis_state,
+            } => Ok((axis_error, axis_state)),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Sets the control mode of an axis, and its input mode to passthrough.
+    pub fn set_control_mode(
+        &mut self,
+        axis: AxisID,
+        control_mode: ControlMode,
+    ) -> ODriveResult<()> {
+        self.set_controller_modes(axis, control_mode, InputMode::Passthrough)
+    }
+
+    /// Sets the control mode and the input mode of an axis.
+    /// The setpoint methods keep the input mode as long as the control mode matches their own,
+    /// for example `set_input_pos` follows trajectories after selecting `InputMode::TrapTraj`.
+    pub fn set_controller_modes(
+        &mut self,
+        axis: AxisID,
+        control_mode: ControlMode,
+        input_mode: InputMode,
+    ) -> ODriveResult<()> {
+        self.send(
+            axis,
+            &CanMessage::SetControllerModes {
+                control_mode,
+                input_mode,
+            },
+        )?;
+        self.control_modes[axis as usize] = Some(control_mode);
+        Ok(())
+    }
+
+    fn ensure_control_mode(&mut self, axis: AxisID, control_mode: ControlMode) -> ODriveResult<()> {
+        if self.control_modes[axis as usize] != Some(control_mode) {
+            self.set_control_mode(axis, control_mode)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the position setpoint in turns, switching the axis to position control.
+    pub fn set_input_pos(
+        &mut self,
+        axis: AxisID,
+        position: f32,
+        velocity_feed_forward: f32,
+        torque_feed_forward: f32,
+    ) -> ODriveResult<()> {
+        self.ensure_control_mode(axis, ControlMode::PositionControl)?;
+        self.send(
+            axis,
+            &CanMessage::SetInputPos {
+                position,
+                velocity_feed_forward,
+                torque_feed_forward,
+            },
+        )
+    }
+
+    /// Sets the velocity setpoint in turns/s, switching the axis to velocity control.
+    pub fn set_input_vel(
+        &mut self,
+        axis: AxisID,
+        velocity: f32,
+        torque_feed_forward: f32,
+    ) -> ODriveResult<()> {
+        self.ensure_control_mode(axis, ControlMode::VelocityControl)?;
+        self.send(
+            axis,
+            &CanMessage::SetInputVel {
+                velocity,
+                torque_feed_forward,
+            },
+        )
+    }
+
+    /// Sets the torque setpoint in Nm, switching the axis to torque control.
+    pub fn set_input_torque(&mut self, axis: AxisID, torque: f32) -> ODriveResult<()> {
+        self.ensure_control_mode(axis, ControlMode::CurrentControl)?;
+        self.send(axis, &CanMessage::SetInputTorque(torque))
+    }
+
+    /// Sets the velocity limit in turns/s and the current limit in A.
+    pub fn set_limits(
+        &mut self,
+        axis: AxisID,
+        velocity_limit: f32,
+        current_limit: f32,
+    ) -> ODriveResult<()> {
+        self.send(
+            axis,
+            &CanMessage::SetLimits {
+                velocity_limit,
+                current_limit,
+            },
+        )
+    }
+
+    /// Reads the position and velocity estimates of an axis, in turns and turns/s.
+    pub fn read_encoder_estimates(&mut self, axis: AxisID) -> ODriveResult<Feedback> {
+        match self.request(axis, CommandId::GetEncoderEstimates)? {
+            CanMessage::EncoderEstimates { position, velocity } => {
+                Ok(Feedback { position, velocity })
+            }
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Same as `read_encoder_estimates`, but converts the estimates with `scaling`, see `units`.
+    pub fn get_scaled_feedback<U: Unit>(
+        &mut self,
+        axis: AxisID,
+        scaling: &AxisScaling,
+    ) -> ODriveResult<ScaledFeedback<U>> {
+        let feedback = self.read_encoder_estimates(axis)?;
+        ScaledFeedback::from_native(feedback, scaling, PositionUnit::Turns)
+    }
+
+    pub fn read_vbus_voltage(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        match self.request(axis, CommandId::GetVbusVoltage)? {
+            CanMessage::VbusVoltage(voltage) => Ok(voltage),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Reboots the ODrive the axis belongs to.
+    pub fn reboot(&mut self, axis: AxisID) -> ODriveResult<()> {
+        self.send(axis, &CanMessage::Reboot)?;
+        self.control_modes = [None, None];
+        Ok(())
+    }
+
+    fn read_error_code(&mut self, axis: AxisID, command: CommandId) -> ODriveResult<u64> {
+        match self.request(axis, command)? {
+            CanMessage::MotorError(error) => Ok(error),
+            CanMessage::EncoderError(error) | CanMessage::ControllerError(error) => {
+                Ok(error as u64)
+            }
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+fn unexpected(message: CanMessage) -> ODriveError {
+    ODriveError::InvalidMessageReceived(format!("{:?}", message))
+}
+
+impl<T> ODriveInterface for CanODrive<T>
+where
+    T: CanTransport,
+{
+    /// Sets the velocity setpoint in turns/s. The feed forward term is a torque, in Nm.
+    fn set_velocity(
+        &mut self,
+        axis: AxisID,
+        velocity: f32,
+        current_feed_forward: Option<f32>,
+    ) -> ODriveResult<()> {
+        self.set_input_vel(axis, velocity, current_feed_forward.unwrap_or_default())
+    }
+
+    /// Sets the torque setpoint, in Nm.
+    fn set_current(&mut self, axis: AxisID, current: f32) -> ODriveResult<()> {
+        self.set_input_torque(axis, current)
+    }
+
+    fn get_feedback(&mut self, axis: AxisID) -> ODriveResult<Feedback> {
+        self.read_encoder_estimates(axis)
+    }
+
+    fn current_state(&mut self, axis: AxisID) -> ODriveResult<AxisState> {
+        self.read_heartbeat(axis).map(|(_, state)| state)
+    }
+
+    fn request_state(&mut self, axis: AxisID, requested_state: AxisState) -> ODriveResult<()> {
+        self.send(axis, &CanMessage::SetAxisState(requested_state))
+    }
+
+    fn read_axis_errors(&mut self, axis: AxisID) -> ODriveResult<AxisErrorReport> {
+        let (axis_error, _) = self.read_heartbeat(axis)?;
+        let motor = self.read_error_code(axis, CommandId::GetMotorError)?;
+        let encoder = self.read_error_code(axis, CommandId::GetEncoderError)?;
+        let controller = self.read_error_code(axis, CommandId::GetControllerError)?;
+
+        // CAN Simple is only supported from firmware 0.5, whose flags differ from this crate's
+        Ok(AxisErrorReport {
+            axis: AxisErrors::undecoded(axis_error as u64),
+            motor: MotorErrors::undecoded(motor),
+            encoder: EncoderErrors::undecoded(encoder),
+            controller: ControllerErrors::undecoded(controller),
+        })
+    }
+
+    /// Clears the errors of both axes.
+    fn clear_errors(&mut self) -> ODriveResult<()> {
+        self.send(AxisID::Zero, &CanMessage::ClearErrors)?;
+        self.send(AxisID::One, &CanMessage::ClearErrors)
+    }
+}
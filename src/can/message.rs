@@ -0,0 +1,227 @@
+use std::convert::{TryFrom, TryInto};
+
+use crate::can::{arbitration_id, split_arbitration_id, CanFrame, CommandId, NodeId};
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+use crate::enumerations::{AxisState, ControlMode, InputMode};
+
+/// The scale of the feed forward terms of `SetInputPos`, which are sent as 16 bit integers.
+const FEED_FORWARD_SCALE: f32 = 0.001;
+
+/// A message of the CAN Simple protocol.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CanMessage {
+    /// Requests a message from the ODrive. It is sent as a remote frame, and the ODrive answers
+    /// with the message of the same command ID.
+    Request(CommandId),
+    /// Sent periodically by every axis, and in reply to a request.
+    Heartbeat {
+        axis_error: u32,
+        axis_state: AxisState,
+    },
+    SetAxisState(AxisState),
+    SetControllerModes {
+        control_mode: ControlMode,
+        input_mode: InputMode,
+    },
+    /// The reply to a request for `GetEncoderEstimates`, in turns and turns/s.
+    EncoderEstimates {
+        position: f32,
+        velocity: f32,
+    },
+    /// Sets the position setpoint, in turns. The feed forward terms are sent with a resolution of
+    /// 0.001 turns/s and 0.001 Nm.
+    SetInputPos {
+        position: f32,
+        velocity_feed_forward: f32,
+        torque_feed_forward: f32,
+    },
+    /// Sets the velocity setpoint in turns/s and the torque feed forward in Nm.
+    SetInputVel {
+        velocity: f32,
+        torque_feed_forward: f32,
+    },
+    /// Sets the torque setpoint, in Nm.
+    SetInputTorque(f32),
+    /// Sets the velocity limit in turns/s and the current limit in A.
+    SetLimits {
+        velocity_limit: f32,
+        current_limit: f32,
+    },
+    /// The reply to a request for `GetVbusVoltage`.
+    VbusVoltage(f32),
+    /// The reply to a request for `GetMotorError`.
+    MotorError(u64),
+    /// The reply to a request for `GetEncoderError`.
+    EncoderError(u32),
+    /// The reply to a request for `GetControllerError`.
+    ControllerError(u32),
+    ClearErrors,
+    Reboot,
+}
+
+impl CanMessage {
+    /// The command ID of the frame carrying this message.
+    pub fn command(&self) -> CommandId {
+        match self {
+            CanMessage::Request(command) => *command,
+            CanMessage::Heartbeat { .. } => CommandId::Heartbeat,
+            CanMessage::SetAxisState(_) => CommandId::SetAxisRequestedState,
+            CanMessage::SetControllerModes { .. } => CommandId::SetControllerModes,
+            CanMessage::EncoderEstimates { .. } => CommandId::GetEncoderEstimates,
+            CanMessage::SetInputPos { .. } => CommandId::SetInputPos,
+            CanMessage::SetInputVel { .. } => CommandId::SetInputVel,
+            CanMessage::SetInputTorque(_) => CommandId::SetInputTorque,
+            CanMessage::SetLimits { .. } => CommandId::SetLimits,
+            CanMessage::VbusVoltage(_) => CommandId::GetVbusVoltage,
+            CanMessage::MotorError(_) => CommandId::GetMotorError,
+            CanMessage::EncoderError(_) => CommandId::GetEncoderError,
+            CanMessage::ControllerError(_) => CommandId::GetControllerError,
+            CanMessage::ClearErrors => CommandId::ClearErrors,
+            CanMessage::Reboot => CommandId::RebootODrive,
+        }
+    }
+
+    /// Encodes the message into a frame addressed to `node_id`.
+    pub fn encode(&self, node_id: NodeId) -> CanFrame {
+        let id = arbitration_id(node_id, self.command());
+        let mut data = Vec::with_capacity(8);
+        match *self {
+            CanMessage::Request(_) => {
+                return CanFrame::remote(id).expect("node and command IDs fit in 11 bits")
+            }
+            CanMessage::Heartbeat {
+                axis_error,
+                axis_state,
+            } => {
+                data.extend_from_slice(&axis_error.to_le_bytes());
+                data.extend_from_slice(&[axis_state as u8, 0, 0, 0]);
+            }
+            CanMessage::SetAxisState(state) => {
+                data.extend_from_slice(&(state as u32).to_le_bytes());
+            }
+            CanMessage::SetControllerModes {
+                control_mode,
+                input_mode,
+            } => {
+                data.extend_from_slice(&(control_mode as u32).to_le_bytes());
+                data.extend_from_slice(&(input_mode as u32).to_le_bytes());
+            }
+            CanMessage::EncoderEstimates { position, velocity } => {
+                data.extend_from_slice(&position.to_le_bytes());
+                data.extend_from_slice(&velocity.to_le_bytes());
+            }
+            CanMessage::SetInputPos {
+                position,
+                velocity_feed_forward,
+                torque_feed_forward,
+            } => {
+                data.extend_from_slice(&position.to_le_bytes());
+                data.extend_from_slice(&scale_feed_forward(velocity_feed_forward).to_le_bytes());
+                data.extend_from_slice(&scale_feed_forward(torque_feed_forward).to_le_bytes());
+            }
+            CanMessage::SetInputVel {
+                velocity,
+                torque_feed_forward,
+            } => {
+                data.extend_from_slice(&velocity.to_le_bytes());
+                data.extend_from_slice(&torque_feed_forward.to_le_bytes());
+            }
+            CanMessage::SetInputTorque(torque) => data.extend_from_slice(&torque.to_le_bytes()),
+            CanMessage::SetLimits {
+                velocity_limit,
+                current_limit,
+            } => {
+                data.extend_from_slice(&velocity_limit.to_le_bytes());
+                data.extend_from_slice(&current_limit.to_le_bytes());
+            }
+            CanMessage::VbusVoltage(voltage) => data.extend_from_slice(&voltage.to_le_bytes()),
+            CanMessage::MotorError(error) => data.extend_from_slice(&error.to_le_bytes()),
+            CanMessage::EncoderError(error) | CanMessage::ControllerError(error) => {
+                data.extend_from_slice(&error.to_le_bytes())
+            }
+            CanMessage::ClearErrors | CanMessage::Reboot => {}
+        }
+        CanFrame::new(id, &data).expect("messages fit in 8 bytes")
+    }
+
+    /// Decodes a frame, returning the node ID it is addressed to or sent from.
+    ///
+    /// Remote frames decode to `Request`. Data frames of commands which are not supported, or
+    /// which are too short, are returned as `InvalidMessageReceived`.
+    pub fn decode(frame: &CanFrame) -> ODriveResult<(NodeId, CanMessage)> {
+        let (node_id, command) = split_arbitration_id(frame.id())?;
+        if frame.is_remote() {
+            return Ok((node_id, CanMessage::Request(command)));
+        }
+
+        let data = frame.data();
+        let invalid = || ODriveError::InvalidMessageReceived(format!("{:?}", frame));
+        let bytes = |offset: usize| -> ODriveResult<[u8; 4]> {
+            data.get(offset..offset + 4)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(invalid)
+        };
+        let u32_at = |offset| bytes(offset).map(u32::from_le_bytes);
+        let f32_at = |offset| bytes(offset).map(f32::from_le_bytes);
+        let i16_at = |offset: usize| -> ODriveResult<f32> {
+            data.get(offset..offset + 2)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(|bytes| i16::from_le_bytes(bytes) as f32 * FEED_FORWARD_SCALE)
+                .ok_or_else(invalid)
+        };
+
+        let message = match command {
+            CommandId::Heartbeat => CanMessage::Heartbeat {
+                axis_error: u32_at(0)?,
+                axis_state: AxisState::try_from(*data.get(4).ok_or_else(invalid)? as i32)?,
+            },
+            CommandId::SetAxisRequestedState => {
+                CanMessage::SetAxisState(AxisState::try_from(u32_at(0)? as i32)?)
+            }
+            CommandId::SetControllerModes => CanMessage::SetControllerModes {
+                control_mode: ControlMode::try_from(u32_at(0)? as i32)?,
+                input_mode: InputMode::try_from(u32_at(4)? as i32)?,
+            },
+            CommandId::GetEncoderEstimates => CanMessage::EncoderEstimates {
+                position: f32_at(0)?,
+                velocity: f32_at(4)?,
+            },
+            CommandId::SetInputPos => CanMessage::SetInputPos {
+                position: f32_at(0)?,
+                velocity_feed_forward: i16_at(4)?,
+                torque_feed_forward: i16_at(6)?,
+            },
+            CommandId::SetInputVel => CanMessage::SetInputVel {
+                velocity: f32_at(0)?,
+                torque_feed_forward: f32_at(4)?,
+            },
+            CommandId::SetInputTorque => CanMessage::SetInputTorque(f32_at(0)?),
+            CommandId::SetLimits => CanMessage::SetLimits {
+                velocity_limit: f32_at(0)?,
+                current_limit: f32_at(4)?,
+            },
+            CommandId::GetVbusVoltage => CanMessage::VbusVoltage(f32_at(0)?),
+            CommandId::GetMotorError => {
+                let low = u32_at(0)? as u64;
+                // Firmware before 0.5.2 sends 32 bit motor errors
+                let high = u32_at(4).unwrap_or(0) as u64;
+                CanMessage::MotorError(high << 32 | low)
+            }
+            CommandId::GetEncoderError => CanMessage::EncoderError(u32_at(0)?),
+            CommandId::GetControllerError => CanMessage::ControllerError(u32_at(0)?),
+            CommandId::ClearErrors => CanMessage::ClearErrors,
+            CommandId::RebootODrive => CanMessage::Reboot,
+            _ => return Err(invalid()),
+        };
+        Ok((node_id, message))
+    }
+}
+
+/// Converts a feed forward term to the 16 bit integer sent by `SetInputPos`, saturating at the
+/// limits of the integer.
+fn scale_feed_forward(value: f32) -> i16 {
+    (value / FEED_FORWARD_SCALE)
+        .round()
+        .max(i16::MIN as f32)
+        .min(i16::MAX as f32) as i16
+}
@@ -0,0 +1,233 @@
+//! Support for the CAN Simple protocol of the ODrive firmware 0.5.
+//!
+//! Every message is a standard CAN frame whose 11 bit identifier combines the node ID of an axis
+//! with a command ID: `node_id << 5 | command_id`. Commands which read a value are sent as
+//! remote frames, and the ODrive answers with a data frame with the same identifier.
+//! All values are little endian, and positions and velocities are in turns and turns/s.
+//!
+//! `CanMessage` encodes and decodes the individual messages, while `CanODrive` uses a
+//! `CanTransport` to provide the same high-level operations as the ASCII protocol through
+//! `interface::ODriveInterface`. With the `socketcan` feature, `SocketCan` is a transport over a
+//! Linux SocketCAN interface such as `can0` or a virtual `vcan0`.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+
+pub use client::CanODrive;
+pub use message::CanMessage;
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+pub use socketcan::SocketCan;
+
+mod client;
+mod message;
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+mod socketcan;
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod can_tests;
+
+/// The node ID of an axis on the CAN bus, configured in `<axis>.config.can_node_id`.
+/// Node IDs use the upper 6 bits of the 11 bit identifier, so they range from 0 to 63.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NodeId(u8);
+
+impl NodeId {
+    pub const MAX: u8 = 0x3F;
+
+    /// Returns `None` if `id` is larger than `NodeId::MAX`.
+    pub fn new(id: u8) -> Option<Self> {
+        if id <= Self::MAX {
+            Some(NodeId(id))
+        } else {
+            None
+        }
+    }
+
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for NodeId {
+    type Error = ODriveError;
+
+    fn try_from(id: u8) -> ODriveResult<Self> {
+        NodeId::new(id).ok_or(ODriveError::InvalidEnumValue {
+            enumeration: "NodeId",
+            value: id.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The command IDs of the CAN Simple protocol, which use the lower 5 bits of the identifier.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CommandId {
+    Heartbeat = 0x001,
+    EStop = 0x002,
+    GetMotorError = 0x003,
+    GetEncoderError = 0x004,
+    GetSensorlessError = 0x005,
+    SetAxisNodeId = 0x006,
+    SetAxisRequestedState = 0x007,
+    SetAxisStartupConfig = 0x008,
+    GetEncoderEstimates = 0x009,
+    GetEncoderCount = 0x00A,
+    SetControllerModes = 0x00B,
+    SetInputPos = 0x00C,
+    SetInputVel = 0x00D,
+    SetInputTorque = 0x00E,
+    SetLimits = 0x00F,
+    StartAnticogging = 0x010,
+    SetTrajVelLimit = 0x011,
+    SetTrajAccelLimits = 0x012,
+    SetTrajInertia = 0x013,
+    GetIq = 0x014,
+    GetSensorlessEstimates = 0x015,
+    RebootODrive = 0x016,
+    GetVbusVoltage = 0x017,
+    ClearErrors = 0x018,
+    SetLinearCount = 0x019,
+    SetPosGain = 0x01A,
+    SetVelGains = 0x01B,
+    GetAdcVoltage = 0x01C,
+    GetControllerError = 0x01D,
+}
+
+impl CommandId {
+    const ALL: [CommandId; 29] = [
+        CommandId::Heartbeat,
+        CommandId::EStop,
+        CommandId::GetMotorError,
+        CommandId::GetEncoderError,
+        CommandId::GetSensorlessError,
+        CommandId::SetAxisNodeId,
+        CommandId::SetAxisRequestedState,
+        CommandId::SetAxisStartupConfig,
+        CommandId::GetEncoderEstimates,
+        CommandId::GetEncoderCount,
+        CommandId::SetControllerModes,
+        CommandId::SetInputPos,
+        CommandId::SetInputVel,
+        CommandId::SetInputTorque,
+        CommandId::SetLimits,
+        CommandId::StartAnticogging,
+        CommandId::SetTrajVelLimit,
+        CommandId::SetTrajAccelLimits,
+        CommandId::SetTrajInertia,
+        CommandId::GetIq,
+        CommandId::GetSensorlessEstimates,
+        CommandId::RebootODrive,
+        CommandId::GetVbusVoltage,
+        CommandId::ClearErrors,
+        CommandId::SetLinearCount,
+        CommandId::SetPosGain,
+        CommandId::SetVelGains,
+        CommandId::GetAdcVoltage,
+        CommandId::GetControllerError,
+    ];
+}
+
+impl TryFrom<u8> for CommandId {
+    type Error = ODriveError;
+
+    fn try_from(id: u8) -> ODriveResult<Self> {
+        CommandId::ALL
+            .iter()
+            .copied()
+            .find(|command| *command as u8 == id)
+            .ok_or(ODriveError::InvalidEnumValue {
+                enumeration: "CommandId",
+                value: id.to_string(),
+            })
+    }
+}
+
+/// Combines a node ID and a command ID into the identifier of a CAN frame.
+pub fn arbitration_id(node_id: NodeId, command: CommandId) -> u16 {
+    (node_id.0 as u16) << 5 | command as u16
+}
+
+/// Splits the identifier of a CAN frame into its node ID and command ID.
+pub fn split_arbitration_id(id: u16) -> ODriveResult<(NodeId, CommandId)> {
+    let node_id = NodeId::try_from((id >> 5) as u8)?;
+    let command = CommandId::try_from((id & 0x1F) as u8)?;
+    Ok((node_id, command))
+}
+
+/// A standard CAN frame with an 11 bit identifier and up to 8 bytes of data.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CanFrame {
+    id: u16,
+    data: [u8; 8],
+    len: u8,
+    remote: bool,
+}
+
+impl CanFrame {
+    pub const MAX_ID: u16 = 0x7FF;
+
+    /// Creates a data frame. Returns `None` if the identifier does not fit in 11 bits or if there
+    /// are more than 8 bytes of data.
+    pub fn new(id: u16, data: &[u8]) -> Option<Self> {
+        if id > Self::MAX_ID || data.len() > 8 {
+            return None;
+        }
+
+        let mut frame = Self {
+            id,
+            data: [0; 8],
+            len: data.len() as u8,
+            remote: false,
+        };
+        frame.data[..data.len()].copy_from_slice(data);
+        Some(frame)
+    }
+
+    /// Creates a remote frame, which requests the data frame with the same identifier.
+    /// Returns `None` if the identifier does not fit in 11 bits.
+    pub fn remote(id: u16) -> Option<Self> {
+        if id > Self::MAX_ID {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            data: [0; 8],
+            len: 0,
+            remote: true,
+        })
+    }
+
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+}
+
+/// A connection to a CAN bus.
+pub trait CanTransport {
+    /// Sends a frame.
+    fn send(&mut self, frame: &CanFrame) -> io::Result<()>;
+
+    /// Waits up to `timeout` for the next frame, returning `None` if none arrives.
+    fn receive(&mut self, timeout: Duration) -> io::Result<Option<CanFrame>>;
+}
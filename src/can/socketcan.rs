@@ -0,0 +1,153 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::time::{Duration, Instant};
+
+use crate::can::{CanFrame, CanTransport};
+
+/// A raw socket bound to a Linux SocketCAN interface, for example `can0` or `vcan0`.
+/// Extended frames and error frames are ignored, since the ODrive only uses standard frames.
+#[derive(Debug)]
+pub struct SocketCan {
+    fd: RawFd,
+}
+
+impl SocketCan {
+    /// Opens a socket on the interface with the given name.
+    pub fn open(interface: &str) -> io::Result<Self> {
+        let name = CString::new(interface)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = unsafe {
+            libc::socket(
+                libc::PF_CAN,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::CAN_RAW,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Owning the descriptor from here on closes it if binding fails
+        let socket = Self { fd };
+
+        let mut address: libc::sockaddr_can = unsafe { mem::zeroed() };
+        address.can_family = libc::AF_CAN as libc::sa_family_t;
+        address.can_ifindex = index as libc::c_int;
+        let result = unsafe {
+            libc::bind(
+                socket.fd,
+                &address as *const libc::sockaddr_can as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(socket)
+    }
+
+    /// Waits until the socket is readable, returning false on timeout.
+    fn poll(&self, timeout: Duration) -> io::Result<bool> {
+        let mut poll_fd = libc::pollfd {
+            fd: self.fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Round up, so that short timeouts do not turn into a busy loop
+        let millis = timeout
+            .as_micros()
+            .div_ceil(1000)
+            .min(libc::c_int::MAX as u128);
+        match unsafe { libc::poll(&mut poll_fd, 1, millis as libc::c_int) } {
+            result if result < 0 => Err(io::Error::last_os_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+}
+
+impl CanTransport for SocketCan {
+    fn send(&mut self, frame: &CanFrame) -> io::Result<()> {
+        let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+        raw.can_id = frame.id() as libc::canid_t;
+        if frame.is_remote() {
+            raw.can_id |= libc::CAN_RTR_FLAG;
+        }
+        raw.can_dlc = frame.data().len() as u8;
+        raw.data[..frame.data().len()].copy_from_slice(frame.data());
+
+        let size = mem::size_of::<libc::can_frame>();
+        let written = unsafe {
+            libc::write(
+                self.fd,
+                &raw as *const libc::can_frame as *const libc::c_void,
+                size,
+            )
+        };
+        if written < 0 {
+            Err(io::Error::last_os_error())
+        } else if written as usize != size {
+            Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "the CAN frame was only partially written",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn receive(&mut self, timeout: Duration) -> io::Result<Option<CanFrame>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if !self.poll(deadline.saturating_duration_since(Instant::now()))? {
+                return Ok(None);
+            }
+
+            let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+            let size = mem::size_of::<libc::can_frame>();
+            let read = unsafe {
+                libc::read(
+                    self.fd,
+                    &mut raw as *mut libc::can_frame as *mut libc::c_void,
+                    size,
+                )
+            };
+            if read < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+
+            if raw.can_id & (libc::CAN_EFF_FLAG | libc::CAN_ERR_FLAG) != 0 {
+                continue;
+            }
+            let id = (raw.can_id & libc::CAN_SFF_MASK) as u16;
+            let frame = if raw.can_id & libc::CAN_RTR_FLAG != 0 {
+                CanFrame::remote(id)
+            } else {
+                let len = (raw.can_dlc as usize).min(raw.data.len());
+                CanFrame::new(id, &raw.data[..len])
+            };
+            if let Some(frame) = frame {
+                return Ok(Some(frame));
+            }
+        }
+    }
+}
+
+impl Drop for SocketCan {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_set_current() {
@@ -136,48 +137,377 @@ fn test_get_velocity() {
     let result = odrive.get_velocity(AxisID::Zero).unwrap().unwrap();
     assert_eq!(25.0, result);
     assert_eq!(
-        b"r axis0 .encoder.vel_estimate\n".to_vec(),
+        b"r axis0.encoder.vel_estimate\n".to_vec(),
         odrive.io_stream.get_mut().write_buffer
     );
     assert!(odrive.io_stream.get_mut().flushed)
 }
 
 #[test]
-fn test_run_state_instant_switch() {
+fn test_run_state_waits_for_request() {
     let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    // The axis is still idle until the firmware picks up the request
+    for response in [
+        b"1\n", b"0\n", b"0\n", b"0\n", b"0\n", b"4\n", b"1\n", b"0\n", b"0\n", b"0\n", b"0\n",
+    ]
+    .iter()
+    {
+        stream.push_response(*response);
+    }
     odrive
-        .io_stream
-        .get_mut()
-        .read_buffer
-        .append(&mut b"1\n".to_vec());
-    odrive.io_stream.get_mut().read_buffer.reverse();
-    let result = odrive
-        .run_state(AxisID::Zero, AxisState::MotorCalibration, true)
+        .run_state(
+            AxisID::Zero,
+            AxisState::MotorCalibration,
+            Transition::for_state(AxisState::MotorCalibration)
+                .with_poll_interval(Duration::from_millis(1)),
+        )
         .unwrap();
-    assert_eq!(true, result);
     assert_eq!(
-        b"w axis0.requested_state 4\nr axis0.current_state\n".to_vec(),
+        b"w axis0.requested_state 4\nr axis0.current_state\nr axis0.error\nr axis0.motor.error\n\
+r axis0.encoder.error\nr axis0.controller.error\nr axis0.current_state\nr axis0.current_state\n\
+r axis0.error\nr axis0.motor.error\nr axis0.encoder.error\nr axis0.controller.error\n"
+            .to_vec(),
         odrive.io_stream.get_mut().write_buffer
     );
     assert!(odrive.io_stream.get_mut().flushed)
 }
 
+#[test]
+fn test_run_state_never_started() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    for response in [
+        b"1\n", b"0\n", b"0\n", b"0\n", b"0\n", b"0\n", b"0\n", b"0\n", b"0\n",
+    ]
+    .iter()
+    {
+        stream.push_response(*response);
+    }
+    match odrive.run_state(
+        AxisID::Zero,
+        AxisState::MotorCalibration,
+        Transition::for_state(AxisState::MotorCalibration).with_timeout(Duration::from_secs(0)),
+    ) {
+        Err(ODriveError::StateTransition(error)) => {
+            assert_eq!(AxisState::Idle, error.last_state);
+            assert!(error.timed_out);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
 #[test]
 fn test_run_state_delayed_switch() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    for response in [b"4\n", b"1\n", b"0\n", b"0\n", b"0\n", b"0\n"].iter() {
+        stream.push_response(*response);
+    }
+    odrive
+        .run_state(
+            AxisID::Zero,
+            AxisState::MotorCalibration,
+            Transition::for_state(AxisState::MotorCalibration)
+                .with_poll_interval(Duration::from_millis(1)),
+        )
+        .unwrap();
+    assert_eq!(
+        b"w axis0.requested_state 4\nr axis0.current_state\nr axis0.current_state\n\
+r axis0.error\nr axis0.motor.error\nr axis0.encoder.error\nr axis0.controller.error\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_run_state_no_wait() {
+    let mut odrive = init_odrive();
+    odrive
+        .run_state(
+            AxisID::One,
+            AxisState::ClosedLoopControl,
+            Transition::no_wait(),
+        )
+        .unwrap();
+    assert_eq!(
+        b"w axis1.requested_state 8\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_run_state_reaches_closed_loop() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    stream.push_response(b"8\n");
+    odrive
+        .run_state(
+            AxisID::Zero,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+    assert_eq!(
+        b"w axis0.requested_state 8\nr axis0.current_state\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_run_state_rejected() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    for response in [b"1\n", b"1\n", b"0\n", b"0\n", b"0\n"].iter() {
+        stream.push_response(*response);
+    }
+    match odrive.run_state(
+        AxisID::Zero,
+        AxisState::ClosedLoopControl,
+        Transition::for_state(AxisState::ClosedLoopControl),
+    ) {
+        Err(ODriveError::StateTransition(error)) => {
+            assert_eq!(AxisID::Zero, error.axis);
+            assert_eq!(AxisState::ClosedLoopControl, error.requested_state);
+            assert_eq!(AxisState::Idle, error.last_state);
+            assert!(error.errors.axis.contains(AxisError::ErrorInvalidState));
+            assert!(!error.timed_out);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_run_state_timeout() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    for response in [b"4\n", b"0\n", b"8\n", b"0\n", b"0\n"].iter() {
+        stream.push_response(*response);
+    }
+    match odrive.run_state(
+        AxisID::One,
+        AxisState::MotorCalibration,
+        Transition::for_state(AxisState::MotorCalibration).with_timeout(Duration::from_secs(0)),
+    ) {
+        Err(ODriveError::StateTransition(error)) => {
+            assert_eq!(AxisState::MotorCalibration, error.last_state);
+            assert!(error.errors.motor.contains(MotorError::ErrorDrvFault));
+            assert!(error.timed_out);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_string_timeout() {
+    let mut odrive = init_odrive();
+    let start = Instant::now();
+    let result = odrive
+        .read_string_timeout(Duration::from_millis(20))
+        .unwrap();
+    assert_eq!(None, result);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+#[test]
+fn test_read_string_split_message() {
     let mut odrive = init_odrive();
     odrive
         .io_stream
         .get_mut()
         .read_buffer
-        .append(&mut b"4\n1\n".to_vec());
+        .append(&mut b"hel".to_vec());
     odrive.io_stream.get_mut().read_buffer.reverse();
-    let result = odrive
-        .run_state(AxisID::Zero, AxisState::MotorCalibration, true)
+    assert_eq!(
+        None,
+        odrive
+            .read_string_timeout(Duration::from_millis(5))
+            .unwrap()
+    );
+
+    odrive
+        .io_stream
+        .get_mut()
+        .read_buffer
+        .append(&mut b"lo\n".to_vec());
+    odrive.io_stream.get_mut().read_buffer.reverse();
+    assert_eq!("hello", odrive.read_string().unwrap().unwrap());
+}
+
+#[test]
+fn test_read_odrive_response_no_message() {
+    let mut odrive = ODrive::with_timeout(MockStream::new(), Duration::from_millis(5));
+    assert_eq!(Duration::from_millis(5), odrive.timeout());
+    match odrive.read_odrive_response() {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_odrive_response_io_error() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().read_error = Some(io::ErrorKind::BrokenPipe);
+    match odrive.read_odrive_response() {
+        Err(ODriveError::Io(error)) => assert_eq!(io::ErrorKind::BrokenPipe, error.kind()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_string_retries_timed_out_reads() {
+    let mut odrive = init_odrive();
+    odrive.set_timeout(Duration::from_millis(5));
+    odrive.io_stream.get_mut().read_error = Some(io::ErrorKind::TimedOut);
+    assert_eq!(None, odrive.read_string().unwrap());
+}
+
+#[test]
+fn test_current_state() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"8\n");
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        odrive.current_state(AxisID::One).unwrap()
+    );
+    assert_eq!(
+        b"r axis1.current_state\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_current_state_out_of_range() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"99\n");
+    match odrive.current_state(AxisID::Zero) {
+        Err(ODriveError::InvalidEnumValue { enumeration, value }) => {
+            assert_eq!("AxisState", enumeration);
+            assert_eq!("99", value);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_feedback() {
+    assert_eq!(
+        Feedback {
+            position: 12.5,
+            velocity: -3.0
+        },
+        "12.5 -3".parse().unwrap()
+    );
+    for line in &["", "12.5", "12.5 -3 7", "12.5 fast", "invalid property"] {
+        match line.parse::<Feedback>() {
+            Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!(*line, message),
+            other => panic!("unexpected result for {:?}: {:?}", line, other),
+        }
+    }
+}
+
+#[test]
+fn test_read_feedback_in_request_order() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1 2\n");
+    odrive.io_stream.get_mut().push_response(b"3 4\n");
+    odrive.request_feedback(AxisID::One).unwrap();
+    odrive.request_feedback(AxisID::Zero).unwrap();
+    assert_eq!(2, odrive.pending_feedback());
+    assert_eq!(
+        b"f 1\nf 0\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(!odrive.io_stream.get_mut().flushed);
+
+    let (axis, feedback) = odrive.read_feedback().unwrap();
+    assert_eq!(
+        (AxisID::One, 1.0, 2.0),
+        (axis, feedback.position, feedback.velocity)
+    );
+    let (axis, feedback) = odrive.read_feedback().unwrap();
+    assert_eq!(
+        (AxisID::Zero, 3.0, 4.0),
+        (axis, feedback.position, feedback.velocity)
+    );
+    assert_eq!(0, odrive.pending_feedback());
+}
+
+#[test]
+fn test_read_feedback_without_request() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().read_buffer = b"\n2 1".to_vec();
+    match odrive.read_feedback() {
+        Err(ODriveError::InvalidMessageReceived(reply)) => assert_eq!("1 2", reply),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match odrive.read_feedback_timeout(Duration::from_millis(5)) {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_feedback_split_reply() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"10.5 -");
+    odrive.request_feedback(AxisID::Zero).unwrap();
+    match odrive.read_feedback_timeout(Duration::from_millis(5)) {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(1, odrive.pending_feedback());
+
+    odrive.io_stream.get_mut().read_buffer = b"\n2".to_vec();
+    let (axis, feedback) = odrive.read_feedback().unwrap();
+    assert_eq!(AxisID::Zero, axis);
+    assert_eq!(-2.0, feedback.velocity);
+}
+
+#[test]
+fn test_read_feedback_malformed_reply() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"invalid motor\n");
+    odrive.io_stream.get_mut().push_response(b"5 6\n");
+    odrive.request_feedback(AxisID::Zero).unwrap();
+    odrive.request_feedback(AxisID::One).unwrap();
+    match odrive.read_feedback() {
+        Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!("invalid motor", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let (axis, feedback) = odrive.read_feedback().unwrap();
+    assert_eq!((AxisID::One, 6.0), (axis, feedback.velocity));
+}
+
+#[test]
+fn test_get_feedback() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"7 8\n");
+    let feedback = odrive.get_feedback(AxisID::One).unwrap();
+    assert_eq!((7.0, 8.0), (feedback.position, feedback.velocity));
+    assert!(odrive.io_stream.get_mut().flushed);
+}
+
+#[test]
+fn test_try_read_both_velocities() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    stream.push_response(b"");
+    stream.push_response(b"");
+    stream.push_response(b"0 1.5\n");
+    stream.push_response(b"0 -2");
+    odrive
+        .set_both_currents_and_request_feedback(0.5, -0.5)
         .unwrap();
-    assert_eq!(true, result);
     assert_eq!(
-        b"w axis0.requested_state 4\nr axis0.current_state\nr axis0.current_state\n".to_vec(),
+        b"c 0 0.5\nc 1 -0.5\nf 0\nf 1\n".to_vec(),
         odrive.io_stream.get_mut().write_buffer
     );
-    assert!(odrive.io_stream.get_mut().flushed)
+    assert_eq!((Some(1.5), None), odrive.try_read_both_velocities());
+
+    odrive.io_stream.get_mut().read_buffer = b"\n5".to_vec();
+    assert_eq!((None, Some(-25.0)), odrive.try_read_both_velocities());
+    assert_eq!(0, odrive.pending_feedback());
 }
@@ -15,3 +15,92 @@ fn test_erase_configuration() {
     assert_eq!(b"se\n".to_vec(), odrive.io_stream.get_mut().write_buffer);
     assert!(odrive.io_stream.get_mut().flushed)
 }
+
+#[test]
+fn test_read_axis_errors() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    stream.push_response(b"66\n");
+    stream.push_response(b"4104\n");
+    stream.push_response(b"0\n");
+    stream.push_response(b"1\n");
+    let report = odrive.read_axis_errors(AxisID::One).unwrap();
+    assert_eq!(
+        vec![
+            AxisError::ErrorDcBusUnderVoltage,
+            AxisError::ErrorMotorFailed
+        ],
+        report.axis.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![MotorError::ErrorDrvFault, MotorError::ErrorCurrentUnstable],
+        report.motor.iter().collect::<Vec<_>>()
+    );
+    assert!(report.encoder.is_empty());
+    assert!(report.controller.contains(ControllerError::ErrorOverspeed));
+    assert_eq!(
+        b"r axis1.error\nr axis1.motor.error\nr axis1.encoder.error\nr axis1.controller.error\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_axis_errors_0_5() {
+    let mut odrive = init_odrive_0_5();
+    let stream = odrive.io_stream.get_mut();
+    stream.push_response(b"2048\n");
+    stream.push_response(b"4104\n");
+    stream.push_response(b"0\n");
+    stream.push_response(b"1\n");
+    let report = odrive.read_axis_errors(AxisID::One).unwrap();
+    // The flags were renumbered in 0.5.0, so the bits are kept without naming them
+    assert_eq!(None, report.axis.iter().next());
+    assert_eq!(2048, report.axis.unknown_bits());
+    assert_eq!(4104, report.motor.bits());
+    assert!(!report.controller.contains(ControllerError::ErrorOverspeed));
+    assert_eq!(
+        "Axis=Unknown error bits 0x800, Motor=Unknown error bits 0x1008, Encoder=No error, \
+         Controller=Unknown error bits 0x1",
+        report.to_string()
+    );
+}
+
+#[test]
+fn test_read_all_errors() {
+    let mut odrive = init_odrive();
+    let stream = odrive.io_stream.get_mut();
+    for response in [
+        b"0\n", b"2\n", b"0\n", b"0\n", b"0\n", b"0\n", b"0\n", b"4\n", b"0\n",
+    ]
+    .iter()
+    {
+        stream.push_response(*response);
+    }
+    let report = odrive.read_all_errors().unwrap();
+    assert!(!report.is_empty());
+    assert_eq!(0, report.system);
+    assert!(report
+        .axis(AxisID::Zero)
+        .axis
+        .contains(AxisError::ErrorDcBusUnderVoltage));
+    assert!(report
+        .axis(AxisID::One)
+        .encoder
+        .contains(EncoderError::ErrorNoResponse));
+}
+
+#[test]
+fn test_read_all_errors_invalid_response() {
+    let mut odrive = init_odrive();
+    odrive
+        .io_stream
+        .get_mut()
+        .push_response(b"invalid property\n");
+    match odrive.read_all_errors() {
+        Err(ODriveError::InvalidMessageReceived(message)) => {
+            assert_eq!("invalid property", message)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -58,3 +58,87 @@ fn test_set_control_mode() {
     );
     assert!(odrive.io_stream.get_mut().flushed)
 }
+
+#[test]
+fn test_read_pos_gain() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"20.0\n");
+    assert_eq!(20.0, odrive.read_position_gain(AxisID::Zero).unwrap());
+    assert_eq!(
+        b"r axis0.controller.config.pos_gain\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_read_vel_gain() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"0.02\n");
+    assert_eq!(0.02, odrive.read_velocity_gain(AxisID::Zero).unwrap());
+    assert_eq!(
+        b"r axis0.controller.config.vel_gain\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_vel_integrator_gain() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"0.1\n");
+    assert_eq!(
+        0.1,
+        odrive.read_velocity_integrator_gain(AxisID::Zero).unwrap()
+    );
+    assert_eq!(
+        b"r axis0.controller.config.vel_integrator_gain\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_vel_limit() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1000\n");
+    assert_eq!(1000.0, odrive.read_velocity_limit(AxisID::One).unwrap());
+    assert_eq!(
+        b"r axis1.controller.config.vel_limit\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_vel_limit_invalid() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"fast\n");
+    match odrive.read_velocity_limit(AxisID::One) {
+        Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!("fast", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_control_mode() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"3\n");
+    assert_eq!(
+        ControlMode::PositionControl,
+        odrive.read_control_mode(AxisID::Zero).unwrap()
+    );
+    assert_eq!(
+        b"r axis0.controller.config.control_mode\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_control_mode_out_of_range() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"7\n");
+    match odrive.read_control_mode(AxisID::Zero) {
+        Err(ODriveError::InvalidEnumValue { enumeration, .. }) => {
+            assert_eq!("ControlMode", enumeration)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
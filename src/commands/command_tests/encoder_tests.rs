@@ -47,3 +47,66 @@ fn test_set_encoder_pre_calibration() {
     );
     assert!(odrive.io_stream.get_mut().flushed)
 }
+
+#[test]
+fn test_read_encoder_mode() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1\n");
+    assert_eq!(
+        EncoderMode::EncoderModeHall,
+        odrive.read_encoder_mode(AxisID::Zero).unwrap()
+    );
+    assert_eq!(
+        b"r axis0.encoder.config.mode\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_read_encoder_mode_invalid() {
+    let mut odrive = init_odrive();
+    odrive
+        .io_stream
+        .get_mut()
+        .push_response(b"invalid property\n");
+    match odrive.read_encoder_mode(AxisID::Zero) {
+        Err(ODriveError::InvalidMessageReceived(message)) => {
+            assert_eq!("invalid property", message)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_encoder_cpr() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"90\n");
+    assert_eq!(90, odrive.read_encoder_cpr(AxisID::One).unwrap());
+    assert_eq!(
+        b"r axis1.encoder.config.cpr\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_encoder_bandwidth() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1000.0\n");
+    assert_eq!(1000.0, odrive.read_encoder_bandwidth(AxisID::Zero).unwrap());
+    assert_eq!(
+        b"r axis0.encoder.config.bandwidth\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_encoder_pre_calibrated() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"0\n");
+    assert!(!odrive.read_encoder_pre_calibrated(AxisID::Zero).unwrap());
+    assert_eq!(
+        b"r axis0.encoder.config.pre_calibrated\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
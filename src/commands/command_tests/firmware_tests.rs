@@ -0,0 +1,144 @@
+use super::*;
+
+fn push_versions(odrive: &mut ODrive<MockStream>, firmware: &str) {
+    let hardware = ["3", "6", "56"];
+    for version in firmware.split('.').chain(hardware.iter().copied()) {
+        let response = format!("{}\n", version);
+        odrive
+            .io_stream
+            .get_mut()
+            .push_response(response.as_bytes());
+    }
+}
+
+fn firmware_0_5_6() -> Capabilities {
+    Capabilities::new(FirmwareVersion::new(0, 5, 6), None)
+}
+
+#[test]
+fn test_detect_firmware() {
+    let mut odrive = init_odrive();
+    push_versions(&mut odrive, "0.5.6");
+    let capabilities = odrive.detect_firmware().unwrap();
+
+    assert_eq!(FirmwareVersion::new(0, 5, 6), capabilities.firmware());
+    assert_eq!(
+        Some(HardwareVersion {
+            major: 3,
+            minor: 6,
+            variant: 56
+        }),
+        capabilities.hardware()
+    );
+    assert_eq!(capabilities, odrive.capabilities());
+    assert_eq!(
+        b"r fw_version_major\nr fw_version_minor\nr fw_version_revision\n\
+          r hw_version_major\nr hw_version_minor\nr hw_version_variant\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_detect_unsupported_firmware() {
+    let mut odrive = init_odrive();
+    push_versions(&mut odrive, "0.6.9");
+    match odrive.detect_firmware() {
+        Err(ODriveError::Unsupported(what)) => assert_eq!("firmware 0.6.9", what),
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert_eq!(Capabilities::default(), odrive.capabilities());
+}
+
+#[test]
+fn test_firmware_version() {
+    let version: FirmwareVersion = "v0.4.12".parse().unwrap();
+    assert_eq!(FirmwareVersion::new(0, 4, 12), version);
+    assert_eq!("0.4.12", version.to_string());
+    assert!(version < "0.5.1".parse().unwrap());
+    assert!("0.5".parse::<FirmwareVersion>().is_err());
+    assert!("0.5.1.2".parse::<FirmwareVersion>().is_err());
+}
+
+#[test]
+fn test_capabilities() {
+    let old = Capabilities::default();
+    assert_eq!(PositionUnit::Counts, old.position_unit());
+    assert!(!old.uses_torque());
+    assert_eq!(
+        "axis0.controller.vel_setpoint",
+        old.property_path("axis0.controller.vel_setpoint")
+    );
+    assert_eq!(4, old.control_mode_value(ControlMode::TrajectoryControl));
+    assert_eq!(
+        ControlMode::TrajectoryControl,
+        old.control_mode_from_value(4).unwrap()
+    );
+    assert_eq!(1, old.motor_type_value(MotorType::LowCurrent).unwrap());
+
+    let new = firmware_0_5_6();
+    assert_eq!(PositionUnit::Turns, new.position_unit());
+    assert!(new.uses_torque());
+    assert_eq!(
+        "axis0.controller.input_vel",
+        new.property_path("axis0.controller.vel_setpoint")
+    );
+    assert_eq!(
+        "config.enable_uart_a",
+        new.property_path("config.enable_uart")
+    );
+    assert_eq!(
+        "axis1.controller.config.vel_limit",
+        new.property_path("axis1.controller.config.vel_limit")
+    );
+    assert_eq!(
+        "axis0.encoder.config.phase_offset_float",
+        new.property_path("axis0.encoder.config.offset_float")
+    );
+    // Other properties named `offset` are not renamed
+    assert_eq!(
+        "axis0.min_endstop.config.offset",
+        new.property_path("axis0.min_endstop.config.offset")
+    );
+    assert_eq!(3, new.control_mode_value(ControlMode::TrajectoryControl));
+    assert!(new.control_mode_from_value(4).is_err());
+    assert!(new.motor_type_value(MotorType::LowCurrent).is_err());
+    assert_eq!(
+        MotorType::MotorTypeGimbal,
+        new.motor_type_from_value(2).unwrap()
+    );
+}
+
+#[test]
+fn test_set_trajectory_control_mode_on_0_5() {
+    let mut odrive = init_odrive_0_5();
+    odrive
+        .set_control_mode(AxisID::One, ControlMode::TrajectoryControl)
+        .unwrap();
+    assert_eq!(
+        b"w axis1.controller.config.control_mode 3\nw axis1.controller.config.input_mode 5\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_control_mode_on_0_5() {
+    let mut odrive = init_odrive_0_5();
+    odrive.io_stream.get_mut().push_response(b"4\n");
+    assert!(odrive.read_control_mode(AxisID::Zero).is_err());
+}
+
+#[test]
+fn test_renamed_property_on_0_5() {
+    let mut odrive = init_odrive_0_5();
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"2.5\n");
+    odrive
+        .write_property("axis0.controller.current_setpoint", 2.5)
+        .unwrap();
+    assert_eq!(
+        b"w axis0.controller.input_torque 2.5\nr axis0.controller.input_torque\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
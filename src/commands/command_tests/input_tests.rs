@@ -0,0 +1,147 @@
+use super::*;
+
+#[test]
+fn test_set_input_mode() {
+    let mut odrive = init_odrive_0_5();
+    odrive
+        .set_input_mode(AxisID::Zero, InputMode::PosFilter)
+        .unwrap();
+    assert_eq!(
+        b"w axis0.controller.config.input_mode 3\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_read_input_mode() {
+    let mut odrive = init_odrive_0_5();
+    odrive.io_stream.get_mut().push_response(b"6\n");
+    assert_eq!(
+        InputMode::TorqueRamp,
+        odrive.read_input_mode(AxisID::One).unwrap()
+    );
+    assert_eq!(
+        b"r axis1.controller.config.input_mode\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_input_mode_requires_0_5() {
+    let mut odrive = init_odrive();
+    match odrive.set_input_mode(AxisID::Zero, InputMode::VelRamp) {
+        Err(ODriveError::Unsupported(what)) => {
+            assert_eq!("input modes on firmware 0.4.12", what)
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(odrive.read_torque_ramp_rate(AxisID::Zero).is_err());
+    assert!(odrive.set_mirror_ratio(AxisID::Zero, 2.0).is_err());
+    assert!(odrive.io_stream.get_mut().write_buffer.is_empty());
+}
+
+#[test]
+fn test_set_inputs() {
+    let mut odrive = init_odrive_0_5();
+    odrive.set_input_pos(AxisID::Zero, 1.5).unwrap();
+    odrive.set_input_vel(AxisID::Zero, -2.0).unwrap();
+    odrive.set_input_torque(AxisID::Zero, 0.25).unwrap();
+    assert_eq!(
+        b"w axis0.controller.input_pos 1.5\n\
+          w axis0.controller.input_vel -2\n\
+          w axis0.controller.input_torque 0.25\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_set_inputs_on_0_4() {
+    let mut odrive = init_odrive();
+    odrive.set_input_pos(AxisID::One, 1000.0).unwrap();
+    odrive.set_input_vel(AxisID::One, 200.0).unwrap();
+    odrive.set_input_torque(AxisID::One, 3.0).unwrap();
+    assert_eq!(
+        b"w axis1.controller.pos_setpoint 1000\n\
+          w axis1.controller.vel_setpoint 200\n\
+          w axis1.controller.current_setpoint 3\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_inputs() {
+    let mut odrive = init_odrive_0_5();
+    odrive.io_stream.get_mut().push_response(b"1.5\n");
+    odrive.io_stream.get_mut().push_response(b"-2.0\n");
+    odrive.io_stream.get_mut().push_response(b"0.25\n");
+    assert_eq!(1.5, odrive.read_input_pos(AxisID::Zero).unwrap());
+    assert_eq!(-2.0, odrive.read_input_vel(AxisID::Zero).unwrap());
+    assert_eq!(0.25, odrive.read_input_torque(AxisID::Zero).unwrap());
+}
+
+#[test]
+fn test_set_ramp_and_filter() {
+    let mut odrive = init_odrive_0_5();
+    odrive
+        .set_input_filter_bandwidth(AxisID::Zero, 20.0)
+        .unwrap();
+    odrive.set_vel_ramp_rate(AxisID::Zero, 5.0).unwrap();
+    odrive.set_torque_ramp_rate(AxisID::Zero, 0.1).unwrap();
+    assert_eq!(
+        b"w axis0.controller.config.input_filter_bandwidth 20\n\
+          w axis0.controller.config.vel_ramp_rate 5\n\
+          w axis0.controller.config.torque_ramp_rate 0.1\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_ramp_and_filter() {
+    let mut odrive = init_odrive_0_5();
+    odrive.io_stream.get_mut().push_response(b"20.0\n");
+    odrive.io_stream.get_mut().push_response(b"5.0\n");
+    odrive.io_stream.get_mut().push_response(b"0.1\n");
+    assert_eq!(
+        20.0,
+        odrive.read_input_filter_bandwidth(AxisID::One).unwrap()
+    );
+    assert_eq!(5.0, odrive.read_vel_ramp_rate(AxisID::One).unwrap());
+    assert_eq!(0.1, odrive.read_torque_ramp_rate(AxisID::One).unwrap());
+}
+
+#[test]
+fn test_vel_ramp_rate_on_0_4() {
+    let mut odrive = init_odrive();
+    odrive.set_vel_ramp_rate(AxisID::One, 10000.0).unwrap();
+    assert_eq!(
+        b"w axis1.controller.config.vel_ramp_rate 10000\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_mirror() {
+    let mut odrive = init_odrive_0_5();
+    odrive
+        .set_axis_to_mirror(AxisID::One, AxisID::Zero)
+        .unwrap();
+    odrive.set_mirror_ratio(AxisID::One, -1.0).unwrap();
+    assert_eq!(
+        b"w axis1.controller.config.axis_to_mirror 0\n\
+          w axis1.controller.config.mirror_ratio -1\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+
+    odrive.io_stream.get_mut().push_response(b"0\n");
+    odrive.io_stream.get_mut().push_response(b"-1.0\n");
+    assert_eq!(
+        AxisID::Zero,
+        odrive.read_axis_to_mirror(AxisID::One).unwrap()
+    );
+    assert_eq!(-1.0, odrive.read_mirror_ratio(AxisID::One).unwrap());
+}
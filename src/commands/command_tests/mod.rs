@@ -1,4 +1,6 @@
 use super::*;
+use crate::enumerations::errors::{AxisError, ControllerError, EncoderError, MotorError};
+use crate::enumerations::MotorType;
 use crate::test_stream::MockStream;
 
 #[cfg(test)]
@@ -19,7 +21,28 @@ mod motor_tests;
 #[cfg(test)]
 mod encoder_tests;
 
+#[cfg(test)]
+mod watchdog_tests;
+
+#[cfg(test)]
+mod property_tests;
+
+#[cfg(test)]
+mod firmware_tests;
+
+#[cfg(test)]
+mod input_tests;
+
+#[cfg(test)]
+mod trajectory_tests;
+
 fn init_odrive() -> ODrive<MockStream> {
-    let stream = MockStream::default();
+    let stream = MockStream::new();
     ODrive::new(stream)
 }
+
+fn init_odrive_0_5() -> ODrive<MockStream> {
+    let mut odrive = init_odrive();
+    odrive.set_capabilities(Capabilities::new(FirmwareVersion::new(0, 5, 6), None));
+    odrive
+}
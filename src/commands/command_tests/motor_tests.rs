@@ -49,3 +49,94 @@ fn test_set_current_control_bandwidth() {
     );
     assert!(odrive.io_stream.get_mut().flushed)
 }
+
+#[test]
+fn test_read_pole_pairs() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"15\n");
+    assert_eq!(15, odrive.read_motor_pole_pairs(AxisID::One).unwrap());
+    assert_eq!(
+        b"r axis1.motor.config.pole_pairs\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_read_pole_pairs_invalid() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"-3\n");
+    match odrive.read_motor_pole_pairs(AxisID::Zero) {
+        Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!("-3", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_resistance_calibration_max_voltage() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"4.000000\n");
+    assert_eq!(
+        4.0,
+        odrive
+            .read_motor_resistance_calib_max_voltage(AxisID::Zero)
+            .unwrap()
+    );
+    assert_eq!(
+        b"r axis0.motor.config.resistance_calib_max_voltage\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_requested_current_range() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"25.5\n");
+    assert_eq!(
+        25.5,
+        odrive
+            .read_motor_requested_current_range(AxisID::Zero)
+            .unwrap()
+    );
+    assert_eq!(
+        b"r axis0.motor.config.requested_current_range\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_current_control_bandwidth() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"100\n");
+    assert_eq!(
+        100.0,
+        odrive
+            .read_motor_current_control_bandwidth(AxisID::Zero)
+            .unwrap()
+    );
+    assert_eq!(
+        b"r axis0.motor.config.current_control_bandwidth\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_motor_pre_calibrated() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1\n");
+    assert!(odrive.read_motor_pre_calibrated(AxisID::Zero).unwrap());
+    assert_eq!(
+        b"r axis0.motor.config.pre_calibrated\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_motor_pre_calibrated_invalid() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"2\n");
+    match odrive.read_motor_pre_calibrated(AxisID::Zero) {
+        Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!("2", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,123 @@
+use super::*;
+
+#[test]
+fn test_read_property() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"24.1\n");
+    let vbus_voltage: f32 = odrive.read_property("vbus_voltage").unwrap();
+    assert_eq!(24.1, vbus_voltage);
+    assert_eq!(
+        b"r vbus_voltage\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_invalid_property() {
+    let mut odrive = init_odrive();
+    odrive
+        .io_stream
+        .get_mut()
+        .push_response(b"invalid property\n");
+    match odrive.read_property::<f32>("vbus_voltag") {
+        Err(ODriveError::InvalidProperty { path, reply }) => {
+            assert_eq!("vbus_voltag", path);
+            assert_eq!("invalid property", reply);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_read_property_of_wrong_type() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"24.1\n");
+    match odrive.read_property::<u32>("vbus_voltage") {
+        Err(ODriveError::InvalidMessageReceived(response)) => assert_eq!("24.1", response),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_write_property() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"11\n");
+    odrive
+        .write_property("axis0.motor.config.current_lim", 11.0)
+        .unwrap();
+    assert_eq!(
+        b"w axis0.motor.config.current_lim 11\nr axis0.motor.config.current_lim\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed);
+}
+
+#[test]
+fn test_write_property_read_back() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"60.000000\n");
+    let current_lim: f32 = odrive
+        .write_property_read_back("axis0.motor.config.current_lim", 80.0)
+        .unwrap();
+    assert_eq!(60.0, current_lim);
+    assert_eq!(
+        b"w axis0.motor.config.current_lim 80\nr axis0.motor.config.current_lim\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_write_rejected_property() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"invalid value\n");
+    odrive.io_stream.get_mut().push_response(b"10\n");
+    match odrive.write_property("axis0.motor.config.current_lim", "high") {
+        Err(ODriveError::InvalidProperty { path, reply }) => {
+            assert_eq!("axis0.motor.config.current_lim", path);
+            assert_eq!("invalid value", reply);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    // Both replies have been consumed
+    assert!(odrive.io_stream.get_mut().read_buffer.is_empty());
+}
+
+#[test]
+fn test_write_property_verified() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"20.000000\n");
+    odrive
+        .write_property_verified("axis1.controller.config.vel_limit", 20.0)
+        .unwrap();
+
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"0.000021\n");
+    odrive
+        .write_property_verified("axis1.controller.config.vel_gain", 2.1e-5)
+        .unwrap();
+
+    // An integer written to a float property reads back with decimals
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"5.000000\n");
+    odrive
+        .write_property_verified("axis1.motor.config.current_lim", 5i32)
+        .unwrap();
+
+    odrive.io_stream.get_mut().push_response(b"");
+    odrive.io_stream.get_mut().push_response(b"60.000000\n");
+    match odrive.write_property_verified("axis1.motor.config.current_lim", 80.0) {
+        Err(ODriveError::PropertyMismatch {
+            path,
+            written,
+            read,
+        }) => {
+            assert_eq!("axis1.motor.config.current_lim", path);
+            assert_eq!("80", written);
+            assert_eq!("60.000000", read);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
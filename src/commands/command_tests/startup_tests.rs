@@ -64,3 +64,16 @@ fn test_startup_sensorless_control_setter() {
     );
     assert!(odrive.io_stream.get_mut().flushed)
 }
+
+#[test]
+fn test_startup_closed_loop_control_getter() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1\n");
+    assert!(odrive
+        .read_startup_closed_loop_control(AxisID::Zero)
+        .unwrap());
+    assert_eq!(
+        b"r axis0.config.startup_closed_loop_control\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
@@ -0,0 +1,95 @@
+use super::*;
+
+#[test]
+fn test_set_trap_traj_config() {
+    let mut odrive = init_odrive();
+    let config = TrapTrajConfig {
+        vel_limit: 10000.0,
+        accel_limit: 2000.0,
+        decel_limit: 3000.0,
+        inertia: 0.5,
+    };
+    odrive.set_trap_traj_config(AxisID::One, config).unwrap();
+    assert_eq!(
+        b"w axis1.trap_traj.config.vel_limit 10000\n\
+          w axis1.trap_traj.config.accel_limit 2000\n\
+          w axis1.trap_traj.config.decel_limit 3000\n\
+          w axis1.trap_traj.config.A_per_css 0.5\n"
+            .to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_set_trap_traj_config_on_0_5() {
+    let mut odrive = init_odrive_0_5();
+    odrive
+        .set_trap_traj_config(AxisID::Zero, TrapTrajConfig::default())
+        .unwrap();
+    let written = String::from_utf8(odrive.io_stream.get_mut().write_buffer.clone()).unwrap();
+    assert!(written.ends_with("w axis0.controller.config.inertia 0\n"));
+}
+
+#[test]
+fn test_read_trap_traj_config() {
+    let mut odrive = init_odrive();
+    for response in ["20000.0", "5000.0", "4000.0", "0.0"].iter() {
+        let response = format!("{}\n", response);
+        odrive
+            .io_stream
+            .get_mut()
+            .push_response(response.as_bytes());
+    }
+    assert_eq!(
+        TrapTrajConfig {
+            decel_limit: 4000.0,
+            ..TrapTrajConfig::default()
+        },
+        odrive.read_trap_traj_config(AxisID::Zero).unwrap()
+    );
+}
+
+#[test]
+fn test_wait_for_finished_trajectory() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1\n");
+    odrive
+        .wait_for_trajectory(AxisID::Zero, Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(
+        b"r axis0.controller.trajectory_done\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_wait_for_trajectory_timeout() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"0\n");
+    odrive.io_stream.get_mut().push_response(b"8\n");
+    match odrive.wait_for_trajectory(AxisID::One, Duration::from_secs(0)) {
+        Err(ODriveError::TrajectoryTimeout(axis)) => assert_eq!(AxisID::One, axis),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_wait_for_trajectory_of_disarmed_axis() {
+    let mut odrive = init_odrive();
+    for response in ["0", "1", "64", "0", "0", "0"].iter() {
+        let response = format!("{}\n", response);
+        odrive
+            .io_stream
+            .get_mut()
+            .push_response(response.as_bytes());
+    }
+    match odrive.wait_for_trajectory(AxisID::Zero, Duration::from_secs(1)) {
+        Err(ODriveError::StateTransition(error)) => {
+            assert_eq!(AxisState::Idle, error.last_state);
+            assert!(!error.timed_out);
+            assert!(error.errors.axis.contains(AxisError::ErrorMotorFailed));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
@@ -0,0 +1,121 @@
+use super::*;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+
+#[test]
+fn test_feed_watchdog() {
+    let mut odrive = init_odrive();
+    odrive.feed_watchdog(AxisID::One).unwrap();
+    assert_eq!(b"u 1\n".to_vec(), odrive.io_stream.get_mut().write_buffer);
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_set_watchdog_timeout() {
+    let mut odrive = init_odrive();
+    odrive.set_watchdog_timeout(AxisID::Zero, 0.5).unwrap();
+    assert_eq!(
+        b"w axis0.config.watchdog_timeout 0.5\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_set_watchdog_enabled() {
+    let mut odrive = init_odrive();
+    odrive.set_watchdog_enabled(AxisID::One, true).unwrap();
+    assert_eq!(
+        b"w axis1.config.enable_watchdog 1\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+    assert!(odrive.io_stream.get_mut().flushed)
+}
+
+#[test]
+fn test_configure_watchdog() {
+    let mut odrive = init_odrive();
+    odrive
+        .configure_watchdog(
+            AxisID::Zero,
+            WatchdogConfig::enabled(Duration::from_millis(250)),
+        )
+        .unwrap();
+    assert_eq!(
+        b"w axis0.config.watchdog_timeout 0.25\nu 0\nw axis0.config.enable_watchdog 1\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+
+    let mut odrive = init_odrive();
+    odrive
+        .configure_watchdog(AxisID::One, WatchdogConfig::disabled())
+        .unwrap();
+    assert_eq!(
+        b"w axis1.config.enable_watchdog 0\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_watchdog_config() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1\n");
+    odrive.io_stream.get_mut().push_response(b"0.5\n");
+    assert_eq!(
+        WatchdogConfig::enabled(Duration::from_millis(500)),
+        odrive.read_watchdog_config(AxisID::One).unwrap()
+    );
+    assert_eq!(
+        b"r axis1.config.enable_watchdog\nr axis1.config.watchdog_timeout\n".to_vec(),
+        odrive.io_stream.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_read_watchdog_config_negative_timeout() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"0\n");
+    odrive.io_stream.get_mut().push_response(b"-1\n");
+    match odrive.read_watchdog_config(AxisID::Zero) {
+        Err(ODriveError::InvalidMessageReceived(message)) => assert_eq!("-1", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_watchdog_config_timeout_overflow() {
+    let mut odrive = init_odrive();
+    odrive.io_stream.get_mut().push_response(b"1\n");
+    odrive.io_stream.get_mut().push_response(b"1e30\n");
+    match odrive.read_watchdog_config(AxisID::Zero) {
+        Err(ODriveError::InvalidMessageReceived(_)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_watchdog_feed_interval() {
+    let config = WatchdogConfig::enabled(Duration::from_millis(300));
+    assert_eq!(Duration::from_millis(100), config.feed_interval());
+}
+
+#[test]
+fn test_watchdog_guard_feeds_until_dropped() {
+    let odrive = Arc::new(Mutex::new(init_odrive()));
+    let guard = WatchdogGuard::new(
+        odrive.clone(),
+        &[AxisID::Zero, AxisID::One],
+        Duration::from_millis(1),
+    );
+    sleep(Duration::from_millis(20));
+    assert!(guard.is_feeding());
+    guard.stop().unwrap();
+
+    let mut odrive = odrive.lock().unwrap();
+    let written = odrive.io_stream.get_mut().write_buffer.clone();
+    assert!(written.len() >= 2 * b"u 0\nu 1\n".len());
+    assert!(written.chunks(8).all(|chunk| chunk == b"u 0\nu 1\n"));
+
+    sleep(Duration::from_millis(5));
+    assert_eq!(written, odrive.io_stream.get_mut().write_buffer);
+}
@@ -0,0 +1,25 @@
+use std::str::FromStr;
+
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+
+/// The position and velocity of an axis, as sent by the ODrive in reply to the `f` command.
+/// Both values are in the units used by the firmware: encoder counts for firmware 0.4.x.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Feedback {
+    pub position: f32,
+    pub velocity: f32,
+}
+
+/// Parses a reply of the form `<position> <velocity>`.
+impl FromStr for Feedback {
+    type Err = ODriveError;
+
+    fn from_str(s: &str) -> ODriveResult<Self> {
+        let invalid = || ODriveError::InvalidMessageReceived(s.to_owned());
+        let mut values = s.split_whitespace().map(str::parse::<f32>);
+        match (values.next(), values.next(), values.next()) {
+            (Some(Ok(position)), Some(Ok(velocity)), None) => Ok(Feedback { position, velocity }),
+            _ => Err(invalid()),
+        }
+    }
+}
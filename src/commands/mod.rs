@@ -1,16 +1,66 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::fmt::Display;
 use std::io;
-use std::io::{BufReader, Error, Read, Write};
-use std::time::Instant;
+use std::io::{BufRead, BufReader, Error, Read, Write};
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
-use crate::enumerations::errors::{ODriveError, ODriveResult};
-use crate::enumerations::{AxisID, AxisState, ControlMode, EncoderMode};
+use crate::config::Tolerance;
+use crate::enumerations::errors::{
+    AxisErrorReport, ErrorReport, ODriveError, ODriveResult, TransitionError,
+};
+use crate::enumerations::{AxisID, AxisState, ControlMode, EncoderMode, InputMode};
+use crate::interface::ODriveInterface;
 
 #[cfg(test)]
 #[cfg_attr(tarpaulin, skip)]
 mod command_tests;
 
-const READ_TIMEOUT: u128 = 100;
+mod feedback;
+mod firmware;
+mod transition;
+mod trap_traj;
+mod watchdog;
+
+pub use feedback::Feedback;
+
+pub use firmware::{Capabilities, FirmwareVersion, HardwareVersion, PositionUnit};
+
+pub use trap_traj::TrapTrajConfig;
+
+pub use transition::{
+    Transition, TransitionEnd, DEFAULT_POLL_INTERVAL, DEFAULT_TRANSITION_TIMEOUT,
+};
+
+pub(crate) use transition::{transition_error, TransitionStep, TransitionWatch};
+
+pub use watchdog::{WatchdogConfig, WatchdogGuard};
+
+/// The default time to wait for a response from the ODrive.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The time to sleep between reads while the stream has no data available.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The time to wait for stale messages when flushing the input.
+const FLUSH_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// How far a float read back from the ODrive may be from the written value, since the ODrive
+/// prints floats with six decimals, for example `0.000021` for `2.1e-5`.
+pub const PRINTED_FLOAT_TOLERANCE: Tolerance = Tolerance {
+    absolute: 0.000_001,
+    relative: 0.0,
+};
+
+/// The replies of the ODrive to commands it cannot execute.
+const ERROR_REPLIES: [&str; 4] = [
+    "invalid property",
+    "invalid value",
+    "invalid command format",
+    "unknown command",
+];
 
 /// The `ODrive` struct manages a connection with an ODrive motor over the ASCII protocol.
 /// It acts as a newtype around a connection stream.
@@ -21,6 +71,13 @@ where
     T: Read,
 {
     io_stream: BufReader<T>,
+    timeout: Duration,
+    /// Holds the start of a message whose end has not been received yet.
+    line_buffer: Vec<u8>,
+    /// The axes of the feedback requests whose replies have not been read yet, oldest first.
+    pending_feedback: VecDeque<AxisID>,
+    /// What the firmware supports, set by `detect_firmware`.
+    capabilities: Capabilities,
 }
 
 impl<T> ODrive<T>
@@ -30,10 +87,52 @@ where
     /// Although any type can be passed in here, it is suggested that the supplied type `T` be
     /// `Read + Write`. Doing so will unlock the full API.
     pub fn new(io_stream: T) -> Self {
+        Self::with_timeout(io_stream, DEFAULT_TIMEOUT)
+    }
+
+    /// Creates a connection which waits up to `timeout` for each response of the ODrive.
+    pub fn with_timeout(io_stream: T, timeout: Duration) -> Self {
         Self {
             io_stream: BufReader::new(io_stream),
+            timeout,
+            line_buffer: Vec::new(),
+            pending_feedback: VecDeque::new(),
+            capabilities: Capabilities::default(),
         }
     }
+
+    /// The time to wait for a response from the ODrive.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Changes the time to wait for a response from the ODrive.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// What the firmware of the ODrive supports. Until `detect_firmware` is called, firmware
+    /// 0.4.12 is assumed.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Changes what the firmware of the ODrive is assumed to support, for example when its version
+    /// is already known.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &T {
+        self.io_stream.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying stream. Be advised that reading from it
+    /// directly may place the connection into an inconsistent state.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.io_stream.get_mut()
+    }
 }
 
 /// An implementation of `Write` has been provided as an escape hatch to enable the usage of
@@ -59,7 +158,14 @@ where
     T: Read,
 {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        self.io_stream.read(buf)
+        if self.line_buffer.is_empty() {
+            return self.io_stream.read(buf);
+        }
+
+        let count = buf.len().min(self.line_buffer.len());
+        buf[..count].copy_from_slice(&self.line_buffer[..count]);
+        self.line_buffer.drain(..count);
+        Ok(count)
     }
 }
 
@@ -67,6 +173,47 @@ impl<T> ODrive<T>
 where
     T: Read,
 {
+    /// Reads the next line, waiting up to `timeout` for it to be completed.
+    /// A partially received line is kept until the next call.
+    fn read_line(&mut self, timeout: Duration) -> io::Result<Option<String>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let (consumed, complete) = match self.io_stream.fill_buf() {
+                Ok(available) => match available.iter().position(|ch| *ch == b'\n') {
+                    Some(end) => {
+                        self.line_buffer.extend_from_slice(&available[..end]);
+                        (end + 1, true)
+                    }
+                    None => {
+                        self.line_buffer.extend_from_slice(available);
+                        (available.len(), false)
+                    }
+                },
+                Err(error) => match error.kind() {
+                    io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted => (0, false),
+                    _ => return Err(error),
+                },
+            };
+            self.io_stream.consume(consumed);
+
+            if complete {
+                let line = String::from_utf8_lossy(&self.line_buffer).trim().to_owned();
+                self.line_buffer.clear();
+                return Ok(Some(line));
+            }
+
+            if consumed == 0 {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(None);
+                }
+                sleep(POLL_INTERVAL.min(deadline - now));
+            }
+        }
+    }
+
     /// Reads the next message sent by the ODrive as a string.
     /// If their is no message, this function should return `None`
     ///
@@ -74,45 +221,37 @@ where
     /// and are expecting a response, as normally the supplied for the ODrive can directly support
     /// reading any response.
     pub fn read_string(&mut self) -> io::Result<Option<String>> {
-        let mut string = String::with_capacity(20);
-        let duration = Instant::now();
-        loop {
-            let mut buffer = [0; 1];
-            while self.read(&mut buffer).unwrap_or_default() == 0 {
-                if duration.elapsed().as_millis() >= READ_TIMEOUT {
-                    return Ok(None);
-                }
-            }
-            let ch = buffer[0];
-            if ch as char == '\n' {
-                break;
-            }
-
-            string.push(ch as char);
-        }
+        self.read_string_timeout(self.timeout)
+    }
 
-        Ok(Some(string.trim().to_owned()))
+    /// Same as `read_string`, but waits up to `timeout` instead of the timeout of the connection.
+    pub fn read_string_timeout(&mut self, timeout: Duration) -> io::Result<Option<String>> {
+        self.read_line(timeout)
     }
 
+    /// Reads the next message sent by the ODrive.
+    /// Returns `NoMessageReceived` if no message arrives before the timeout of the connection.
     pub fn read_odrive_response(&mut self) -> ODriveResult<String> {
-        let mut string = String::with_capacity(20);
-        let duration = Instant::now();
-        loop {
-            let mut buffer = [0; 1];
-            while self.read(&mut buffer).unwrap_or_default() == 0 {
-                if duration.elapsed().as_millis() >= READ_TIMEOUT {
-                    return Err(ODriveError::NoMessageReceived);
-                }
-            }
-            let ch = buffer[0];
-            if ch as char == '\n' {
-                break;
-            }
+        self.read_odrive_response_timeout(self.timeout)
+    }
 
-            string.push(ch as char);
-        }
+    /// Same as `read_odrive_response`, but waits up to `timeout` instead of the timeout of the
+    /// connection.
+    pub fn read_odrive_response_timeout(&mut self, timeout: Duration) -> ODriveResult<String> {
+        self.read_line(timeout)
+            .map_err(ODriveError::Io)?
+            .ok_or(ODriveError::NoMessageReceived)
+    }
 
-        Ok(string.trim().to_owned())
+    /// Discards all messages which have already been sent by the ODrive.
+    fn flush_input(&mut self) -> ODriveResult<()> {
+        while self
+            .read_line(FLUSH_TIMEOUT)
+            .map_err(ODriveError::Io)?
+            .is_some()
+        {}
+        self.line_buffer.clear();
+        Ok(())
     }
 
     /// Reads the next message as a float. This will return zero if the message is not a valid
@@ -135,6 +274,9 @@ where
     }
 }
 
+/// # Motion
+/// Positions and velocities are in encoder counts for firmware 0.4.x and in turns since firmware
+/// 0.5.0, see `Capabilities::position_unit`.
 impl<T> ODrive<T>
 where
     T: Write + Read,
@@ -223,6 +365,60 @@ where
         self.flush()
     }
 
+    /// Feeds the watchdog of an axis, which restarts its `watchdog_timeout`.
+    /// `WatchdogGuard` can do this periodically from a helper thread.
+    pub fn feed_watchdog(&mut self, axis: AxisID) -> io::Result<()> {
+        writeln!(self, "u {}", axis as u8)?;
+        self.flush()
+    }
+
+    /// Requests the position and velocity of an axis with the `f` command.
+    /// The reply can be read later with `read_feedback`, which matches it to this request.
+    ///
+    /// The command is not flushed, so that it can be called in a fast loop. Read the replies to all
+    /// pending requests before sending another command which expects a reply.
+    pub fn request_feedback(&mut self, axis: AxisID) -> io::Result<()> {
+        writeln!(self, "f {}", axis as u8)?;
+        self.pending_feedback.push_back(axis);
+        Ok(())
+    }
+
+    /// The number of feedback requests whose replies have not been read yet.
+    pub fn pending_feedback(&self) -> usize {
+        self.pending_feedback.len()
+    }
+
+    /// Reads the reply to the oldest pending feedback request, returning the axis it belongs to.
+    ///
+    /// Returns `NoMessageReceived` if the reply does not arrive in time, in which case the request
+    /// stays pending. A malformed reply is consumed along with its request and returned as
+    /// `InvalidMessageReceived`, as is a reply received while no request is pending.
+    pub fn read_feedback(&mut self) -> ODriveResult<(AxisID, Feedback)> {
+        self.read_feedback_timeout(self.timeout)
+    }
+
+    /// Same as `read_feedback`, but waits up to `timeout` instead of the timeout of the connection.
+    pub fn read_feedback_timeout(&mut self, timeout: Duration) -> ODriveResult<(AxisID, Feedback)> {
+        let response = self.read_odrive_response_timeout(timeout)?;
+        let axis = match self.pending_feedback.pop_front() {
+            Some(axis) => axis,
+            None => return Err(ODriveError::InvalidMessageReceived(response)),
+        };
+        Ok((axis, response.parse()?))
+    }
+
+    /// Requests the position and velocity of an axis and waits for the reply.
+    pub fn get_feedback(&mut self, axis: AxisID) -> ODriveResult<Feedback> {
+        self.request_feedback(axis).map_err(ODriveError::Io)?;
+        self.flush().map_err(ODriveError::Io)?;
+        loop {
+            let (reply_axis, feedback) = self.read_feedback()?;
+            if reply_axis == axis && self.pending_feedback.is_empty() {
+                return Ok(feedback);
+            }
+        }
+    }
+
     /// Convenience function for setting both currents and requesting feedback
     /// Feedback can be obtained some time later with try_read_both_velocities function
     /// To be meant for calling in a fast loop (no flush which would take about 30 ms with SerialPort crate)
@@ -233,28 +429,27 @@ where
     ) -> io::Result<()> {
         writeln!(self, "c 0 {}", current_axis0)?;
         writeln!(self, "c 1 {}", current_axis1)?;
-        writeln!(self, "f 0")?;
-        writeln!(self, "f 1")?;
-        Ok(())
+        self.request_feedback(AxisID::Zero)?;
+        self.request_feedback(AxisID::One)
     }
-    /// Convenience function for setting both currents and requesting both velocities (in turns/s)
+
+    /// Convenience function for reading the velocities requested by
+    /// `set_both_currents_and_request_feedback` without waiting for them.
+    /// The velocities are in counts per second for firmware 0.4.x and in turns per second since
+    /// firmware 0.5.0, see `ScaledAxis::get_feedback` for typed units.
+    /// Replies which have not been completely received yet are kept for the next call, and
+    /// malformed replies are skipped.
     pub fn try_read_both_velocities(&mut self) -> (Option<f32>, Option<f32>) {
-        let mut buf = [0; 128];
-        let size = self.read(&mut buf).unwrap_or_default();
-
-        let mut velocities: Vec<f32> = Vec::new();
-        if let Ok(str_buf) = std::str::from_utf8(&buf[0..size]) {
-            for line in str_buf.lines().rev() {
-                let values = line
-                    .split_whitespace()
-                    .flat_map(str::parse::<f32>)
-                    .collect::<Vec<_>>();
-                if values.len() == 2 {
-                    velocities.push(values[1]);
-                }
+        let mut velocities = (None, None);
+        while !self.pending_feedback.is_empty() {
+            match self.read_feedback_timeout(Duration::from_secs(0)) {
+                Ok((AxisID::Zero, feedback)) => velocities.0 = Some(feedback.velocity),
+                Ok((AxisID::One, feedback)) => velocities.1 = Some(feedback.velocity),
+                Err(ODriveError::InvalidMessageReceived(_)) => {}
+                Err(_) => break,
             }
         }
-        (velocities.pop(), velocities.pop())
+        velocities
     }
 }
 
@@ -262,43 +457,192 @@ impl<T> ODrive<T>
 where
     T: Read + Write,
 {
-    /// Retrieves the velocity of a motor, in counts per second.
+    /// Retrieves the velocity of a motor, in counts per second for firmware 0.4.x and in turns per
+    /// second since firmware 0.5.0.
     pub fn get_velocity(&mut self, axis: AxisID) -> io::Result<Option<f32>> {
         writeln!(self, "r axis{}.encoder.vel_estimate", axis as u8)?;
         self.flush()?;
         self.read_float()
     }
 
-    /// Changes the state of an axis.
-    /// The `wait` flag indicates whether this command should block until the state is updated.
-    /// Returns true unless we are in blocking mode and the operation times out.
-    /// The current timeout is 10 seconds.
+    /// Retrieves the current state of an axis.
+    pub fn current_state(&mut self, axis: AxisID) -> ODriveResult<AxisState> {
+        let response = self.get_axis_property(axis, "current_state")?;
+        parse_enum(response)
+    }
+
+    /// Requests a new state for an axis without waiting for it.
+    pub fn request_state(&mut self, axis: AxisID, requested_state: AxisState) -> ODriveResult<()> {
+        self.set_axis_property(axis, "requested_state", requested_state as u8)
+    }
+
+    /// Changes the state of an axis and waits as configured by `transition`.
     ///
-    /// This command will likely be deprecated and reworked in a future release.
+    /// A transition fails with `ODriveError::StateTransition` if it does not complete before the
+    /// timeout, or if the axis is back in `Idle` with errors. The error contains the last observed
+    /// state and the errors read from the axis.
     pub fn run_state(
         &mut self,
         axis: AxisID,
         requested_state: AxisState,
-        wait: bool,
-    ) -> io::Result<bool> {
-        let timer = Instant::now();
-        writeln!(
-            self,
-            "w axis{}.requested_state {}",
-            axis as u8, requested_state as u8
-        )?;
-        self.flush()?;
-        if wait {
-            while {
-                writeln!(self, "r axis{}.current_state", axis as u8)?;
-                self.flush()?;
-
-                self.read_int()?.unwrap_or_default() != AxisState::Idle as i32
-                    && timer.elapsed().as_millis() < 10_000 // exit
-            } {}
+        transition: Transition,
+    ) -> ODriveResult<()> {
+        ODriveInterface::run_state(self, axis, requested_state, transition)
+    }
+}
+
+impl<T> ODriveInterface for ODrive<T>
+where
+    T: Read + Write,
+{
+    fn set_velocity(
+        &mut self,
+        axis: AxisID,
+        velocity: f32,
+        current_feed_forward: Option<f32>,
+    ) -> ODriveResult<()> {
+        ODrive::set_velocity(self, axis, velocity, current_feed_forward).map_err(ODriveError::Io)
+    }
+
+    fn set_current(&mut self, axis: AxisID, current: f32) -> ODriveResult<()> {
+        ODrive::set_current(self, axis, current).map_err(ODriveError::Io)
+    }
+
+    fn get_feedback(&mut self, axis: AxisID) -> ODriveResult<Feedback> {
+        ODrive::get_feedback(self, axis)
+    }
+
+    fn current_state(&mut self, axis: AxisID) -> ODriveResult<AxisState> {
+        ODrive::current_state(self, axis)
+    }
+
+    fn request_state(&mut self, axis: AxisID, requested_state: AxisState) -> ODriveResult<()> {
+        ODrive::request_state(self, axis, requested_state)
+    }
+
+    fn read_axis_errors(&mut self, axis: AxisID) -> ODriveResult<AxisErrorReport> {
+        ODrive::read_axis_errors(self, axis)
+    }
+
+    fn clear_errors(&mut self) -> ODriveResult<()> {
+        ODrive::clear_errors(self)
+    }
+}
+
+/// # Properties
+/// Any property of the ODrive can be read and written by its path, such as
+/// `axis0.motor.config.current_lim`. Unlike the `Write` escape hatch, these methods detect when
+/// the ODrive rejects a path or a value.
+///
+/// Booleans are sent and received as `0` or `1`, so they should be read as integers.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Reads the property at `path`.
+    pub fn read_property<V: FromStr>(&mut self, path: &str) -> ODriveResult<V> {
+        let response = self.read_property_response(path)?;
+        parse_response(response)
+    }
+
+    /// Writes `value` to the property at `path`.
+    ///
+    /// The ODrive does not reply to successful writes, so the property is read right after it is
+    /// written to find out whether the write was rejected.
+    pub fn write_property<D: Display>(&mut self, path: &str, value: D) -> ODriveResult<()> {
+        self.write_property_response(path, value).map(|_| ())
+    }
+
+    /// Same as `write_property`, but also checks that the property reads back `value`.
+    /// Returns `PropertyMismatch` otherwise, for example if the ODrive clamped the value.
+    ///
+    /// The ODrive prints floats with six decimals, so floats are compared within
+    /// `PRINTED_FLOAT_TOLERANCE`. This also accepts an integer written to a float property.
+    pub fn write_property_verified<V>(&mut self, path: &str, value: V) -> ODriveResult<()>
+    where
+        V: Display + FromStr + PartialEq,
+    {
+        let response = self.write_property_response(path, &value)?;
+        if printed_float_matches(&response, &value.to_string()) {
+            return Ok(());
+        }
+        let read: V = parse_response(response.clone())?;
+        if read == value {
+            Ok(())
+        } else {
+            Err(ODriveError::PropertyMismatch {
+                path: path.to_owned(),
+                written: value.to_string(),
+                read: response,
+            })
         }
+    }
+
+    /// Same as `write_property`, but returns the value the property reads back after the write.
+    pub fn write_property_read_back<D, V>(&mut self, path: &str, value: D) -> ODriveResult<V>
+    where
+        D: Display,
+        V: FromStr,
+    {
+        let response = self.write_property_response(path, value)?;
+        parse_response(response)
+    }
 
-        Ok(timer.elapsed().as_millis() < 10_000)
+    /// Reads the property at `path`, mapping an error reply to `InvalidProperty`.
+    pub(crate) fn read_property_response(&mut self, path: &str) -> ODriveResult<String> {
+        let response = self.get_config_property(path)?;
+        check_property_reply(path, response)
+    }
+
+    /// Writes the property at `path` and returns the value it reads back.
+    fn write_property_response<D: Display>(
+        &mut self,
+        path: &str,
+        value: D,
+    ) -> ODriveResult<String> {
+        let param = self.capabilities.property_path(path);
+        writeln!(self, "w {} {}", param, value).map_err(ODriveError::Io)?;
+        writeln!(self, "r {}", param).map_err(ODriveError::Io)?;
+        self.flush().map_err(ODriveError::Io)?;
+
+        let response = self.read_odrive_response()?;
+        if ERROR_REPLIES.contains(&response.as_str()) {
+            // The reply to the read follows the reply to the rejected write
+            self.read_odrive_response()?;
+            return Err(ODriveError::InvalidProperty {
+                path: path.to_owned(),
+                reply: response,
+            });
+        }
+        check_property_reply(path, response)
+    }
+}
+
+/// # Firmware version
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Reads the firmware and hardware versions of the ODrive, and adapts the following commands
+    /// to them. Returns `Unsupported` for firmware other than 0.4.x and 0.5.x.
+    pub fn detect_firmware(&mut self) -> ODriveResult<Capabilities> {
+        let firmware = FirmwareVersion {
+            major: self.read_property("fw_version_major")?,
+            minor: self.read_property("fw_version_minor")?,
+            revision: self.read_property("fw_version_revision")?,
+        };
+        let hardware = HardwareVersion {
+            major: self.read_property("hw_version_major")?,
+            minor: self.read_property("hw_version_minor")?,
+            variant: self.read_property("hw_version_variant")?,
+        };
+
+        let capabilities = Capabilities::new(firmware, Some(hardware));
+        if !capabilities.is_supported() {
+            return Err(ODriveError::Unsupported(format!("firmware {}", firmware)));
+        }
+        self.capabilities = capabilities;
+        Ok(capabilities)
     }
 }
 
@@ -307,12 +651,18 @@ impl<T> ODrive<T>
 where
     T: Read + Write,
 {
-    fn set_config_property<D: Display>(&mut self, param: &str, value: D) -> ODriveResult<()> {
+    pub(crate) fn set_config_property<D: Display>(
+        &mut self,
+        param: &str,
+        value: D,
+    ) -> ODriveResult<()> {
+        let param = self.capabilities.property_path(param);
         writeln!(self, "w {} {}", param, value).map_err(ODriveError::Io)?;
         self.flush().map_err(ODriveError::Io)
     }
 
-    fn get_config_property(&mut self, param: &str) -> ODriveResult<String> {
+    pub(crate) fn get_config_property(&mut self, param: &str) -> ODriveResult<String> {
+        let param = self.capabilities.property_path(param);
         writeln!(self, "r {}", param).map_err(ODriveError::Io)?;
         self.flush().map_err(ODriveError::Io)?;
         self.read_odrive_response()
@@ -338,12 +688,66 @@ where
         self.set_config_property(&config, value)
     }
 
+    fn get_axis_property(&mut self, axis: AxisID, property: &str) -> ODriveResult<String> {
+        let config = format!("axis{}.{}", axis as u8, property);
+        self.get_config_property(&config)
+    }
+
     fn get_axis_config_property(&mut self, axis: AxisID, name: &str) -> ODriveResult<String> {
         let config = format!("axis{}.config.{}", axis as u8, name);
         self.get_config_property(&config)
     }
 }
 
+/// Whether `response` is a float printed by the ODrive for the written value `written`.
+/// Floats are printed with `%f`, which always has a decimal point, unlike integers.
+fn printed_float_matches(response: &str, written: &str) -> bool {
+    if !response.contains('.') {
+        return false;
+    }
+    match (response.parse::<f32>(), written.parse::<f32>()) {
+        (Ok(read), Ok(written)) => PRINTED_FLOAT_TOLERANCE.matches(read, written),
+        _ => false,
+    }
+}
+
+/// Maps an error reply of the ODrive to the property at `path` to `InvalidProperty`.
+fn check_property_reply(path: &str, response: String) -> ODriveResult<String> {
+    if ERROR_REPLIES.contains(&response.as_str()) {
+        Err(ODriveError::InvalidProperty {
+            path: path.to_owned(),
+            reply: response,
+        })
+    } else {
+        Ok(response)
+    }
+}
+
+/// Parses a response of the ODrive, mapping invalid values to `InvalidMessageReceived`.
+pub(crate) fn parse_response<V: FromStr>(response: String) -> ODriveResult<V> {
+    match response.parse() {
+        Ok(value) => Ok(value),
+        Err(_error) => Err(ODriveError::InvalidMessageReceived(response)),
+    }
+}
+
+/// Parses a boolean property, which the ODrive reports as `0` or `1`.
+pub(crate) fn parse_bool(response: String) -> ODriveResult<bool> {
+    match response.parse::<u8>() {
+        Ok(0) => Ok(false),
+        Ok(1) => Ok(true),
+        _ => Err(ODriveError::InvalidMessageReceived(response)),
+    }
+}
+
+/// Parses an enumeration property, which the ODrive reports as an integer.
+pub(crate) fn parse_enum<E: TryFrom<i32, Error = ODriveError>>(
+    response: String,
+) -> ODriveResult<E> {
+    let value = parse_response::<i32>(response)?;
+    E::try_from(value)
+}
+
 /// # Startup Configuration
 /// The ODrive motor controllers have several optional startup procedures which can be enabled.
 /// Each of them has an associated getter and setter which can be invoked to read to and write from
@@ -403,62 +807,27 @@ where
 
     pub fn read_startup_motor_calibration(&mut self, axis: AxisID) -> ODriveResult<bool> {
         let response = self.get_axis_config_property(axis, "startup_motor_calibration")?;
-        match response.parse::<u8>() {
-            Ok(val) => match val {
-                0 => Ok(false),
-                1 => Ok(true),
-                _ => Err(ODriveError::InvalidMessageReceived(response)),
-            },
-            Err(_error) => Err(ODriveError::InvalidMessageReceived(response)),
-        }
+        parse_bool(response)
     }
 
     pub fn read_startup_encoder_index_search(&mut self, axis: AxisID) -> ODriveResult<bool> {
         let response = self.get_axis_config_property(axis, "startup_encoder_index_search")?;
-        match response.parse::<u8>() {
-            Ok(val) => match val {
-                0 => Ok(false),
-                1 => Ok(true),
-                _ => Err(ODriveError::InvalidMessageReceived(response)),
-            },
-            Err(_error) => Err(ODriveError::InvalidMessageReceived(response)),
-        }
+        parse_bool(response)
     }
 
     pub fn read_startup_encoder_offset_calibration(&mut self, axis: AxisID) -> ODriveResult<bool> {
         let response = self.get_axis_config_property(axis, "startup_encoder_offset_calibration")?;
-        match response.parse::<u8>() {
-            Ok(val) => match val {
-                0 => Ok(false),
-                1 => Ok(true),
-                _ => Err(ODriveError::InvalidMessageReceived(response)),
-            },
-            Err(_error) => Err(ODriveError::InvalidMessageReceived(response)),
-        }
+        parse_bool(response)
     }
 
     pub fn read_startup_closed_loop_control(&mut self, axis: AxisID) -> ODriveResult<bool> {
         let response = self.get_axis_config_property(axis, "startup_closed_loop_control")?;
-        match response.parse::<u8>() {
-            Ok(val) => match val {
-                0 => Ok(false),
-                1 => Ok(true),
-                _ => Err(ODriveError::InvalidMessageReceived(response)),
-            },
-            Err(_error) => Err(ODriveError::InvalidMessageReceived(response)),
-        }
+        parse_bool(response)
     }
 
     pub fn read_startup_sensorless_control(&mut self, axis: AxisID) -> ODriveResult<bool> {
         let response = self.get_axis_config_property(axis, "startup_sensorless_control")?;
-        match response.parse::<u8>() {
-            Ok(val) => match val {
-                0 => Ok(false),
-                1 => Ok(true),
-                _ => Err(ODriveError::InvalidMessageReceived(response)),
-            },
-            Err(_error) => Err(ODriveError::InvalidMessageReceived(response)),
-        }
+        parse_bool(response)
     }
 }
 
@@ -492,35 +861,39 @@ where
         self.flush().map_err(ODriveError::Io)
     }
 
-    fn read_axis_errors(&mut self, axis: AxisID) -> ODriveResult<String> {
+    fn read_error_property(&mut self, param: &str) -> ODriveResult<u64> {
+        let response = self.get_config_property(param)?;
+        response
+            .parse()
+            .map_err(|_| ODriveError::InvalidMessageReceived(response))
+    }
+
+    /// Read the errors of an axis and its motor, encoder and controller.
+    pub fn read_axis_errors(&mut self, axis: AxisID) -> ODriveResult<AxisErrorReport> {
         let axis = axis as u8;
-        let mut result = String::new();
-        result += "Motor=";
-        result += &self.get_config_property(&format!("axis{axis}.motor.error"))?;
-        result += ", Controller=";
-        result += &self.get_config_property(&format!("axis{axis}.controller.error"))?;
-        result += ", Encoder=";
-        result += &self.get_config_property(&format!("axis{axis}.encoder.error"))?;
-
-        Ok(result)
-    }
-
-    /// Read all errors and return them as string.
-    pub fn read_all_errors(&mut self) -> ODriveResult<String> {
-        // flush input buffer
-        let duration = Instant::now();
-        let mut buffer = [0; 1];
-        while duration.elapsed().as_millis() < 10 {
-            self.read(&mut buffer).unwrap_or_default();
-        }
-        let mut result = String::new();
-        result += "Config=";
-        result += &self.get_config_property("error")?;
-        result += "\nAxis0: ";
-        result += &self.read_axis_errors(AxisID::Zero)?;
-        result += "\nAxis1: ";
-        result += &self.read_axis_errors(AxisID::One)?;
-        Ok(result)
+        let axis_error = self.read_error_property(&format!("axis{}.error", axis))?;
+        let motor = self.read_error_property(&format!("axis{}.motor.error", axis))?;
+        let encoder = self.read_error_property(&format!("axis{}.encoder.error", axis))?;
+        let controller = self.read_error_property(&format!("axis{}.controller.error", axis))?;
+
+        Ok(AxisErrorReport::for_firmware(
+            axis_error,
+            motor,
+            encoder,
+            controller,
+            &self.capabilities,
+        ))
+    }
+
+    /// Read all errors reported by the ODrive.
+    pub fn read_all_errors(&mut self) -> ODriveResult<ErrorReport> {
+        self.flush_input()?;
+
+        Ok(ErrorReport {
+            system: self.read_error_property("error")?,
+            axis0: self.read_axis_errors(AxisID::Zero)?,
+            axis1: self.read_axis_errors(AxisID::One)?,
+        })
     }
 }
 
@@ -560,6 +933,31 @@ where
     pub fn set_motor_pre_calibrated(&mut self, axis: AxisID, value: bool) -> ODriveResult<()> {
         self.set_axis_property(axis, "motor.config.pre_calibrated", value as u8)
     }
+
+    pub fn read_motor_pole_pairs(&mut self, axis: AxisID) -> ODriveResult<u16> {
+        let response = self.get_axis_property(axis, "motor.config.pole_pairs")?;
+        parse_response(response)
+    }
+
+    pub fn read_motor_resistance_calib_max_voltage(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "motor.config.resistance_calib_max_voltage")?;
+        parse_response(response)
+    }
+
+    pub fn read_motor_requested_current_range(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "motor.config.requested_current_range")?;
+        parse_response(response)
+    }
+
+    pub fn read_motor_current_control_bandwidth(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "motor.config.current_control_bandwidth")?;
+        parse_response(response)
+    }
+
+    pub fn read_motor_pre_calibrated(&mut self, axis: AxisID) -> ODriveResult<bool> {
+        let response = self.get_axis_property(axis, "motor.config.pre_calibrated")?;
+        parse_bool(response)
+    }
 }
 
 /// Encoder configuration
@@ -582,6 +980,28 @@ where
     pub fn set_encoder_pre_calibrated(&mut self, axis: AxisID, value: bool) -> ODriveResult<()> {
         self.set_axis_property(axis, "encoder.config.pre_calibrated", value as u8)
     }
+
+    pub fn read_encoder_mode(&mut self, axis: AxisID) -> ODriveResult<EncoderMode> {
+        let response = self.get_axis_property(axis, "encoder.config.mode")?;
+        parse_enum(response)
+    }
+
+    /// Reads the counts per revolution, which is an `int32` in the firmware and can exceed the
+    /// range of `set_encoder_cpr`, for example with a 17 bit absolute encoder.
+    pub fn read_encoder_cpr(&mut self, axis: AxisID) -> ODriveResult<i32> {
+        let response = self.get_axis_property(axis, "encoder.config.cpr")?;
+        parse_response(response)
+    }
+
+    pub fn read_encoder_bandwidth(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "encoder.config.bandwidth")?;
+        parse_response(response)
+    }
+
+    pub fn read_encoder_pre_calibrated(&mut self, axis: AxisID) -> ODriveResult<bool> {
+        let response = self.get_axis_property(axis, "encoder.config.pre_calibrated")?;
+        parse_bool(response)
+    }
 }
 
 /// Controller configuration
@@ -605,7 +1025,283 @@ where
         self.set_axis_property(axis, "controller.config.vel_limit", value)
     }
 
+    /// Since firmware 0.5.0, `TrajectoryControl` selects position control with the trapezoidal
+    /// trajectory input mode.
     pub fn set_control_mode(&mut self, axis: AxisID, mode: ControlMode) -> ODriveResult<()> {
-        self.set_axis_property(axis, "controller.config.control_mode", mode as u8)
+        let value = self.capabilities.control_mode_value(mode);
+        self.set_axis_property(axis, "controller.config.control_mode", value)?;
+        if mode == ControlMode::TrajectoryControl && self.capabilities.has_input_modes() {
+            self.set_input_mode(axis, InputMode::TrapTraj)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_position_gain(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.config.pos_gain")?;
+        parse_response(response)
+    }
+
+    pub fn read_velocity_gain(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.config.vel_gain")?;
+        parse_response(response)
+    }
+
+    pub fn read_velocity_integrator_gain(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.config.vel_integrator_gain")?;
+        parse_response(response)
+    }
+
+    pub fn read_velocity_limit(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.config.vel_limit")?;
+        parse_response(response)
+    }
+
+    pub fn read_control_mode(&mut self, axis: AxisID) -> ODriveResult<ControlMode> {
+        let response = self.get_axis_property(axis, "controller.config.control_mode")?;
+        let value = parse_response(response)?;
+        self.capabilities.control_mode_from_value(value)
+    }
+}
+
+/// # Controller inputs
+/// The inputs of the controller are followed according to the input mode. Firmware 0.4.x has no
+/// input modes and passes the inputs through, as its setpoints `pos_setpoint`, `vel_setpoint` and
+/// `current_setpoint`, so the input mode, the filter, the torque ramp and mirroring require
+/// firmware 0.5.0 or newer and return `Unsupported` otherwise.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    pub fn set_input_pos(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.set_axis_property(axis, "controller.input_pos", value)
+    }
+
+    pub fn set_input_vel(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.set_axis_property(axis, "controller.input_vel", value)
+    }
+
+    /// `value` is a torque in Nm, or a current in A for firmware 0.4.x.
+    pub fn set_input_torque(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.set_axis_property(axis, "controller.input_torque", value)
+    }
+
+    pub fn set_input_mode(&mut self, axis: AxisID, mode: InputMode) -> ODriveResult<()> {
+        self.require_input_modes("input modes")?;
+        self.set_axis_property(axis, "controller.config.input_mode", mode as u8)
+    }
+
+    /// The bandwidth of the `PosFilter` input mode, in Hz.
+    pub fn set_input_filter_bandwidth(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.require_input_modes("input_filter_bandwidth")?;
+        self.set_axis_property(axis, "controller.config.input_filter_bandwidth", value)
+    }
+
+    /// The acceleration of the `VelRamp` input mode. Firmware 0.4.x uses it while
+    /// `<axis>.controller.vel_ramp_enable` is set.
+    pub fn set_vel_ramp_rate(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.set_axis_property(axis, "controller.config.vel_ramp_rate", value)
+    }
+
+    /// The rate of change of the torque in the `TorqueRamp` input mode, in Nm/s.
+    pub fn set_torque_ramp_rate(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.require_input_modes("torque_ramp_rate")?;
+        self.set_axis_property(axis, "controller.config.torque_ramp_rate", value)
+    }
+
+    /// Selects the axis followed by `axis` in the `Mirror` input mode.
+    pub fn set_axis_to_mirror(&mut self, axis: AxisID, source: AxisID) -> ODriveResult<()> {
+        self.require_input_modes("axis_to_mirror")?;
+        self.set_axis_property(axis, "controller.config.axis_to_mirror", source as u8)
+    }
+
+    /// The ratio between the position of `axis` and the position of the mirrored axis.
+    pub fn set_mirror_ratio(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.require_input_modes("mirror_ratio")?;
+        self.set_axis_property(axis, "controller.config.mirror_ratio", value)
+    }
+
+    pub fn read_input_pos(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.input_pos")?;
+        parse_response(response)
+    }
+
+    pub fn read_input_vel(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.input_vel")?;
+        parse_response(response)
+    }
+
+    pub fn read_input_torque(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.input_torque")?;
+        parse_response(response)
+    }
+
+    pub fn read_input_mode(&mut self, axis: AxisID) -> ODriveResult<InputMode> {
+        self.require_input_modes("input modes")?;
+        let response = self.get_axis_property(axis, "controller.config.input_mode")?;
+        parse_enum(response)
+    }
+
+    pub fn read_input_filter_bandwidth(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        self.require_input_modes("input_filter_bandwidth")?;
+        let response = self.get_axis_property(axis, "controller.config.input_filter_bandwidth")?;
+        parse_response(response)
+    }
+
+    pub fn read_vel_ramp_rate(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_property(axis, "controller.config.vel_ramp_rate")?;
+        parse_response(response)
+    }
+
+    pub fn read_torque_ramp_rate(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        self.require_input_modes("torque_ramp_rate")?;
+        let response = self.get_axis_property(axis, "controller.config.torque_ramp_rate")?;
+        parse_response(response)
+    }
+
+    pub fn read_axis_to_mirror(&mut self, axis: AxisID) -> ODriveResult<AxisID> {
+        self.require_input_modes("axis_to_mirror")?;
+        let response = self.get_axis_property(axis, "controller.config.axis_to_mirror")?;
+        parse_enum(response)
+    }
+
+    pub fn read_mirror_ratio(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        self.require_input_modes("mirror_ratio")?;
+        let response = self.get_axis_property(axis, "controller.config.mirror_ratio")?;
+        parse_response(response)
+    }
+
+    /// Returns `Unsupported` if the firmware predates input modes.
+    fn require_input_modes(&self, what: &str) -> ODriveResult<()> {
+        if self.capabilities.has_input_modes() {
+            Ok(())
+        } else {
+            Err(ODriveError::Unsupported(format!(
+                "{} on firmware {}",
+                what,
+                self.capabilities.firmware()
+            )))
+        }
+    }
+}
+
+/// # Trajectory planner
+/// The `t` command of `set_trajectory` moves an axis along a trapezoidal velocity profile,
+/// limited by its `TrapTrajConfig`. Once the move is complete, `<axis>.controller.trajectory_done`
+/// is set.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    pub fn set_trap_traj_config(
+        &mut self,
+        axis: AxisID,
+        config: TrapTrajConfig,
+    ) -> ODriveResult<()> {
+        self.set_axis_property(axis, "trap_traj.config.vel_limit", config.vel_limit)?;
+        self.set_axis_property(axis, "trap_traj.config.accel_limit", config.accel_limit)?;
+        self.set_axis_property(axis, "trap_traj.config.decel_limit", config.decel_limit)?;
+        self.set_axis_property(axis, "trap_traj.config.A_per_css", config.inertia)
+    }
+
+    pub fn read_trap_traj_config(&mut self, axis: AxisID) -> ODriveResult<TrapTrajConfig> {
+        let vel_limit = self.get_axis_property(axis, "trap_traj.config.vel_limit")?;
+        let accel_limit = self.get_axis_property(axis, "trap_traj.config.accel_limit")?;
+        let decel_limit = self.get_axis_property(axis, "trap_traj.config.decel_limit")?;
+        let inertia = self.get_axis_property(axis, "trap_traj.config.A_per_css")?;
+        Ok(TrapTrajConfig {
+            vel_limit: parse_response(vel_limit)?,
+            accel_limit: parse_response(accel_limit)?,
+            decel_limit: parse_response(decel_limit)?,
+            inertia: parse_response(inertia)?,
+        })
+    }
+
+    pub fn read_trajectory_done(&mut self, axis: AxisID) -> ODriveResult<bool> {
+        let response = self.get_axis_property(axis, "controller.trajectory_done")?;
+        parse_bool(response)
+    }
+
+    /// Waits until the trajectory of an axis is done, reading `<axis>.controller.trajectory_done`
+    /// every `DEFAULT_POLL_INTERVAL`.
+    ///
+    /// Returns `TrajectoryTimeout` if the move has not finished after `timeout`, and
+    /// `StateTransition` with the errors of the axis if it leaves closed loop control before.
+    pub fn wait_for_trajectory(&mut self, axis: AxisID, timeout: Duration) -> ODriveResult<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.read_trajectory_done(axis)? {
+                return Ok(());
+            }
+
+            let state = self.current_state(axis)?;
+            if state != AxisState::ClosedLoopControl {
+                let errors = self.read_axis_errors(axis)?;
+                return Err(ODriveError::StateTransition(Box::new(TransitionError {
+                    axis,
+                    requested_state: AxisState::ClosedLoopControl,
+                    last_state: state,
+                    errors,
+                    timed_out: false,
+                })));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(ODriveError::TrajectoryTimeout(axis));
+            }
+            sleep(DEFAULT_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// Moves an axis to `position` with `set_trajectory` and waits for the move to finish, see
+    /// `wait_for_trajectory`.
+    pub fn move_to(&mut self, axis: AxisID, position: f32, timeout: Duration) -> ODriveResult<()> {
+        self.set_trajectory(axis, position)
+            .map_err(ODriveError::Io)?;
+        self.wait_for_trajectory(axis, timeout)
+    }
+}
+
+/// # Watchdog configuration
+/// Once enabled, the watchdog disarms an active axis which has not been fed with `feed_watchdog`
+/// for longer than `<axis>.config.watchdog_timeout`, in seconds.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    pub fn set_watchdog_timeout(&mut self, axis: AxisID, value: f32) -> ODriveResult<()> {
+        self.set_axis_config_property(axis, "watchdog_timeout", value)
+    }
+
+    pub fn set_watchdog_enabled(&mut self, axis: AxisID, value: bool) -> ODriveResult<()> {
+        self.set_axis_config_property(axis, "enable_watchdog", value as u8)
+    }
+
+    pub fn read_watchdog_timeout(&mut self, axis: AxisID) -> ODriveResult<f32> {
+        let response = self.get_axis_config_property(axis, "watchdog_timeout")?;
+        parse_response(response)
+    }
+
+    pub fn read_watchdog_enabled(&mut self, axis: AxisID) -> ODriveResult<bool> {
+        let response = self.get_axis_config_property(axis, "enable_watchdog")?;
+        parse_bool(response)
+    }
+
+    /// Writes the watchdog configuration of an axis.
+    /// The watchdog is fed before it is enabled, so that it does not expire right away. Disabling
+    /// the watchdog only writes `enable_watchdog`, so the timeout of the axis is kept.
+    pub fn configure_watchdog(&mut self, axis: AxisID, config: WatchdogConfig) -> ODriveResult<()> {
+        if !config.enabled {
+            return self.set_watchdog_enabled(axis, false);
+        }
+        self.set_watchdog_timeout(axis, config.timeout.as_secs_f32())?;
+        self.feed_watchdog(axis).map_err(ODriveError::Io)?;
+        self.set_watchdog_enabled(axis, true)
+    }
+
+    pub fn read_watchdog_config(&mut self, axis: AxisID) -> ODriveResult<WatchdogConfig> {
+        let enabled = self.read_watchdog_enabled(axis)?;
+        let timeout = self.read_watchdog_timeout(axis)?;
+        WatchdogConfig::from_properties(enabled, timeout)
     }
 }
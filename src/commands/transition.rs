@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use crate::enumerations::errors::{AxisErrorReport, ODriveError, TransitionError};
+use crate::enumerations::{AxisID, AxisState};
+
+/// The default time to wait for a state transition to complete.
+pub const DEFAULT_TRANSITION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The default time between two reads of `<axis>.current_state`.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Describes when a requested state transition is considered complete.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TransitionEnd {
+    /// Do not wait at all once the state has been requested.
+    NoWait,
+    /// Wait until the axis reports the given state.
+    State(AxisState),
+    /// Wait until the axis has run the requested state and returned to `Idle`.
+    /// This is how calibration states and the startup sequence complete.
+    ReturnToIdle,
+}
+
+impl TransitionEnd {
+    /// Returns the natural end of a transition to `state`: calibration states and the startup
+    /// sequence complete back in `Idle`, while all other states complete once they are entered.
+    pub fn for_state(state: AxisState) -> Self {
+        match state {
+            AxisState::StartupSequence
+            | AxisState::FullCalibrationSequence
+            | AxisState::MotorCalibration
+            | AxisState::EncoderIndexSearch
+            | AxisState::EncoderOffsetCalibration => TransitionEnd::ReturnToIdle,
+            _ => TransitionEnd::State(state),
+        }
+    }
+}
+
+/// Configures how `ODrive::run_state` waits for a state transition.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Transition {
+    pub end: TransitionEnd,
+    /// The time after which the transition fails if it has not completed.
+    pub timeout: Duration,
+    /// The time between two reads of `<axis>.current_state`.
+    pub poll_interval: Duration,
+}
+
+impl Transition {
+    pub fn new(end: TransitionEnd) -> Self {
+        Self {
+            end,
+            timeout: DEFAULT_TRANSITION_TIMEOUT,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Waits for the natural end of a transition to `state`, see `TransitionEnd::for_state`.
+    pub fn for_state(state: AxisState) -> Self {
+        Self::new(TransitionEnd::for_state(state))
+    }
+
+    /// Only requests the state without waiting for it.
+    pub fn no_wait() -> Self {
+        Self::new(TransitionEnd::NoWait)
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    pub fn with_poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+}
+
+/// What a state transition loop does after polling the state of the axis.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum TransitionStep {
+    /// Poll again, unless the timeout has passed.
+    Continue,
+    Complete,
+    /// The axis fell back to `Idle` with errors.
+    Failed,
+}
+
+/// Decides from the polled states and errors of an axis when a requested transition completes.
+/// It does no I/O, so that the sync and async clients share the same loop logic.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct TransitionWatch {
+    end: TransitionEnd,
+    /// Whether the axis has been seen outside of `Idle` since the state was requested.
+    started: bool,
+}
+
+impl TransitionWatch {
+    pub(crate) fn new(end: TransitionEnd) -> Self {
+        Self {
+            end,
+            started: false,
+        }
+    }
+
+    /// Whether the errors of the axis must be read to decide on `state`.
+    /// The axis falls back to `Idle` when the requested state fails or finishes.
+    pub(crate) fn needs_errors(state: AxisState) -> bool {
+        state == AxisState::Idle
+    }
+
+    /// Decides on the polled `state`, with the `errors` of the axis if `needs_errors` is true.
+    ///
+    /// The first reads after `requested_state` is written can still report `Idle` because the
+    /// firmware has not picked up the request yet, so a return to `Idle` only completes a
+    /// transition once the axis has left `Idle`.
+    pub(crate) fn step(&mut self, state: AxisState, errors: &AxisErrorReport) -> TransitionStep {
+        match self.end {
+            TransitionEnd::NoWait => return TransitionStep::Complete,
+            TransitionEnd::State(end_state) if state == end_state => {
+                return TransitionStep::Complete
+            }
+            _ => (),
+        }
+
+        if state != AxisState::Idle {
+            self.started = true;
+            TransitionStep::Continue
+        } else if !errors.is_empty() {
+            TransitionStep::Failed
+        } else if self.started && self.end == TransitionEnd::ReturnToIdle {
+            TransitionStep::Complete
+        } else {
+            TransitionStep::Continue
+        }
+    }
+}
+
+/// Builds the error of a state transition which failed in `last_state`.
+pub(crate) fn transition_error(
+    axis: AxisID,
+    requested_state: AxisState,
+    last_state: AxisState,
+    errors: AxisErrorReport,
+    timed_out: bool,
+) -> ODriveError {
+    ODriveError::StateTransition(Box::new(TransitionError {
+        axis,
+        requested_state,
+        last_state,
+        errors,
+        timed_out,
+    }))
+}
@@ -0,0 +1,27 @@
+/// The limits of the trapezoidal trajectory planner of an axis, stored in
+/// `<axis>.trap_traj.config`. The planner is used by `ODrive::set_trajectory`.
+///
+/// Velocities and accelerations are in counts for firmware 0.4.x and in turns since firmware
+/// 0.5.0, see `Capabilities::position_unit`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrapTrajConfig {
+    pub vel_limit: f32,
+    pub accel_limit: f32,
+    pub decel_limit: f32,
+    /// The current feed forward per acceleration, `A_per_css` in firmware 0.4.x.
+    /// Since firmware 0.5.0 this is `<axis>.controller.config.inertia`, in Nm/(turn/s²).
+    pub inertia: f32,
+}
+
+/// The factory defaults of firmware 0.4.12.
+impl Default for TrapTrajConfig {
+    fn default() -> Self {
+        Self {
+            vel_limit: 20000.0,
+            accel_limit: 5000.0,
+            decel_limit: 5000.0,
+            inertia: 0.0,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::io;
+use std::io::{Read, Write};
+use std::sync::mpsc;
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::commands::ODrive;
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+use crate::enumerations::AxisID;
+
+/// The watchdog configuration of an axis, stored in `<axis>.config.enable_watchdog` and
+/// `<axis>.config.watchdog_timeout`.
+///
+/// While the watchdog is enabled, an active axis disarms with `ErrorWatchdogTimerExpired` when it
+/// has not been fed with `ODrive::feed_watchdog` for longer than the timeout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    pub timeout: Duration,
+}
+
+impl WatchdogConfig {
+    /// An enabled watchdog which expires `timeout` after the last feed.
+    pub fn enabled(timeout: Duration) -> Self {
+        Self {
+            enabled: true,
+            timeout,
+        }
+    }
+
+    /// A disabled watchdog. Its timeout is not written by `ODrive::configure_watchdog`, which
+    /// keeps the timeout of the axis.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            timeout: Duration::from_secs(0),
+        }
+    }
+
+    /// Builds the configuration from the values of the properties, with the timeout in seconds.
+    /// Returns `InvalidMessageReceived` if the timeout is not a valid duration.
+    pub(crate) fn from_properties(enabled: bool, timeout: f32) -> ODriveResult<Self> {
+        let timeout = Duration::try_from_secs_f32(timeout)
+            .map_err(|_| ODriveError::InvalidMessageReceived(timeout.to_string()))?;
+        Ok(Self { enabled, timeout })
+    }
+
+    /// An interval for feeding the watchdog which leaves room for two missed feeds.
+    pub fn feed_interval(&self) -> Duration {
+        self.timeout / 3
+    }
+}
+
+/// Feeds the watchdog of one or more axes from a helper thread for as long as the guard is alive.
+///
+/// The ODrive is shared with the control loop through a mutex, and the helper thread locks it
+/// only to send the `u` commands. Once the guard is dropped, including while unwinding from a
+/// panic, the feeding stops and the watchdog disarms the axes after its timeout. Feeding also
+/// stops if writing to the ODrive fails or if the mutex is poisoned.
+#[derive(Debug)]
+pub struct WatchdogGuard {
+    /// Dropping the sender wakes the helper thread up and stops it.
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl WatchdogGuard {
+    /// Starts feeding the watchdog of `axes` every `interval`, beginning immediately.
+    pub fn new<T>(odrive: Arc<Mutex<ODrive<T>>>, axes: &[AxisID], interval: Duration) -> Self
+    where
+        T: Read + Write + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let axes = axes.to_vec();
+        let handle = thread::spawn(move || loop {
+            {
+                let mut odrive = odrive
+                    .lock()
+                    .map_err(|_| io::Error::other("the ODrive mutex was poisoned"))?;
+                for axis in &axes {
+                    odrive.feed_watchdog(*axis)?;
+                }
+            }
+
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => return Ok(()),
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// Returns false once the helper thread has stopped feeding because of an error.
+    pub fn is_feeding(&self) -> bool {
+        match &self.handle {
+            Some(handle) => !handle.is_finished(),
+            None => false,
+        }
+    }
+
+    /// Stops feeding the watchdog, returning the error which stopped the helper thread early.
+    pub fn stop(mut self) -> io::Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.stop.take();
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("the watchdog thread panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for WatchdogGuard {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::io::{Read, Write};
+
+use super::ConfigProperty;
+use crate::commands::ODrive;
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+
+/// The outcome of `ODrive::apply_config`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ApplyReport {
+    /// The properties which were written and verified, with their new values.
+    pub applied: Vec<ConfigProperty>,
+    /// The properties which were restored to their previous values after a failure.
+    pub rolled_back: Vec<ConfigProperty>,
+}
+
+/// Describes a configuration which could not be applied, see `ODrive::apply_config`.
+#[derive(Debug)]
+pub struct ApplyError {
+    /// The property whose write failed, or `None` if all writes succeeded but saving the
+    /// configuration failed.
+    pub path: Option<String>,
+    /// Why the write or the save failed.
+    pub error: ODriveError,
+    /// The properties applied before the failure, and those which were rolled back.
+    pub report: ApplyReport,
+    /// The properties which could not be restored to their previous values, with the errors of
+    /// the writes. If this is not empty, the ODrive is left partially configured.
+    pub rollback_errors: Vec<(ConfigProperty, ODriveError)>,
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "writing {} failed", path)?,
+            None => f.write_str("saving the configuration failed")?,
+        }
+        write!(
+            f,
+            ": {}; {} properties rolled back",
+            self.error,
+            self.report.rolled_back.len()
+        )?;
+        if !self.rollback_errors.is_empty() {
+            write!(f, ", {} could not be restored", self.rollback_errors.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// # Configuration transactions
+/// `apply_config` writes a set of properties all or nothing.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Applies `properties` as a transaction:
+    ///
+    /// 1. The current value of every property is read as a snapshot.
+    /// 2. Each property is written and read back with `write_config_property`.
+    /// 3. If all writes succeed, the configuration is saved with `save_configuration` if `save`
+    ///    is set.
+    ///
+    /// If a write fails, the properties written so far are restored from the snapshot, in reverse
+    /// order, and the configuration is not saved. If the save fails, every property is restored.
+    /// The error is then `ConfigApply`, which lists what was applied and what was rolled back.
+    /// Errors while taking the snapshot are returned as they are, before anything is written.
+    pub fn apply_config(
+        &mut self,
+        properties: &[ConfigProperty],
+        save: bool,
+    ) -> ODriveResult<ApplyReport> {
+        let mut snapshot = Vec::with_capacity(properties.len());
+        for property in properties {
+            let response = self.read_property_response(&property.path)?;
+            snapshot.push(ConfigProperty {
+                path: property.path.clone(),
+                value: property.value.parse_like(response)?,
+            });
+        }
+
+        let mut report = ApplyReport::default();
+        for (index, property) in properties.iter().enumerate() {
+            match self.write_config_property(property) {
+                Ok(()) => report.applied.push(property.clone()),
+                Err(error) => {
+                    // A rejected write leaves the property unchanged, other failures may not
+                    let written = match error {
+                        ODriveError::InvalidProperty { .. } => index,
+                        _ => index + 1,
+                    };
+                    let rollback_errors =
+                        self.roll_back(&snapshot[..written], &mut report.rolled_back);
+                    return Err(ODriveError::ConfigApply(Box::new(ApplyError {
+                        path: Some(property.path.clone()),
+                        error,
+                        report,
+                        rollback_errors,
+                    })));
+                }
+            }
+        }
+
+        if save {
+            if let Err(error) = self.save_configuration() {
+                let rollback_errors = self.roll_back(&snapshot, &mut report.rolled_back);
+                return Err(ODriveError::ConfigApply(Box::new(ApplyError {
+                    path: None,
+                    error,
+                    report,
+                    rollback_errors,
+                })));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Restores `snapshot` in reverse order, appending the restored properties to `rolled_back`,
+    /// and returns those which could not be restored.
+    fn roll_back(
+        &mut self,
+        snapshot: &[ConfigProperty],
+        rolled_back: &mut Vec<ConfigProperty>,
+    ) -> Vec<(ConfigProperty, ODriveError)> {
+        let mut errors = Vec::new();
+        for property in snapshot.iter().rev() {
+            match self.write_config_property(property) {
+                Ok(()) => rolled_back.push(property.clone()),
+                Err(error) => errors.push((property.clone(), error)),
+            }
+        }
+        errors
+    }
+}
@@ -0,0 +1,357 @@
+use std::io::ErrorKind;
+
+use super::*;
+use crate::commands::FirmwareVersion;
+use crate::enumerations::errors::ODriveError;
+use crate::test_stream::MockStream;
+
+fn find<'a>(properties: &'a [ConfigProperty], path: &str) -> &'a ConfigValue {
+    &properties
+        .iter()
+        .find(|property| property.path == path)
+        .unwrap_or_else(|| panic!("missing {}", path))
+        .value
+}
+
+#[test]
+fn test_properties() {
+    let mut config = ODriveConfig::default();
+    config.axis1.motor.pole_pairs = 15;
+    config.axis1.encoder.mode = EncoderMode::EncoderModeHall;
+    config.axis1.controller.control_mode = ControlMode::TrajectoryControl;
+
+    let properties = config.properties(&Capabilities::default()).unwrap();
+    assert_eq!(4 + 2 * 31, properties.len());
+    assert_eq!(
+        ConfigProperty {
+            path: "config.brake_resistance".to_owned(),
+            value: ConfigValue::Float(2.0),
+        },
+        properties[0]
+    );
+    assert_eq!(
+        &ConfigValue::Bool(true),
+        find(&properties, "config.enable_uart")
+    );
+    assert_eq!(
+        &ConfigValue::Bool(false),
+        find(&properties, "axis0.config.startup_closed_loop_control")
+    );
+    assert_eq!(
+        &ConfigValue::Int(15),
+        find(&properties, "axis1.motor.config.pole_pairs")
+    );
+    assert_eq!(
+        &ConfigValue::Int(1),
+        find(&properties, "axis1.encoder.config.mode")
+    );
+    assert_eq!(
+        &ConfigValue::Int(4),
+        find(&properties, "axis1.controller.config.control_mode")
+    );
+    assert_eq!(
+        &ConfigValue::Float(0.0),
+        find(&properties, "axis1.trap_traj.config.A_per_css")
+    );
+}
+
+#[test]
+fn test_properties_0_5() {
+    let capabilities = Capabilities::new(FirmwareVersion::new(0, 5, 6), None);
+    let mut config = ODriveConfig::default();
+    config.axis0.controller.control_mode = ControlMode::TrajectoryControl;
+    config.axis0.motor.motor_type = MotorType::MotorTypeGimbal;
+    let properties = config.properties(&capabilities).unwrap();
+    assert_eq!(
+        &ConfigValue::Int(3),
+        find(&properties, "axis0.controller.config.control_mode")
+    );
+    assert_eq!(
+        &ConfigValue::Int(2),
+        find(&properties, "axis0.motor.config.motor_type")
+    );
+
+    config.axis1.motor.motor_type = MotorType::LowCurrent;
+    assert!(config.properties(&capabilities).is_err());
+}
+
+#[test]
+fn test_config_value_display() {
+    assert_eq!("1", ConfigValue::Bool(true).to_string());
+    assert_eq!("-3", ConfigValue::Int(-3).to_string());
+    assert_eq!("0.5", ConfigValue::Float(0.5).to_string());
+}
+
+#[test]
+fn test_write_config_property() {
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"");
+    odrive.get_mut().push_response(b"1\n");
+    odrive
+        .write_config_property(&ConfigProperty {
+            path: "axis0.config.startup_closed_loop_control".to_owned(),
+            value: ConfigValue::Bool(true),
+        })
+        .unwrap();
+    assert_eq!(
+        b"w axis0.config.startup_closed_loop_control 1\nr axis0.config.startup_closed_loop_control\n"
+            .to_vec(),
+        odrive.get_mut().write_buffer
+    );
+
+    odrive.get_mut().push_response(b"");
+    odrive.get_mut().push_response(b"8192\n");
+    match odrive.write_config_property(&ConfigProperty {
+        path: "axis0.encoder.config.cpr".to_owned(),
+        value: ConfigValue::Int(90),
+    }) {
+        Err(ODriveError::PropertyMismatch { written, read, .. }) => {
+            assert_eq!("90", written);
+            assert_eq!("8192", read);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let mut config = ODriveConfig::default();
+    config.axis0.motor.pole_pairs = 15;
+    config.axis0.encoder.mode = EncoderMode::EncoderModeHall;
+    config.axis1.controller.control_mode = ControlMode::VelocityControl;
+    config.axis1.trap_traj.inertia = 0.25;
+
+    let json = serde_json::to_string_pretty(&config).unwrap();
+    assert_eq!(config, serde_json::from_str(&json).unwrap());
+
+    let property = ConfigProperty {
+        path: "axis0.motor.config.pole_pairs".to_owned(),
+        value: ConfigValue::Int(15),
+    };
+    let json = serde_json::to_string(&property).unwrap();
+    assert_eq!(
+        "{\"path\":\"axis0.motor.config.pole_pairs\",\"value\":15}",
+        json
+    );
+    assert_eq!(property, serde_json::from_str(&json).unwrap());
+
+    let text = toml::to_string(&config).unwrap();
+    assert!(text.contains("[axis0.motor]"));
+    assert!(text.contains("mode = \"EncoderModeHall\""));
+    assert_eq!(config, toml::from_str(&text).unwrap());
+}
+
+#[test]
+fn test_tolerance() {
+    let value = ConfigValue::Float(0.0201);
+    let expected = ConfigValue::Float(0.02);
+    assert!(!value.matches(&expected, Tolerance::default()));
+    assert!(value.matches(&expected, Tolerance::absolute(0.001)));
+    assert!(!value.matches(&expected, Tolerance::absolute(0.00001)));
+    assert!(value.matches(&expected, Tolerance::relative(0.01)));
+    assert!(!value.matches(&expected, Tolerance::relative(0.001)));
+    assert!(ConfigValue::Float(0.0).matches(&ConfigValue::Float(0.0), Tolerance::default()));
+
+    // Only floats have a tolerance
+    assert!(!ConfigValue::Int(8193).matches(&ConfigValue::Int(8192), Tolerance::relative(0.1)));
+}
+
+#[test]
+fn test_parse_like() {
+    assert_eq!(
+        ConfigValue::Bool(true),
+        ConfigValue::Bool(false).parse_like("1".to_owned()).unwrap()
+    );
+    assert_eq!(
+        ConfigValue::Int(90),
+        ConfigValue::Int(0).parse_like("90".to_owned()).unwrap()
+    );
+    assert_eq!(
+        ConfigValue::Float(0.5),
+        ConfigValue::Float(0.0)
+            .parse_like("0.500000".to_owned())
+            .unwrap()
+    );
+    assert!(ConfigValue::Int(0).parse_like("0.5".to_owned()).is_err());
+}
+
+#[test]
+fn test_diff_config() {
+    let expected = vec![
+        ConfigProperty {
+            path: "axis0.motor.config.pole_pairs".to_owned(),
+            value: ConfigValue::Int(15),
+        },
+        ConfigProperty {
+            path: "axis0.controller.config.vel_gain".to_owned(),
+            value: ConfigValue::Float(0.02),
+        },
+        ConfigProperty {
+            path: "axis0.config.startup_closed_loop_control".to_owned(),
+            value: ConfigValue::Bool(true),
+        },
+    ];
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"7\n");
+    odrive.get_mut().push_response(b"0.020000\n");
+    odrive.get_mut().push_response(b"0\n");
+    let differences = odrive
+        .diff_config(&expected, Tolerance::absolute(0.0001))
+        .unwrap();
+    assert_eq!(
+        vec![
+            ConfigDifference {
+                path: "axis0.motor.config.pole_pairs".to_owned(),
+                device: ConfigValue::Int(7),
+                expected: ConfigValue::Int(15),
+            },
+            ConfigDifference {
+                path: "axis0.config.startup_closed_loop_control".to_owned(),
+                device: ConfigValue::Bool(false),
+                expected: ConfigValue::Bool(true),
+            },
+        ],
+        differences
+    );
+    assert_eq!(
+        "axis0.motor.config.pole_pairs: 7 on the device, 15 expected",
+        differences[0].to_string()
+    );
+    assert_eq!(
+        b"r axis0.motor.config.pole_pairs\nr axis0.controller.config.vel_gain\nr axis0.config.startup_closed_loop_control\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_diff_config_invalid_property() {
+    let expected = vec![ConfigProperty {
+        path: "axis0.motor.config.missing".to_owned(),
+        value: ConfigValue::Int(1),
+    }];
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"invalid property\n");
+    match odrive.diff_config(&expected, Tolerance::default()) {
+        Err(ODriveError::InvalidProperty { path, .. }) => {
+            assert_eq!("axis0.motor.config.missing", path)
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_apply_config_rolls_back() {
+    let properties = vec![
+        ConfigProperty {
+            path: "axis0.motor.config.pole_pairs".to_owned(),
+            value: ConfigValue::Int(15),
+        },
+        ConfigProperty {
+            path: "axis0.motor.config.current_lim".to_owned(),
+            value: ConfigValue::Float(80.0),
+        },
+    ];
+    let mut odrive = ODrive::new(MockStream::new());
+    for response in &[
+        &b"7\n"[..],
+        b"10.000000\n",
+        b"",
+        b"15\n",
+        b"",
+        b"60.000000\n",
+        b"",
+        b"10.000000\n",
+        b"",
+        b"7\n",
+    ] {
+        odrive.get_mut().push_response(response);
+    }
+
+    let error = match odrive.apply_config(&properties, true) {
+        Err(ODriveError::ConfigApply(error)) => error,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!(
+        Some("axis0.motor.config.current_lim"),
+        error.path.as_deref()
+    );
+    match error.error {
+        ODriveError::PropertyMismatch { .. } => {}
+        ref other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(vec![properties[0].clone()], error.report.applied);
+    assert_eq!(
+        vec![
+            ConfigProperty {
+                path: "axis0.motor.config.current_lim".to_owned(),
+                value: ConfigValue::Float(10.0),
+            },
+            ConfigProperty {
+                path: "axis0.motor.config.pole_pairs".to_owned(),
+                value: ConfigValue::Int(7),
+            },
+        ],
+        error.report.rolled_back
+    );
+    assert!(error.rollback_errors.is_empty());
+    assert_eq!(
+        "writing axis0.motor.config.current_lim failed: Property axis0.motor.config.current_lim \
+         was set to 80 but reads 60.000000; 2 properties rolled back",
+        error.to_string()
+    );
+
+    // The configuration is not saved
+    let written = String::from_utf8(odrive.get_mut().write_buffer.clone()).unwrap();
+    assert_eq!(
+        "r axis0.motor.config.pole_pairs\nr axis0.motor.config.current_lim\n\
+         w axis0.motor.config.pole_pairs 15\nr axis0.motor.config.pole_pairs\n\
+         w axis0.motor.config.current_lim 80\nr axis0.motor.config.current_lim\n\
+         w axis0.motor.config.current_lim 10\nr axis0.motor.config.current_lim\n\
+         w axis0.motor.config.pole_pairs 7\nr axis0.motor.config.pole_pairs\n",
+        written
+    );
+}
+
+#[test]
+fn test_apply_config_rolls_back_failed_save() {
+    let properties = vec![ConfigProperty {
+        path: "axis0.motor.config.pole_pairs".to_owned(),
+        value: ConfigValue::Int(15),
+    }];
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().write_error = Some((b"ss", ErrorKind::BrokenPipe));
+    for response in &[&b"7\n"[..], b"", b"15\n", b"", b"7\n"] {
+        odrive.get_mut().push_response(response);
+    }
+
+    let error = match odrive.apply_config(&properties, true) {
+        Err(ODriveError::ConfigApply(error)) => error,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!(None, error.path);
+    match error.error {
+        ODriveError::Io(ref io) => assert_eq!(ErrorKind::BrokenPipe, io.kind()),
+        ref other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(properties, error.report.applied);
+    assert_eq!(
+        vec![ConfigProperty {
+            path: "axis0.motor.config.pole_pairs".to_owned(),
+            value: ConfigValue::Int(7),
+        }],
+        error.report.rolled_back
+    );
+    assert!(error.rollback_errors.is_empty());
+    assert!(error
+        .to_string()
+        .starts_with("saving the configuration failed: "));
+
+    let written = String::from_utf8(odrive.get_mut().write_buffer.clone()).unwrap();
+    assert_eq!(
+        "r axis0.motor.config.pole_pairs\n\
+         w axis0.motor.config.pole_pairs 15\nr axis0.motor.config.pole_pairs\n\
+         w axis0.motor.config.pole_pairs 7\nr axis0.motor.config.pole_pairs\n",
+        written
+    );
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io::{Read, Write};
+
+use super::{ConfigProperty, ConfigValue};
+use crate::commands::{parse_bool, parse_response, ODrive, PRINTED_FLOAT_TOLERANCE};
+use crate::enumerations::errors::ODriveResult;
+
+/// How far a float property may be from its expected value to be considered equal.
+/// A value matches if it is within `absolute` or within `relative` times the expected value.
+///
+/// The default tolerance is exact.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f32,
+    pub relative: f32,
+}
+
+impl Tolerance {
+    pub fn absolute(absolute: f32) -> Self {
+        Self {
+            absolute,
+            relative: 0.0,
+        }
+    }
+
+    pub fn relative(relative: f32) -> Self {
+        Self {
+            absolute: 0.0,
+            relative,
+        }
+    }
+
+    pub(crate) fn matches(&self, value: f32, expected: f32) -> bool {
+        let difference = (value - expected).abs();
+        difference <= self.absolute || difference <= self.relative * expected.abs()
+    }
+}
+
+/// A property whose value on the device differs from the expected one.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigDifference {
+    pub path: String,
+    pub device: ConfigValue,
+    pub expected: ConfigValue,
+}
+
+impl fmt::Display for ConfigDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} on the device, {} expected",
+            self.path, self.device, self.expected
+        )
+    }
+}
+
+impl ConfigValue {
+    /// Parses a response to the `r` command as a value of the same type as `self`.
+    pub fn parse_like(&self, response: String) -> ODriveResult<ConfigValue> {
+        Ok(match self {
+            ConfigValue::Bool(_) => ConfigValue::Bool(parse_bool(response)?),
+            ConfigValue::Int(_) => ConfigValue::Int(parse_response(response)?),
+            ConfigValue::Float(_) => ConfigValue::Float(parse_response(response)?),
+        })
+    }
+
+    /// Whether `self` equals `expected`, within `tolerance` for floats.
+    pub fn matches(&self, expected: &ConfigValue, tolerance: Tolerance) -> bool {
+        match (self, expected) {
+            (ConfigValue::Float(value), ConfigValue::Float(expected)) => {
+                tolerance.matches(*value, *expected)
+            }
+            _ => self == expected,
+        }
+    }
+}
+
+/// # Configuration diff
+/// Compares the configuration of the ODrive with an expected one, for example with the
+/// properties of an `ODriveConfig` or an `OdrivetoolConfig`.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Reads every property of `expected` and lists those whose value differs, in the same order.
+    /// Floats are compared within `tolerance`, but never more precisely than the ODrive prints
+    /// them, see `PRINTED_FLOAT_TOLERANCE`.
+    ///
+    /// Returns `InvalidProperty` if the ODrive does not have one of the properties.
+    pub fn diff_config(
+        &mut self,
+        expected: &[ConfigProperty],
+        tolerance: Tolerance,
+    ) -> ODriveResult<Vec<ConfigDifference>> {
+        let mut differences = Vec::new();
+        for property in expected {
+            let response = self.read_property_response(&property.path)?;
+            let device = property.value.parse_like(response)?;
+            if !device.matches(&property.value, tolerance)
+                && !device.matches(&property.value, PRINTED_FLOAT_TOLERANCE)
+            {
+                differences.push(ConfigDifference {
+                    path: property.path.clone(),
+                    device,
+                    expected: property.value,
+                });
+            }
+        }
+        Ok(differences)
+    }
+}
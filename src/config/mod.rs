@@ -0,0 +1,443 @@
+//! Backup and restore of the configuration of an ODrive.
+//!
+//! `ODrive::backup_config` reads the system configuration and the motor, encoder, controller,
+//! trajectory and startup configuration of both axes into an `ODriveConfig`, which
+//! `ODrive::restore_config` writes back, for example to set up a replacement board like a
+//! known-good one. With the `serde` feature, `ODriveConfig` can be stored in JSON or TOML files,
+//! and with the `json` feature, the files of `odrivetool backup-config` can be read and written
+//! with `OdrivetoolConfig`.
+//!
+//! `ODrive::diff_config` lists the properties whose values on the device differ from an expected
+//! configuration, and `ODrive::apply_config` writes a configuration all or nothing, rolling back
+//! on failure.
+//!
+//! Values are stored in the units of the firmware they were read from, see
+//! `Capabilities::position_unit`.
+
+use std::fmt;
+use std::io::{Read, Write};
+
+use crate::commands::{
+    parse_bool, parse_enum, parse_response, Capabilities, ODrive, TrapTrajConfig,
+};
+use crate::enumerations::errors::ODriveResult;
+use crate::enumerations::{AxisID, ControlMode, EncoderMode, MotorType};
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod config_tests;
+
+mod apply;
+mod diff;
+
+#[cfg(feature = "json")]
+mod odrivetool;
+
+#[cfg(all(test, feature = "json"))]
+#[cfg_attr(tarpaulin, skip)]
+mod odrivetool_tests;
+
+pub use apply::{ApplyError, ApplyReport};
+
+pub use diff::{ConfigDifference, Tolerance};
+
+#[cfg(feature = "json")]
+pub use odrivetool::OdrivetoolConfig;
+
+/// The value of a configuration property, as it is sent over the ASCII protocol.
+/// It is serialized as a plain boolean or number.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum ConfigValue {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+}
+
+/// Formats values the way the ODrive expects them, with booleans sent as `0` or `1`.
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigValue::Bool(value) => write!(f, "{}", *value as u8),
+            ConfigValue::Int(value) => write!(f, "{}", value),
+            ConfigValue::Float(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// A configuration property and its value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigProperty {
+    pub path: String,
+    pub value: ConfigValue,
+}
+
+/// A field of a configuration group, converted to and from the value sent for the firmware.
+trait ConfigField: Sized {
+    fn to_value(&self, capabilities: &Capabilities) -> ODriveResult<ConfigValue>;
+
+    fn from_response(response: String, capabilities: &Capabilities) -> ODriveResult<Self>;
+}
+
+impl ConfigField for bool {
+    fn to_value(&self, _capabilities: &Capabilities) -> ODriveResult<ConfigValue> {
+        Ok(ConfigValue::Bool(*self))
+    }
+
+    fn from_response(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+        parse_bool(response)
+    }
+}
+
+impl ConfigField for f32 {
+    fn to_value(&self, _capabilities: &Capabilities) -> ODriveResult<ConfigValue> {
+        Ok(ConfigValue::Float(*self))
+    }
+
+    fn from_response(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+        parse_response(response)
+    }
+}
+
+macro_rules! impl_int_field {
+    ($($type:ty),*) => {
+        $(
+            impl ConfigField for $type {
+                fn to_value(&self, _capabilities: &Capabilities) -> ODriveResult<ConfigValue> {
+                    Ok(ConfigValue::Int(i64::from(*self)))
+                }
+
+                fn from_response(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+                    parse_response(response)
+                }
+            }
+        )*
+    };
+}
+
+impl_int_field!(u16, i32);
+
+impl ConfigField for EncoderMode {
+    fn to_value(&self, _capabilities: &Capabilities) -> ODriveResult<ConfigValue> {
+        Ok(ConfigValue::Int(*self as i64))
+    }
+
+    fn from_response(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+        parse_enum(response)
+    }
+}
+
+impl ConfigField for ControlMode {
+    fn to_value(&self, capabilities: &Capabilities) -> ODriveResult<ConfigValue> {
+        Ok(ConfigValue::Int(i64::from(
+            capabilities.control_mode_value(*self),
+        )))
+    }
+
+    fn from_response(response: String, capabilities: &Capabilities) -> ODriveResult<Self> {
+        capabilities.control_mode_from_value(parse_response(response)?)
+    }
+}
+
+impl ConfigField for MotorType {
+    fn to_value(&self, capabilities: &Capabilities) -> ODriveResult<ConfigValue> {
+        Ok(ConfigValue::Int(i64::from(
+            capabilities.motor_type_value(*self)?,
+        )))
+    }
+
+    fn from_response(response: String, capabilities: &Capabilities) -> ODriveResult<Self> {
+        capabilities.motor_type_from_value(parse_response(response)?)
+    }
+}
+
+/// A group of configuration properties below a common path.
+trait ConfigGroup: Sized {
+    fn push_properties(
+        &self,
+        prefix: &str,
+        capabilities: &Capabilities,
+        properties: &mut Vec<ConfigProperty>,
+    ) -> ODriveResult<()>;
+
+    fn read<T: Read + Write>(odrive: &mut ODrive<T>, prefix: &str) -> ODriveResult<Self>;
+}
+
+/// Implements `ConfigGroup` for a struct whose fields are stored in the listed properties.
+macro_rules! impl_config_group {
+    ($name:ident { $($path:literal => $field:ident: $type:ty),* $(,)? }) => {
+        impl ConfigGroup for $name {
+            fn push_properties(
+                &self,
+                prefix: &str,
+                capabilities: &Capabilities,
+                properties: &mut Vec<ConfigProperty>,
+            ) -> ODriveResult<()> {
+                $(
+                    properties.push(ConfigProperty {
+                        path: format!("{}.{}", prefix, $path),
+                        value: self.$field.to_value(capabilities)?,
+                    });
+                )*
+                Ok(())
+            }
+
+            fn read<T: Read + Write>(odrive: &mut ODrive<T>, prefix: &str) -> ODriveResult<Self> {
+                let capabilities = odrive.capabilities();
+                Ok(Self {
+                    $(
+                        $field: {
+                            let path = format!("{}.{}", prefix, $path);
+                            let response = odrive.read_property_response(&path)?;
+                            <$type as ConfigField>::from_response(response, &capabilities)?
+                        },
+                    )*
+                })
+            }
+        }
+    };
+}
+
+/// Declares a configuration group, with the property and the factory default of each field.
+macro_rules! config_group {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$field_meta:meta])* $path:literal => $field:ident: $type:ty = $default:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $type,)*
+        }
+
+        /// The factory defaults of firmware 0.4.12.
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl_config_group!($name { $($path => $field: $type),* });
+    };
+}
+
+config_group! {
+    /// The configuration of the board, in `config`.
+    SystemConfig {
+        "brake_resistance" => brake_resistance: f32 = 2.0,
+        "dc_bus_undervoltage_trip_level" => dc_bus_undervoltage_trip_level: f32 = 8.0,
+        "dc_bus_overvoltage_trip_level" => dc_bus_overvoltage_trip_level: f32 = 56.0,
+        /// `enable_uart_a` since firmware 0.5.0.
+        "enable_uart" => enable_uart: bool = true,
+    }
+}
+
+config_group! {
+    /// The startup procedures of an axis, in `<axis>.config`. See `ODrive::set_startup_motor_calibration`.
+    StartupConfig {
+        "startup_motor_calibration" => motor_calibration: bool = false,
+        "startup_encoder_index_search" => encoder_index_search: bool = false,
+        "startup_encoder_offset_calibration" => encoder_offset_calibration: bool = false,
+        "startup_closed_loop_control" => closed_loop_control: bool = false,
+        "startup_sensorless_control" => sensorless_control: bool = false,
+    }
+}
+
+config_group! {
+    /// The motor configuration of an axis, in `<axis>.motor.config`.
+    MotorConfig {
+        "motor_type" => motor_type: MotorType = MotorType::HighCurrent,
+        "pole_pairs" => pole_pairs: u16 = 7,
+        "calibration_current" => calibration_current: f32 = 10.0,
+        "resistance_calib_max_voltage" => resistance_calib_max_voltage: f32 = 2.0,
+        "phase_inductance" => phase_inductance: f32 = 0.0,
+        "phase_resistance" => phase_resistance: f32 = 0.0,
+        "current_lim" => current_lim: f32 = 10.0,
+        "requested_current_range" => requested_current_range: f32 = 60.0,
+        "current_control_bandwidth" => current_control_bandwidth: f32 = 1000.0,
+        "pre_calibrated" => pre_calibrated: bool = false,
+    }
+}
+
+config_group! {
+    /// The encoder configuration of an axis, in `<axis>.encoder.config`.
+    EncoderConfig {
+        "mode" => mode: EncoderMode = EncoderMode::EncoderModeIncremental,
+        "use_index" => use_index: bool = false,
+        "cpr" => cpr: i32 = 8192,
+        "bandwidth" => bandwidth: f32 = 1000.0,
+        /// The commutation offset found by the offset calibration, `phase_offset` since firmware
+        /// 0.5.0. Restored before `pre_calibrated`, which makes the board trust it.
+        "offset" => offset: i32 = 0,
+        /// `phase_offset_float` since firmware 0.5.0.
+        "offset_float" => offset_float: f32 = 0.0,
+        "pre_calibrated" => pre_calibrated: bool = false,
+    }
+}
+
+config_group! {
+    /// The controller configuration of an axis, in `<axis>.controller.config`.
+    ///
+    /// Since firmware 0.5.0, `TrajectoryControl` is stored as `PositionControl` with the
+    /// `TrapTraj` input mode, so it is read back as `PositionControl`.
+    ControllerConfig {
+        "control_mode" => control_mode: ControlMode = ControlMode::PositionControl,
+        "pos_gain" => pos_gain: f32 = 20.0,
+        "vel_gain" => vel_gain: f32 = 0.0005,
+        "vel_integrator_gain" => vel_integrator_gain: f32 = 0.001,
+        "vel_limit" => vel_limit: f32 = 20000.0,
+    }
+}
+
+impl_config_group!(TrapTrajConfig {
+    "vel_limit" => vel_limit: f32,
+    "accel_limit" => accel_limit: f32,
+    "decel_limit" => decel_limit: f32,
+    "A_per_css" => inertia: f32,
+});
+
+/// The configuration of an axis.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisConfig {
+    pub startup: StartupConfig,
+    pub motor: MotorConfig,
+    pub encoder: EncoderConfig,
+    pub controller: ControllerConfig,
+    pub trap_traj: TrapTrajConfig,
+}
+
+impl AxisConfig {
+    fn push_properties(
+        &self,
+        axis: AxisID,
+        capabilities: &Capabilities,
+        properties: &mut Vec<ConfigProperty>,
+    ) -> ODriveResult<()> {
+        let prefix = format!("axis{}", axis as u8);
+        self.startup
+            .push_properties(&format!("{}.config", prefix), capabilities, properties)?;
+        self.motor.push_properties(
+            &format!("{}.motor.config", prefix),
+            capabilities,
+            properties,
+        )?;
+        self.encoder.push_properties(
+            &format!("{}.encoder.config", prefix),
+            capabilities,
+            properties,
+        )?;
+        self.controller.push_properties(
+            &format!("{}.controller.config", prefix),
+            capabilities,
+            properties,
+        )?;
+        self.trap_traj.push_properties(
+            &format!("{}.trap_traj.config", prefix),
+            capabilities,
+            properties,
+        )
+    }
+
+    fn read<T: Read + Write>(odrive: &mut ODrive<T>, axis: AxisID) -> ODriveResult<Self> {
+        let prefix = format!("axis{}", axis as u8);
+        Ok(Self {
+            startup: ConfigGroup::read(odrive, &format!("{}.config", prefix))?,
+            motor: ConfigGroup::read(odrive, &format!("{}.motor.config", prefix))?,
+            encoder: ConfigGroup::read(odrive, &format!("{}.encoder.config", prefix))?,
+            controller: ConfigGroup::read(odrive, &format!("{}.controller.config", prefix))?,
+            trap_traj: ConfigGroup::read(odrive, &format!("{}.trap_traj.config", prefix))?,
+        })
+    }
+}
+
+/// The configuration of an ODrive and of both of its axes.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ODriveConfig {
+    pub system: SystemConfig,
+    pub axis0: AxisConfig,
+    pub axis1: AxisConfig,
+}
+
+impl ODriveConfig {
+    pub fn axis(&self, axis: AxisID) -> &AxisConfig {
+        match axis {
+            AxisID::Zero => &self.axis0,
+            AxisID::One => &self.axis1,
+        }
+    }
+
+    pub fn axis_mut(&mut self, axis: AxisID) -> &mut AxisConfig {
+        match axis {
+            AxisID::Zero => &mut self.axis0,
+            AxisID::One => &mut self.axis1,
+        }
+    }
+
+    /// Lists every property of the configuration with the value sent to an ODrive with
+    /// `capabilities`. The paths are those of firmware 0.4.x, which `ODrive` translates.
+    ///
+    /// Returns `Unsupported` if a value does not exist in the firmware, such as a low current
+    /// motor type since firmware 0.5.0.
+    pub fn properties(&self, capabilities: &Capabilities) -> ODriveResult<Vec<ConfigProperty>> {
+        let mut properties = Vec::new();
+        self.system
+            .push_properties("config", capabilities, &mut properties)?;
+        self.axis0
+            .push_properties(AxisID::Zero, capabilities, &mut properties)?;
+        self.axis1
+            .push_properties(AxisID::One, capabilities, &mut properties)?;
+        Ok(properties)
+    }
+}
+
+/// # Configuration backup
+/// An `ODriveConfig` captures the configuration of a board, so that it can be restored later or
+/// on another board.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Reads the configuration of the ODrive and of both axes.
+    pub fn backup_config(&mut self) -> ODriveResult<ODriveConfig> {
+        Ok(ODriveConfig {
+            system: ConfigGroup::read(self, "config")?,
+            axis0: AxisConfig::read(self, AxisID::Zero)?,
+            axis1: AxisConfig::read(self, AxisID::One)?,
+        })
+    }
+
+    /// Writes every property of `config` and checks that it reads back the written value, then
+    /// calls `save_configuration` if `save` is set.
+    ///
+    /// Stops at the first property which is rejected or reads back another value, with
+    /// `InvalidProperty` or `PropertyMismatch`. The properties written before are not reverted,
+    /// see `apply_config` for that.
+    pub fn restore_config(&mut self, config: &ODriveConfig, save: bool) -> ODriveResult<()> {
+        for property in config.properties(&self.capabilities())? {
+            self.write_config_property(&property)?;
+        }
+        if save {
+            self.save_configuration()?;
+        }
+        Ok(())
+    }
+
+    /// Writes a configuration property with `write_property_verified`.
+    pub fn write_config_property(&mut self, property: &ConfigProperty) -> ODriveResult<()> {
+        let path = &property.path;
+        match property.value {
+            ConfigValue::Bool(value) => self.write_property_verified(path, value as u8),
+            ConfigValue::Int(value) => self.write_property_verified(path, value),
+            ConfigValue::Float(value) => self.write_property_verified(path, value),
+        }
+    }
+}
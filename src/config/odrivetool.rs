@@ -0,0 +1,216 @@
+//! The JSON files of `odrivetool backup-config` and `odrivetool restore-config`.
+//!
+//! The files map dotted property paths, such as `axis0.motor.config.pole_pairs`, to their values.
+//! Nested objects, as written by older versions of odrivetool, are flattened to the same paths.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use serde_json::{Map, Number, Value};
+
+use super::{ConfigProperty, ConfigValue};
+use crate::commands::{Capabilities, FirmwareVersion, ODrive};
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+use crate::properties::{self, PropertyInfo, ValueType};
+
+/// The properties of an odrivetool configuration file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OdrivetoolConfig {
+    /// The configuration properties known to this crate, by their path in firmware 0.4.x.
+    pub properties: Vec<ConfigProperty>,
+    /// The keys of the file which are not configuration properties of firmware 0.4.12 or 0.5.6.
+    /// `ODrive::apply_odrivetool_config` refuses to apply the file until they are cleared.
+    pub unknown_keys: Vec<String>,
+}
+
+impl OdrivetoolConfig {
+    /// Parses a file written by `odrivetool backup-config`, from firmware 0.4.x or 0.5.x.
+    /// Read-only properties of the file, which cannot be restored, are skipped.
+    ///
+    /// Returns `InvalidConfig` if the file is not a JSON object, or if a value does not fit the
+    /// type of its property.
+    pub fn from_json(text: &str) -> ODriveResult<Self> {
+        let root: Value = serde_json::from_str(&quote_infinities(text))
+            .map_err(|error| ODriveError::InvalidConfig(error.to_string()))?;
+        let mut entries = Vec::new();
+        match root {
+            Value::Object(object) => flatten("", object, &mut entries),
+            _ => return Err(invalid_config("the file is not a JSON object")),
+        }
+
+        // The paths of this crate follow firmware 0.4.x, so the properties of 0.5.x are known
+        // by their name in 0.4.x
+        let firmware_0_4 = Capabilities::default();
+        let firmware_0_5 = Capabilities::new(FirmwareVersion::new(0, 5, 6), None);
+        let known: HashMap<String, PropertyInfo> = properties::for_firmware(&firmware_0_4)
+            .into_iter()
+            .chain(properties::for_firmware(&firmware_0_5))
+            .filter(|info| is_config_path(&info.path))
+            .map(|info| (firmware_0_4.property_path(&info.path).into_owned(), info))
+            .collect();
+
+        let mut config = OdrivetoolConfig {
+            properties: Vec::new(),
+            unknown_keys: Vec::new(),
+        };
+        for (key, value) in entries {
+            let path = firmware_0_4.property_path(&key).into_owned();
+            match known.get(&path) {
+                Some(info) if info.writable => {
+                    let value = parse_value(&key, info.value_type, &value)?;
+                    config.properties.push(ConfigProperty { path, value });
+                }
+                // Read-only values, such as the measured `anticogging.cogging_ratio`
+                Some(_) => {}
+                None => config.unknown_keys.push(key),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Writes `properties` as a file for `odrivetool restore-config`, with the paths of the
+    /// firmware of `capabilities`. Infinite floats are written as `Infinity`, like odrivetool does.
+    pub fn to_json(properties: &[ConfigProperty], capabilities: &Capabilities) -> String {
+        let object: Map<String, Value> = properties
+            .iter()
+            .map(|property| {
+                let key = capabilities.property_path(&property.path).into_owned();
+                (key, json_value(property.value))
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&Value::Object(object))
+            .expect("JSON objects can always be serialized");
+        json.replace(&format!("\"{}\"", NEG_INFINITY), NEG_INFINITY)
+            .replace(&format!("\"{}\"", INFINITY), INFINITY)
+    }
+}
+
+/// How Python's `json` module, and so odrivetool, writes infinite floats such as the default
+/// `motor.config.torque_lim` of firmware 0.5.x. They are not valid JSON.
+const INFINITY: &str = "Infinity";
+const NEG_INFINITY: &str = "-Infinity";
+
+/// Quotes the infinities outside of strings, so that the text can be parsed as JSON.
+fn quote_infinities(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut rest = text;
+    while let Some(character) = rest.chars().next() {
+        if in_string {
+            in_string = escaped || character != '"';
+            escaped = !escaped && character == '\\';
+        } else if character == '"' {
+            in_string = true;
+        } else {
+            let infinity = [NEG_INFINITY, INFINITY]
+                .iter()
+                .find(|infinity| rest.starts_with(**infinity));
+            if let Some(infinity) = infinity {
+                quoted.push('"');
+                quoted.push_str(infinity);
+                quoted.push('"');
+                rest = &rest[infinity.len()..];
+                continue;
+            }
+        }
+        quoted.push(character);
+        rest = &rest[character.len_utf8()..];
+    }
+    quoted
+}
+
+/// Whether a path belongs to a `config` object, which odrivetool backs up.
+fn is_config_path(path: &str) -> bool {
+    path.starts_with("config.") || path.contains(".config.")
+}
+
+/// Appends the properties of a JSON object with their dotted paths.
+fn flatten(prefix: &str, object: Map<String, Value>, entries: &mut Vec<(String, Value)>) {
+    for (name, value) in object {
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            Value::Object(object) => flatten(&path, object, entries),
+            value => entries.push((path, value)),
+        }
+    }
+}
+
+fn parse_value(key: &str, value_type: ValueType, value: &Value) -> ODriveResult<ConfigValue> {
+    let parsed = match (value_type, value) {
+        (ValueType::Bool, Value::Bool(value)) => Some(ConfigValue::Bool(*value)),
+        (ValueType::Float, Value::Number(number)) => number
+            .as_f64()
+            .map(|value| ConfigValue::Float(value as f32)),
+        (ValueType::Float, Value::String(text)) if text == INFINITY => {
+            Some(ConfigValue::Float(f32::INFINITY))
+        }
+        (ValueType::Float, Value::String(text)) if text == NEG_INFINITY => {
+            Some(ConfigValue::Float(f32::NEG_INFINITY))
+        }
+        (_, Value::Number(number)) if value_type != ValueType::Bool => {
+            number.as_i64().map(ConfigValue::Int)
+        }
+        _ => None,
+    };
+    parsed.ok_or_else(|| invalid_config(&format!("{} has an invalid value {}", key, value)))
+}
+
+fn json_value(value: ConfigValue) -> Value {
+    match value {
+        ConfigValue::Bool(value) => Value::Bool(value),
+        ConfigValue::Int(value) => Value::from(value),
+        ConfigValue::Float(value) if value == f32::INFINITY => Value::from(INFINITY),
+        ConfigValue::Float(value) if value == f32::NEG_INFINITY => Value::from(NEG_INFINITY),
+        // Converted through the shortest representation, so that 0.1 is not written as
+        // 0.10000000149011612
+        ConfigValue::Float(value) => value
+            .to_string()
+            .parse()
+            .ok()
+            .and_then(Number::from_f64)
+            .map_or(Value::Null, Value::Number),
+    }
+}
+
+fn invalid_config(message: &str) -> ODriveError {
+    ODriveError::InvalidConfig(message.to_owned())
+}
+
+/// # odrivetool configuration files
+/// Files of `odrivetool backup-config` can be applied with `apply_odrivetool_config`, and
+/// `export_odrivetool_config` writes the configuration of the ODrive in the same format.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Writes every property of `config` with the `w` command, checking that it reads back the
+    /// written value, see `write_config_property`. The values are written as they are, so the
+    /// file should come from a board with the same firmware.
+    ///
+    /// Returns `InvalidConfig` without writing anything if `config` has unknown keys.
+    pub fn apply_odrivetool_config(&mut self, config: &OdrivetoolConfig) -> ODriveResult<()> {
+        if !config.unknown_keys.is_empty() {
+            return Err(invalid_config(&format!(
+                "unknown keys {}",
+                config.unknown_keys.join(", ")
+            )));
+        }
+        for property in &config.properties {
+            self.write_config_property(property)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the configuration with `backup_config` and formats it for
+    /// `odrivetool restore-config`.
+    pub fn export_odrivetool_config(&mut self) -> ODriveResult<String> {
+        let capabilities = self.capabilities();
+        let properties = self.backup_config()?.properties(&capabilities)?;
+        Ok(OdrivetoolConfig::to_json(&properties, &capabilities))
+    }
+}
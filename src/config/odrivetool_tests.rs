@@ -0,0 +1,220 @@
+use super::*;
+use crate::commands::FirmwareVersion;
+use crate::enumerations::errors::ODriveError;
+use crate::test_stream::MockStream;
+
+fn property(path: &str, value: ConfigValue) -> ConfigProperty {
+    ConfigProperty {
+        path: path.to_owned(),
+        value,
+    }
+}
+
+#[test]
+fn test_from_json() {
+    let config = OdrivetoolConfig::from_json(
+        r#"{
+            "config.brake_resistance": 0.5,
+            "config.enable_uart": false,
+            "axis0.motor.config.pole_pairs": 15,
+            "axis0.encoder.config.mode": 1,
+            "axis0.config.can_node_id": 3,
+            "axis0.config.calibration_lockin.current": 10.0,
+            "axis0.config.calibration_lockin.unknown": 1.0,
+            "axis0.motor.error": 0
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            property(
+                "axis0.config.calibration_lockin.current",
+                ConfigValue::Float(10.0)
+            ),
+            property("axis0.config.can_node_id", ConfigValue::Int(3)),
+            property("axis0.encoder.config.mode", ConfigValue::Int(1)),
+            property("axis0.motor.config.pole_pairs", ConfigValue::Int(15)),
+            property("config.brake_resistance", ConfigValue::Float(0.5)),
+            property("config.enable_uart", ConfigValue::Bool(false)),
+        ],
+        config.properties
+    );
+    assert_eq!(
+        vec![
+            "axis0.config.calibration_lockin.unknown".to_owned(),
+            "axis0.motor.error".to_owned(),
+        ],
+        config.unknown_keys
+    );
+}
+
+#[test]
+fn test_from_nested_json_of_0_5() {
+    let config = OdrivetoolConfig::from_json(
+        r#"{
+            "config": {"enable_uart_a": true},
+            "axis1": {"controller": {"config": {"inertia": 0.25, "input_mode": 5, "gain": 1}}}
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            property("axis1.trap_traj.config.A_per_css", ConfigValue::Float(0.25)),
+            property("axis1.controller.config.input_mode", ConfigValue::Int(5)),
+            property("config.enable_uart", ConfigValue::Bool(true)),
+        ],
+        config.properties
+    );
+    assert_eq!(
+        vec!["axis1.controller.config.gain".to_owned()],
+        config.unknown_keys
+    );
+}
+
+fn find<'a>(config: &'a OdrivetoolConfig, path: &str) -> Option<&'a ConfigValue> {
+    config
+        .properties
+        .iter()
+        .find(|property| property.path == path)
+        .map(|property| &property.value)
+}
+
+#[test]
+fn test_from_backup_of_0_4_12() {
+    let config =
+        OdrivetoolConfig::from_json(include_str!("testdata/odrivetool_0_4_12.json")).unwrap();
+    assert_eq!(Vec::<String>::new(), config.unknown_keys);
+    assert_eq!(
+        Some(&ConfigValue::Float(10.0)),
+        find(&config, "axis0.config.calibration_lockin.current")
+    );
+    assert_eq!(
+        Some(&ConfigValue::Bool(false)),
+        find(&config, "axis1.controller.config.setpoints_in_cpr")
+    );
+    assert_eq!(
+        Some(&ConfigValue::Float(f32::INFINITY)),
+        find(&config, "config.dc_max_positive_current")
+    );
+    // Read-only, so not restored
+    assert_eq!(
+        None,
+        find(&config, "axis0.controller.config.anticogging.cogging_ratio")
+    );
+}
+
+#[test]
+fn test_from_backup_of_0_5_6() {
+    let config =
+        OdrivetoolConfig::from_json(include_str!("testdata/odrivetool_0_5_6.json")).unwrap();
+    assert_eq!(Vec::<String>::new(), config.unknown_keys);
+    assert_eq!(
+        Some(&ConfigValue::Int(1)),
+        find(&config, "axis0.controller.config.input_mode")
+    );
+    assert_eq!(
+        Some(&ConfigValue::Bool(true)),
+        find(&config, "config.enable_uart")
+    );
+    assert_eq!(
+        Some(&ConfigValue::Float(8.0)),
+        find(&config, "axis1.motor.config.current_lim_tolerance")
+    );
+    assert_eq!(
+        Some(&ConfigValue::Float(f32::NEG_INFINITY)),
+        find(&config, "axis1.motor.config.I_bus_hard_min")
+    );
+    assert_eq!(
+        Some(&ConfigValue::Int(1)),
+        find(&config, "axis1.config.can.node_id")
+    );
+}
+
+#[test]
+fn test_infinities() {
+    let config = OdrivetoolConfig::from_json(
+        r#"{"config.dc_max_positive_current": Infinity, "config.uart_baudrate": 115200,
+            "axis0.config.unknown\"Infinity": -Infinity}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            property(
+                "config.dc_max_positive_current",
+                ConfigValue::Float(f32::INFINITY)
+            ),
+            property("config.uart_baudrate", ConfigValue::Int(115200)),
+        ],
+        config.properties
+    );
+    assert_eq!(
+        vec!["axis0.config.unknown\"Infinity".to_owned()],
+        config.unknown_keys
+    );
+    assert_eq!(
+        "{\n  \"config.dc_max_positive_current\": Infinity\n}",
+        OdrivetoolConfig::to_json(&config.properties[..1], &Capabilities::default())
+    );
+}
+
+#[test]
+fn test_from_invalid_json() {
+    for text in &[
+        "[1, 2]",
+        "{\"config.brake_resistance\": true}",
+        "{\"config.enable_uart\": 1}",
+        "{\"axis0.motor.config.pole_pairs\": 7.5}",
+        "{\"config.brake_resistance\": NaN}",
+    ] {
+        match OdrivetoolConfig::from_json(text) {
+            Err(ODriveError::InvalidConfig(_)) => {}
+            result => panic!("unexpected result {:?} for {}", result, text),
+        }
+    }
+}
+
+#[test]
+fn test_to_json() {
+    let properties = vec![
+        property("config.enable_uart", ConfigValue::Bool(true)),
+        property("axis0.motor.config.pole_pairs", ConfigValue::Int(15)),
+        property("axis0.controller.config.vel_gain", ConfigValue::Float(0.1)),
+    ];
+    let capabilities = Capabilities::new(FirmwareVersion::new(0, 5, 6), None);
+    let json = OdrivetoolConfig::to_json(&properties, &capabilities);
+    assert_eq!(
+        "{\n  \"axis0.controller.config.vel_gain\": 0.1,\n  \"axis0.motor.config.pole_pairs\": 15,\n  \"config.enable_uart_a\": true\n}",
+        json
+    );
+
+    let mut config = OdrivetoolConfig::from_json(&json).unwrap();
+    config.properties.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut expected = properties;
+    expected.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(expected, config.properties);
+}
+
+#[test]
+fn test_apply_odrivetool_config() {
+    let mut config = OdrivetoolConfig::from_json(
+        r#"{"axis0.motor.config.pole_pairs": 15, "axis0.config.unknown": 1}"#,
+    )
+    .unwrap();
+    let mut odrive = ODrive::new(MockStream::new());
+    match odrive.apply_odrivetool_config(&config) {
+        Err(ODriveError::InvalidConfig(message)) => {
+            assert_eq!("unknown keys axis0.config.unknown", message)
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(odrive.get_mut().write_buffer.is_empty());
+
+    config.unknown_keys.clear();
+    odrive.get_mut().push_response(b"");
+    odrive.get_mut().push_response(b"15\n");
+    odrive.apply_odrivetool_config(&config).unwrap();
+    assert_eq!(
+        b"w axis0.motor.config.pole_pairs 15\nr axis0.motor.config.pole_pairs\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
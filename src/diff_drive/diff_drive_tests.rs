@@ -0,0 +1,129 @@
+use super::*;
+use std::time::Duration;
+
+use crate::test_stream::MockStream;
+use crate::test_util::assert_close;
+
+const TOLERANCE: f32 = 1e-4;
+
+#[test]
+fn test_kinematics() {
+    let config = DiffDriveConfig::new(0.1, 0.5, 8192.0);
+    let twist = Twist {
+        linear: 1.0,
+        angular: -2.0,
+    };
+    let wheels = config.wheel_velocities(twist);
+    assert_eq!(
+        WheelVelocities {
+            left: 1.5,
+            right: 0.5,
+        },
+        wheels
+    );
+    assert_eq!(twist, config.twist(wheels));
+}
+
+#[test]
+fn test_normalize_angle() {
+    assert_close(0.5, normalize_angle(0.5), TOLERANCE);
+    assert_close(-PI + 0.5, normalize_angle(PI + 0.5), TOLERANCE);
+    assert_close(PI - 0.5, normalize_angle(-PI - 0.5 + 4.0 * PI), TOLERANCE);
+    assert_close(PI, normalize_angle(PI), TOLERANCE);
+}
+
+#[test]
+fn test_odometry_straight() {
+    let start = Instant::now();
+    let mut odometry = Odometry::new(0.5);
+    let estimate = odometry.update(3.0, -1.0, start);
+    assert_eq!(Pose::default(), estimate.pose);
+    assert_eq!(Twist::default(), estimate.twist);
+
+    let estimate = odometry.update(4.0, 0.0, start + Duration::from_millis(500));
+    assert_close(1.0, estimate.pose.x, TOLERANCE);
+    assert_close(0.0, estimate.pose.y, TOLERANCE);
+    assert_close(0.0, estimate.pose.heading, TOLERANCE);
+    assert_close(2.0, estimate.twist.linear, TOLERANCE);
+    assert_close(0.0, estimate.twist.angular, TOLERANCE);
+    assert_eq!(start + Duration::from_millis(500), estimate.timestamp);
+}
+
+#[test]
+fn test_odometry_turns() {
+    let start = Instant::now();
+    let track_width = 0.5;
+    let mut odometry = Odometry::new(track_width);
+    odometry.update(0.0, 0.0, start);
+
+    // A quarter circle to the left, with a radius of one meter
+    let steps = 100;
+    for step in 1..=steps {
+        let angle = PI / 2.0 * step as f32 / steps as f32;
+        let left = angle * (1.0 - track_width / 2.0);
+        let right = angle * (1.0 + track_width / 2.0);
+        odometry.update(left, right, start + Duration::from_millis(step * 10));
+    }
+    let pose = odometry.pose();
+    assert_close(1.0, pose.x, TOLERANCE);
+    assert_close(1.0, pose.y, TOLERANCE);
+    assert_close(PI / 2.0, pose.heading, TOLERANCE);
+
+    // Turning in place wraps the heading around
+    let mut odometry = Odometry::new(track_width);
+    odometry.update(0.0, 0.0, start);
+    odometry.update(-PI / 4.0, PI / 4.0, start + Duration::from_secs(2));
+    let estimate = odometry.update(
+        -PI / 4.0 - 1.0,
+        PI / 4.0 + 1.0,
+        start + Duration::from_secs(3),
+    );
+    assert_close(0.0, estimate.pose.x, TOLERANCE);
+    assert_close(4.0 - PI, estimate.pose.heading, TOLERANCE);
+    assert_close(4.0, estimate.twist.angular, TOLERANCE);
+}
+
+#[test]
+fn test_drive() {
+    let config =
+        DiffDriveConfig::new(0.5 / PI, 0.5, 8192.0).with_right(AxisID::One, Direction::Reversed);
+    let drive = DiffDrive::new(config);
+    let mut odrive = ODrive::new(MockStream::new());
+    drive
+        .drive(
+            &mut odrive,
+            Twist {
+                linear: 1.0,
+                angular: 2.0,
+            },
+        )
+        .unwrap();
+    drive.stop(&mut odrive).unwrap();
+    // The wheels have a circumference of one meter
+    assert_eq!(
+        b"v 0 4096 0\nv 1 -12288 0\nv 0 0 0\nv 1 -0 0\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_update_odometry() {
+    let config = DiffDriveConfig::new(0.5 / PI, 1.0, 8192.0)
+        .with_left(AxisID::One, Direction::Reversed)
+        .with_right(AxisID::Zero, Direction::Forward);
+    let mut drive = DiffDrive::new(config);
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"0 0\n");
+    odrive.get_mut().push_response(b"0 0\n");
+    odrive.get_mut().push_response(b"-8192 0\n");
+    odrive.get_mut().push_response(b"8192 0\n");
+
+    drive.update_odometry(&mut odrive).unwrap();
+    let estimate = drive.update_odometry(&mut odrive).unwrap();
+    assert_close(1.0, estimate.pose.x, TOLERANCE);
+    assert_close(0.0, estimate.pose.heading, TOLERANCE);
+    assert_eq!(
+        b"f 1\nf 0\nf 1\nf 0\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
@@ -0,0 +1,310 @@
+//! Kinematics and odometry of a differential drive robot, such as a hoverboard, with one wheel on
+//! each axis of the ODrive.
+//!
+//! A `Twist` of linear and angular velocity is converted into a velocity for each wheel, and the
+//! positions of the wheels are integrated into the `Pose` of the robot:
+//!
+//! ```
+//! use odrive_rs::diff_drive::{DiffDriveConfig, Twist};
+//!
+//! let config = DiffDriveConfig::new(0.1, 0.5, 8192.0);
+//! let wheels = config.wheel_velocities(Twist {
+//!     linear: 1.0,
+//!     angular: 2.0,
+//! });
+//! assert_eq!((0.5, 1.5), (wheels.left, wheels.right));
+//! ```
+//!
+//! Linear distances are in meters and angles in radians. A positive angular velocity turns the
+//! robot to the left.
+
+use std::f32::consts::PI;
+use std::io::{Read, Write};
+use std::time::Instant;
+
+use crate::commands::ODrive;
+use crate::enumerations::errors::ODriveResult;
+use crate::enumerations::AxisID;
+use crate::units::{AxisScaling, Meters};
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod diff_drive_tests;
+
+/// Whether an axis turns its wheel forwards or backwards for positive velocities. On most robots
+/// the motors are mirrored, so one of them is reversed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    Forward,
+    Reversed,
+}
+
+impl Direction {
+    fn sign(self) -> f32 {
+        match self {
+            Direction::Forward => 1.0,
+            Direction::Reversed => -1.0,
+        }
+    }
+}
+
+/// A wheel of the robot and the axis which drives it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Wheel {
+    pub axis: AxisID,
+    pub direction: Direction,
+}
+
+/// The velocity of the robot, in m/s forwards and in rad/s counterclockwise.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Twist {
+    pub linear: f32,
+    pub angular: f32,
+}
+
+/// The velocities of the left and right wheels at their circumference, in m/s.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct WheelVelocities {
+    pub left: f32,
+    pub right: f32,
+}
+
+/// The geometry of a differential drive robot.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DiffDriveConfig {
+    pub left: Wheel,
+    pub right: Wheel,
+    /// The distance between the contact points of the two wheels, in meters.
+    pub track_width: f32,
+    /// The scaling of both axes, including the radius of the wheels.
+    pub scaling: AxisScaling,
+}
+
+impl DiffDriveConfig {
+    /// A robot with its left wheel on axis 0 and its right wheel on axis 1, both turning forwards.
+    /// The wheels have a radius of `wheel_radius` and their encoders `counts_per_turn` counts per
+    /// turn.
+    pub fn new(wheel_radius: f32, track_width: f32, counts_per_turn: f32) -> Self {
+        let wheel = |axis| Wheel {
+            axis,
+            direction: Direction::Forward,
+        };
+        Self {
+            left: wheel(AxisID::Zero),
+            right: wheel(AxisID::One),
+            track_width,
+            scaling: AxisScaling::new(counts_per_turn).with_wheel_radius(wheel_radius),
+        }
+    }
+
+    pub fn with_left(self, axis: AxisID, direction: Direction) -> Self {
+        Self {
+            left: Wheel { axis, direction },
+            ..self
+        }
+    }
+
+    pub fn with_right(self, axis: AxisID, direction: Direction) -> Self {
+        Self {
+            right: Wheel { axis, direction },
+            ..self
+        }
+    }
+
+    pub fn with_gear_ratio(self, gear_ratio: f32) -> Self {
+        Self {
+            scaling: self.scaling.with_gear_ratio(gear_ratio),
+            ..self
+        }
+    }
+
+    /// The wheel velocities which move the robot with `twist`.
+    pub fn wheel_velocities(&self, twist: Twist) -> WheelVelocities {
+        let turn = twist.angular * self.track_width / 2.0;
+        WheelVelocities {
+            left: twist.linear - turn,
+            right: twist.linear + turn,
+        }
+    }
+
+    /// The motion of the robot when its wheels turn with `wheels`.
+    pub fn twist(&self, wheels: WheelVelocities) -> Twist {
+        Twist {
+            linear: (wheels.left + wheels.right) / 2.0,
+            angular: (wheels.right - wheels.left) / self.track_width,
+        }
+    }
+}
+
+/// The position of the robot relative to where the odometry started, in meters, and its heading
+/// in radians within [-π, π], counterclockwise from the x axis.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Pose {
+    pub x: f32,
+    pub y: f32,
+    pub heading: f32,
+}
+
+/// A pose estimated by `Odometry`, with the motion of the robot since the previous estimate.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OdometryEstimate {
+    pub pose: Pose,
+    pub twist: Twist,
+    pub timestamp: Instant,
+}
+
+/// Integrates the distances travelled by both wheels into the pose of the robot.
+#[derive(Debug, Clone)]
+pub struct Odometry {
+    track_width: f32,
+    pose: Pose,
+    /// The positions of the left and right wheels at the last update, in meters.
+    last: Option<(f32, f32, Instant)>,
+}
+
+impl Odometry {
+    /// Starts at the origin, heading along the x axis.
+    pub fn new(track_width: f32) -> Self {
+        Self::with_pose(track_width, Pose::default())
+    }
+
+    pub fn with_pose(track_width: f32, pose: Pose) -> Self {
+        Self {
+            track_width,
+            pose,
+            last: None,
+        }
+    }
+
+    pub fn pose(&self) -> Pose {
+        self.pose
+    }
+
+    /// Moves the estimate to `pose`, keeping the positions of the wheels.
+    pub fn reset(&mut self, pose: Pose) {
+        self.pose = pose;
+    }
+
+    /// Updates the pose from the positions of the left and right wheels, measured at `timestamp`.
+    /// The first update only records the positions.
+    pub fn update(&mut self, left: f32, right: f32, timestamp: Instant) -> OdometryEstimate {
+        let (last_left, last_right, last_timestamp) = self.last.unwrap_or((left, right, timestamp));
+        self.last = Some((left, right, timestamp));
+
+        let left_distance = left - last_left;
+        let right_distance = right - last_right;
+        let distance = (left_distance + right_distance) / 2.0;
+        let rotation = (right_distance - left_distance) / self.track_width;
+
+        // Move along the average heading of the interval
+        let heading = self.pose.heading + rotation / 2.0;
+        self.pose.x += distance * heading.cos();
+        self.pose.y += distance * heading.sin();
+        self.pose.heading = normalize_angle(self.pose.heading + rotation);
+
+        let elapsed = timestamp
+            .saturating_duration_since(last_timestamp)
+            .as_secs_f32();
+        let twist = if elapsed > 0.0 {
+            Twist {
+                linear: distance / elapsed,
+                angular: rotation / elapsed,
+            }
+        } else {
+            Twist::default()
+        };
+
+        OdometryEstimate {
+            pose: self.pose,
+            twist,
+            timestamp,
+        }
+    }
+}
+
+/// Wraps an angle into [-π, π].
+fn normalize_angle(angle: f32) -> f32 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped == -PI && angle > 0.0 {
+        PI
+    } else {
+        wrapped
+    }
+}
+
+/// Drives a differential drive robot with an ODrive and tracks its odometry.
+///
+/// The axes must be in closed loop velocity control.
+#[derive(Debug, Clone)]
+pub struct DiffDrive {
+    config: DiffDriveConfig,
+    odometry: Odometry,
+}
+
+impl DiffDrive {
+    pub fn new(config: DiffDriveConfig) -> Self {
+        Self {
+            config,
+            odometry: Odometry::new(config.track_width),
+        }
+    }
+
+    pub fn config(&self) -> &DiffDriveConfig {
+        &self.config
+    }
+
+    pub fn odometry(&self) -> &Odometry {
+        &self.odometry
+    }
+
+    pub fn odometry_mut(&mut self) -> &mut Odometry {
+        &mut self.odometry
+    }
+
+    /// Sets the velocities of both wheels to move the robot with `twist`.
+    pub fn drive<T>(&self, odrive: &mut ODrive<T>, twist: Twist) -> ODriveResult<()>
+    where
+        T: Read + Write,
+    {
+        let wheels = self.config.wheel_velocities(twist);
+        for (wheel, velocity) in [
+            (self.config.left, wheels.left),
+            (self.config.right, wheels.right),
+        ]
+        .iter()
+        {
+            odrive
+                .scaled(wheel.axis, self.config.scaling)
+                .set_velocity(Meters(wheel.direction.sign() * velocity), None)?;
+        }
+        Ok(())
+    }
+
+    pub fn stop<T>(&self, odrive: &mut ODrive<T>) -> ODriveResult<()>
+    where
+        T: Read + Write,
+    {
+        self.drive(odrive, Twist::default())
+    }
+
+    /// Reads the positions of both wheels and updates the odometry with them.
+    pub fn update_odometry<T>(&mut self, odrive: &mut ODrive<T>) -> ODriveResult<OdometryEstimate>
+    where
+        T: Read + Write,
+    {
+        let left = self.wheel_position(odrive, self.config.left)?;
+        let right = self.wheel_position(odrive, self.config.right)?;
+        Ok(self.odometry.update(left, right, Instant::now()))
+    }
+
+    /// The distance travelled by a wheel, in meters.
+    fn wheel_position<T>(&self, odrive: &mut ODrive<T>, wheel: Wheel) -> ODriveResult<f32>
+    where
+        T: Read + Write,
+    {
+        let feedback = odrive
+            .scaled(wheel.axis, self.config.scaling)
+            .get_feedback::<Meters>()?;
+        Ok(wheel.direction.sign() * feedback.position.0)
+    }
+}
@@ -0,0 +1,109 @@
+use super::*;
+use std::convert::TryFrom;
+
+#[test]
+fn test_axis_state_try_from() {
+    assert_eq!(AxisState::Idle, AxisState::try_from(1).unwrap());
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        AxisState::try_from(8).unwrap()
+    );
+}
+
+#[test]
+fn test_axis_state_try_from_out_of_range() {
+    match AxisState::try_from(42) {
+        Err(ODriveError::InvalidEnumValue { enumeration, value }) => {
+            assert_eq!("AxisState", enumeration);
+            assert_eq!("42", value);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_axis_state_from_str() {
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        "ClosedLoopControl".parse().unwrap()
+    );
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        "closed_loop_control".parse().unwrap()
+    );
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        "AXIS_STATE_CLOSED_LOOP_CONTROL".parse().unwrap()
+    );
+    assert_eq!(AxisState::MotorCalibration, "4".parse().unwrap());
+}
+
+#[test]
+fn test_control_mode_from_str() {
+    assert_eq!(
+        ControlMode::VelocityControl,
+        "velocity_control".parse().unwrap()
+    );
+    assert_eq!(
+        ControlMode::VelocityControl,
+        "CTRL_MODE_VELOCITY_CONTROL".parse().unwrap()
+    );
+    assert_eq!(
+        ControlMode::CurrentControl,
+        ControlMode::try_from(1).unwrap()
+    );
+}
+
+#[test]
+fn test_encoder_mode_from_str() {
+    assert_eq!(EncoderMode::EncoderModeHall, "hall".parse().unwrap());
+    assert_eq!(
+        EncoderMode::EncoderModeHall,
+        "ENCODER_MODE_HALL".parse().unwrap()
+    );
+    assert_eq!(
+        EncoderMode::EncoderModeIncremental,
+        "EncoderModeIncremental".parse().unwrap()
+    );
+}
+
+#[test]
+fn test_axis_id_from_str() {
+    assert_eq!(AxisID::Zero, "axis0".parse().unwrap());
+    assert_eq!(AxisID::One, "AXIS1".parse().unwrap());
+    assert_eq!(AxisID::One, "1".parse().unwrap());
+    assert_eq!(AxisID::Zero, "zero".parse().unwrap());
+    match "axis2".parse::<AxisID>() {
+        Err(ODriveError::InvalidEnumValue { value, .. }) => assert_eq!("axis2", value),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_motor_type_from_str() {
+    assert_eq!(
+        MotorType::MotorTypeGimbal,
+        "MOTOR_TYPE_GIMBAL".parse().unwrap()
+    );
+    assert_eq!(MotorType::HighCurrent, "high_current".parse().unwrap());
+    assert_eq!(MotorType::LowCurrent, MotorType::try_from(1).unwrap());
+}
+
+#[test]
+fn test_input_mode_from_str() {
+    assert_eq!(InputMode::TrapTraj, "INPUT_MODE_TRAP_TRAJ".parse().unwrap());
+    assert_eq!(InputMode::VelRamp, "vel_ramp".parse().unwrap());
+    assert_eq!(InputMode::Mirror, InputMode::try_from(7).unwrap());
+    assert!(InputMode::try_from(9).is_err());
+}
+
+#[test]
+fn test_from_str_unknown_name() {
+    match "warp_drive".parse::<ControlMode>() {
+        Err(ODriveError::InvalidEnumValue { enumeration, value }) => {
+            assert_eq!("ControlMode", enumeration);
+            assert_eq!("warp_drive", value);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,108 @@
+use crate::commands::{Capabilities, FirmwareVersion};
+use crate::enumerations::errors::{
+    AxisError, AxisErrors, ControllerError, ControllerErrors, EncoderErrors, MotorError,
+    MotorErrors, ODriveError,
+};
+
+#[test]
+fn test_from_bits_multiple_flags() {
+    let errors = AxisErrors::from_bits(0x842);
+    assert!(errors.contains(AxisError::ErrorDcBusUnderVoltage));
+    assert!(errors.contains(AxisError::ErrorMotorFailed));
+    assert!(errors.contains(AxisError::ErrorWatchdogTimerExpired));
+    assert!(!errors.contains(AxisError::ErrorInvalidState));
+    assert!(!errors.contains(AxisError::ErrorNone));
+    assert_eq!(0, errors.unknown_bits());
+    assert_eq!(
+        vec![
+            AxisError::ErrorDcBusUnderVoltage,
+            AxisError::ErrorMotorFailed,
+            AxisError::ErrorWatchdogTimerExpired
+        ],
+        errors.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_unknown_bits_are_kept() {
+    let errors = MotorErrors::from_bits(0x1_0000_0808);
+    assert_eq!(0x1_0000_0800, errors.unknown_bits());
+    assert_eq!(0x1_0000_0808, errors.bits());
+    assert_eq!(
+        vec![MotorError::ErrorDrvFault],
+        errors.into_iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        "The gate driver chip reported a fault, Unknown error bits 0x100000800",
+        errors.to_string()
+    );
+}
+
+#[test]
+fn test_undecoded_flags() {
+    let errors = MotorErrors::undecoded(0x808);
+    assert_eq!(0x808, errors.bits());
+    assert_eq!(0x808, errors.unknown_bits());
+    assert!(!errors.contains(MotorError::ErrorDrvFault));
+    assert_eq!(None, errors.iter().next());
+    assert_eq!("Unknown error bits 0x808", errors.to_string());
+    assert_ne!(MotorErrors::from_bits(0x808), errors);
+}
+
+#[test]
+fn test_flags_for_firmware() {
+    let firmware_0_4 = Capabilities::default();
+    let firmware_0_5 = Capabilities::new(FirmwareVersion::new(0, 5, 6), None);
+    assert_eq!(
+        AxisErrors::from_bits(0x40),
+        AxisErrors::for_firmware(0x40, &firmware_0_4)
+    );
+    assert_eq!(
+        AxisErrors::undecoded(0x40),
+        AxisErrors::for_firmware(0x40, &firmware_0_5)
+    );
+}
+
+#[test]
+fn test_empty_flags() {
+    let errors = EncoderErrors::from_bits(0);
+    assert!(errors.is_empty());
+    assert_eq!(None, errors.iter().next());
+    assert_eq!("No error", errors.to_string());
+    assert_eq!(EncoderErrors::default(), errors);
+}
+
+#[test]
+fn test_build_flags() {
+    let mut errors: AxisErrors = vec![AxisError::ErrorInvalidState, AxisError::ErrorMotorFailed]
+        .into_iter()
+        .collect();
+    assert_eq!(0x41, errors.bits());
+    errors.remove(AxisError::ErrorInvalidState);
+    errors |= AxisError::ErrorEncoderFailed;
+    assert_eq!(0x140, errors.bits());
+    assert_eq!(
+        AxisErrors::from_bits(0x142),
+        errors | AxisError::ErrorDcBusUnderVoltage
+    );
+}
+
+#[test]
+fn test_display() {
+    let errors = AxisErrors::from(AxisError::ErrorInvalidState) | AxisError::ErrorMotorDisarmed;
+    assert_eq!(
+        "An invalid state was requested, The motor was unexpectedly disarmed",
+        errors.to_string()
+    );
+    assert_eq!(
+        "Controller error: The motor speed exceeded the velocity limit by the configured tolerance",
+        ODriveError::Controller(ControllerErrors::from(ControllerError::ErrorOverspeed))
+            .to_string()
+    );
+}
+
+#[test]
+fn test_debug() {
+    let errors = AxisErrors::from_bits(0x1002);
+    assert_eq!("{ErrorDcBusUnderVoltage, 0x1000}", format!("{:?}", errors));
+}
@@ -0,0 +1,7 @@
+use super::*;
+
+#[cfg(test)]
+mod error_tests;
+
+#[cfg(test)]
+mod conversion_tests;
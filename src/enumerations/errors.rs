@@ -1,19 +1,53 @@
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{BitOr, BitOrAssign};
 use std::{fmt, io};
 
+use crate::commands::Capabilities;
+use crate::config::ApplyError;
+use crate::enumerations::{AxisID, AxisState};
+
 /// The `ODriveResult` type is used as a return type for operations which read to
 /// or write from the ODrive.
 pub type ODriveResult<T> = Result<T, ODriveError>;
 
 #[derive(Debug)]
 pub enum ODriveError {
-    Axis(AxisError),
-    Motor(MotorError),
-    Encoder(EncoderError),
-    Controller(ControllerError),
+    Axis(AxisErrors),
+    Motor(MotorErrors),
+    Encoder(EncoderErrors),
+    Controller(ControllerErrors),
     /// Used when the ODrive sends us an invalid message.
     /// If you see this, file an issue.
     InvalidMessageReceived(String),
+    /// Used when a value does not correspond to any variant of an enumeration.
+    InvalidEnumValue {
+        enumeration: &'static str,
+        value: String,
+    },
     NoMessageReceived,
+    /// Used when the ODrive rejects a read or write of a property, for example because the path
+    /// does not exist or the value does not fit its type.
+    InvalidProperty {
+        path: String,
+        reply: String,
+    },
+    /// Used when a property reads back a different value than the one which was written.
+    PropertyMismatch {
+        path: String,
+        written: String,
+        read: String,
+    },
+    /// Used when the firmware of the ODrive does not support a value or an operation.
+    Unsupported(String),
+    /// Used when a configuration file cannot be parsed or applied.
+    InvalidConfig(String),
+    /// Used when a configuration transaction fails and is rolled back, see `ODrive::apply_config`.
+    ConfigApply(Box<ApplyError>),
+    /// Used when the trajectory of an axis does not finish in time.
+    TrajectoryTimeout(AxisID),
+    /// Used when an axis fails to complete a requested state transition.
+    StateTransition(Box<TransitionError>),
     Io(io::Error),
 }
 
@@ -21,14 +55,40 @@ impl std::error::Error for ODriveError {}
 impl fmt::Display for ODriveError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ODriveError::Axis(err) => write!(f, "Axis error: {:?}", err),
-            ODriveError::Motor(err) => write!(f, "Motor error: {:?}", err),
-            ODriveError::Encoder(err) => write!(f, "Encoder error: {:?}", err),
-            ODriveError::Controller(err) => write!(f, "Controller error: {:?}", err),
+            ODriveError::Axis(err) => write!(f, "Axis error: {}", err),
+            ODriveError::Motor(err) => write!(f, "Motor error: {}", err),
+            ODriveError::Encoder(err) => write!(f, "Encoder error: {}", err),
+            ODriveError::Controller(err) => write!(f, "Controller error: {}", err),
             ODriveError::InvalidMessageReceived(err) => {
                 write!(f, "Invalid message received: {:?}", err)
             }
+            ODriveError::InvalidEnumValue { enumeration, value } => {
+                write!(f, "Invalid value for {}: {:?}", enumeration, value)
+            }
             ODriveError::NoMessageReceived => write!(f, "No message received"),
+            ODriveError::InvalidProperty { path, reply } => {
+                write!(f, "Property {} was rejected: {}", path, reply)
+            }
+            ODriveError::PropertyMismatch {
+                path,
+                written,
+                read,
+            } => write!(
+                f,
+                "Property {} was set to {} but reads {}",
+                path, written, read
+            ),
+            ODriveError::Unsupported(what) => write!(f, "Not supported: {}", what),
+            ODriveError::InvalidConfig(err) => write!(f, "Invalid configuration: {}", err),
+            ODriveError::ConfigApply(err) => write!(f, "Configuration not applied: {}", err),
+            ODriveError::TrajectoryTimeout(axis) => {
+                write!(
+                    f,
+                    "Trajectory of axis {} did not finish in time",
+                    *axis as u8
+                )
+            }
+            ODriveError::StateTransition(err) => write!(f, "State transition failed: {}", err),
             ODriveError::Io(err) => write!(f, "I/O error: {:?}", err),
         }
     }
@@ -91,3 +151,541 @@ pub enum ControllerError {
     ErrorNone = 0,
     ErrorOverspeed = 0x01,
 }
+
+/// A single error flag of one of the bitmasks reported by the ODrive.
+pub trait ErrorFlag: Copy + 'static {
+    /// Every flag which can be set, excluding `ErrorNone`.
+    const FLAGS: &'static [Self];
+
+    /// The bit of the bitmask corresponding to this flag.
+    fn bit(self) -> u64;
+
+    /// The description of this flag from the official documentation.
+    fn description(self) -> &'static str;
+}
+
+impl ErrorFlag for AxisError {
+    const FLAGS: &'static [Self] = &[
+        AxisError::ErrorInvalidState,
+        AxisError::ErrorDcBusUnderVoltage,
+        AxisError::ErrorDcBusOverVoltage,
+        AxisError::ErrorCurrentMeasurementTimeout,
+        AxisError::ErrorBrakeResistorDisarmed,
+        AxisError::ErrorMotorDisarmed,
+        AxisError::ErrorMotorFailed,
+        AxisError::ErrorSensorlessEstimatorFailed,
+        AxisError::ErrorEncoderFailed,
+        AxisError::ErrorControllerFailed,
+        AxisError::ErrorPosCtrlDuringSensorless,
+        AxisError::ErrorWatchdogTimerExpired,
+    ];
+
+    fn bit(self) -> u64 {
+        self as u64
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            AxisError::ErrorNone => "No error",
+            AxisError::ErrorInvalidState => "An invalid state was requested",
+            AxisError::ErrorDcBusUnderVoltage => {
+                "The DC bus voltage fell below the undervoltage trip level"
+            }
+            AxisError::ErrorDcBusOverVoltage => {
+                "The DC bus voltage exceeded the overvoltage trip level"
+            }
+            AxisError::ErrorCurrentMeasurementTimeout => "The current measurement timed out",
+            AxisError::ErrorBrakeResistorDisarmed => "The brake resistor was unexpectedly disarmed",
+            AxisError::ErrorMotorDisarmed => "The motor was unexpectedly disarmed",
+            AxisError::ErrorMotorFailed => "The motor reported an error",
+            AxisError::ErrorSensorlessEstimatorFailed => {
+                "The sensorless estimator reported an error"
+            }
+            AxisError::ErrorEncoderFailed => "The encoder reported an error",
+            AxisError::ErrorControllerFailed => "The controller reported an error",
+            AxisError::ErrorPosCtrlDuringSensorless => {
+                "Position control was requested during sensorless control"
+            }
+            AxisError::ErrorWatchdogTimerExpired => {
+                "The axis watchdog timer expired before it was fed"
+            }
+        }
+    }
+}
+
+impl ErrorFlag for MotorError {
+    const FLAGS: &'static [Self] = &[
+        MotorError::ErrorPhaseResistanceOutOfRange,
+        MotorError::ErrorPhaseInductanceOutOfRange,
+        MotorError::ErrorAdcFailed,
+        MotorError::ErrorDrvFault,
+        MotorError::ErrorControlDeadlineMissed,
+        MotorError::ErrorNotImplementedMotorType,
+        MotorError::ErrorBrakeCurrentOutOfRange,
+        MotorError::ErrorModulationMagnitude,
+        MotorError::ErrorBrakeDeadTimeViolation,
+        MotorError::ErrorUnexpectedTimerCallback,
+        MotorError::ErrorCurrentSenseSaturation,
+        MotorError::ErrorCurrentUnstable,
+    ];
+
+    fn bit(self) -> u64 {
+        self as u64
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            MotorError::ErrorNone => "No error",
+            MotorError::ErrorPhaseResistanceOutOfRange => {
+                "The measured phase resistance is outside the plausible range"
+            }
+            MotorError::ErrorPhaseInductanceOutOfRange => {
+                "The measured phase inductance is outside the plausible range"
+            }
+            MotorError::ErrorAdcFailed => "The ADC measurement failed",
+            MotorError::ErrorDrvFault => "The gate driver chip reported a fault",
+            MotorError::ErrorControlDeadlineMissed => "The motor control loop missed its deadline",
+            MotorError::ErrorNotImplementedMotorType => {
+                "The configured motor type is not supported"
+            }
+            MotorError::ErrorBrakeCurrentOutOfRange => {
+                "The brake resistor current exceeded the allowed range"
+            }
+            MotorError::ErrorModulationMagnitude => {
+                "The bus voltage was insufficient to push the requested current through the motor"
+            }
+            MotorError::ErrorBrakeDeadTimeViolation => "The brake dead time was violated",
+            MotorError::ErrorUnexpectedTimerCallback => "An unexpected timer callback occurred",
+            MotorError::ErrorCurrentSenseSaturation => "The current sense amplifier saturated",
+            MotorError::ErrorCurrentUnstable => "The current control loop became unstable",
+        }
+    }
+}
+
+impl ErrorFlag for EncoderError {
+    const FLAGS: &'static [Self] = &[
+        EncoderError::ErrorUnstableGain,
+        EncoderError::ErrorCprOutOfRange,
+        EncoderError::ErrorNoResponse,
+        EncoderError::ErrorUnsupportedEncoderMode,
+        EncoderError::ErrorIllegalHallState,
+        EncoderError::ErrorIndexNotFoundYet,
+    ];
+
+    fn bit(self) -> u64 {
+        self as u64
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            EncoderError::ErrorNone => "No error",
+            EncoderError::ErrorUnstableGain => "The encoder estimator gain is unstable",
+            EncoderError::ErrorCprOutOfRange => {
+                "The measured counts per revolution do not match the configured cpr"
+            }
+            EncoderError::ErrorNoResponse => "The encoder did not move during calibration",
+            EncoderError::ErrorUnsupportedEncoderMode => {
+                "The configured encoder mode is not supported"
+            }
+            EncoderError::ErrorIllegalHallState => "An invalid hall sensor state was observed",
+            EncoderError::ErrorIndexNotFoundYet => "The encoder index has not been found yet",
+        }
+    }
+}
+
+impl ErrorFlag for ControllerError {
+    const FLAGS: &'static [Self] = &[ControllerError::ErrorOverspeed];
+
+    fn bit(self) -> u64 {
+        self as u64
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ControllerError::ErrorNone => "No error",
+            ControllerError::ErrorOverspeed => {
+                "The motor speed exceeded the velocity limit by the configured tolerance"
+            }
+        }
+    }
+}
+
+impl fmt::Display for AxisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl fmt::Display for MotorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl fmt::Display for EncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// A set of error flags, decoded from a bitmask reported by the ODrive.
+///
+/// Bits which do not correspond to a known flag, for example because they were added in a newer
+/// firmware version, are kept and can be inspected with `unknown_bits`.
+///
+/// The flags follow firmware 0.4.x. Firmware 0.5.0 renumbered them, so its bitmasks are kept
+/// undecoded: no flag is set and every bit is unknown.
+pub struct ErrorFlags<E> {
+    bits: u64,
+    decoded: bool,
+    flag: PhantomData<E>,
+}
+
+/// The errors reported by `<axis>.error`.
+pub type AxisErrors = ErrorFlags<AxisError>;
+/// The errors reported by `<axis>.motor.error`.
+pub type MotorErrors = ErrorFlags<MotorError>;
+/// The errors reported by `<axis>.encoder.error`.
+pub type EncoderErrors = ErrorFlags<EncoderError>;
+/// The errors reported by `<axis>.controller.error`.
+pub type ControllerErrors = ErrorFlags<ControllerError>;
+
+impl<E: ErrorFlag> ErrorFlags<E> {
+    /// Creates a set without any flags.
+    pub fn empty() -> Self {
+        Self::from_bits(0)
+    }
+
+    /// Decodes a bitmask reported by the ODrive. All bits are kept, including unknown ones.
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            bits,
+            decoded: true,
+            flag: PhantomData,
+        }
+    }
+
+    /// Keeps a bitmask whose flags are not known, such as one reported by firmware 0.5.x.
+    /// No flag is set, and `unknown_bits` returns the whole bitmask.
+    pub fn undecoded(bits: u64) -> Self {
+        Self {
+            bits,
+            decoded: false,
+            flag: PhantomData,
+        }
+    }
+
+    /// Decodes a bitmask reported by the given firmware, see `undecoded`.
+    pub fn for_firmware(bits: u64, capabilities: &Capabilities) -> Self {
+        if capabilities.decodes_error_flags() {
+            Self::from_bits(bits)
+        } else {
+            Self::undecoded(bits)
+        }
+    }
+
+    /// Returns the raw bitmask.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns the bits which do not correspond to any known flag.
+    pub fn unknown_bits(&self) -> u64 {
+        if !self.decoded {
+            return self.bits;
+        }
+        E::FLAGS
+            .iter()
+            .fold(self.bits, |bits, flag| bits & !flag.bit())
+    }
+
+    /// Returns true if no bit is set.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns true if the given flag is set.
+    pub fn contains(&self, flag: E) -> bool {
+        let bit = flag.bit();
+        self.decoded && bit != 0 && self.bits & bit == bit
+    }
+
+    pub fn insert(&mut self, flag: E) {
+        self.bits |= flag.bit();
+    }
+
+    pub fn remove(&mut self, flag: E) {
+        self.bits &= !flag.bit();
+    }
+
+    /// Iterates over the known flags which are set.
+    pub fn iter(&self) -> Flags<E> {
+        Flags {
+            bits: if self.decoded { self.bits } else { 0 },
+            index: 0,
+            flag: PhantomData,
+        }
+    }
+}
+
+impl<E> Clone for ErrorFlags<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for ErrorFlags<E> {}
+
+impl<E> PartialEq for ErrorFlags<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits && self.decoded == other.decoded
+    }
+}
+
+impl<E> Eq for ErrorFlags<E> {}
+
+impl<E> std::hash::Hash for ErrorFlags<E> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+        self.decoded.hash(state)
+    }
+}
+
+impl<E: ErrorFlag> Default for ErrorFlags<E> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<E: ErrorFlag> From<E> for ErrorFlags<E> {
+    fn from(flag: E) -> Self {
+        Self::from_bits(flag.bit())
+    }
+}
+
+impl<E: ErrorFlag> FromIterator<E> for ErrorFlags<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut flags = Self::empty();
+        for flag in iter {
+            flags.insert(flag);
+        }
+        flags
+    }
+}
+
+impl<E: ErrorFlag> BitOr<E> for ErrorFlags<E> {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: E) -> Self {
+        self.insert(rhs);
+        self
+    }
+}
+
+impl<E: ErrorFlag> BitOr for ErrorFlags<E> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            bits: self.bits | rhs.bits,
+            decoded: self.decoded && rhs.decoded,
+            flag: PhantomData,
+        }
+    }
+}
+
+impl<E: ErrorFlag> BitOrAssign<E> for ErrorFlags<E> {
+    fn bitor_assign(&mut self, rhs: E) {
+        self.insert(rhs)
+    }
+}
+
+impl<E: ErrorFlag> IntoIterator for ErrorFlags<E> {
+    type Item = E;
+    type IntoIter = Flags<E>;
+
+    fn into_iter(self) -> Flags<E> {
+        self.iter()
+    }
+}
+
+impl<E: ErrorFlag + fmt::Debug> fmt::Debug for ErrorFlags<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut set = f.debug_set();
+        set.entries(self.iter());
+        if self.unknown_bits() != 0 {
+            set.entry(&format_args!("{:#x}", self.unknown_bits()));
+        }
+        set.finish()
+    }
+}
+
+/// Lists the description of every flag which is set, followed by any unknown bits.
+impl<E: ErrorFlag> fmt::Display for ErrorFlags<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No error");
+        }
+
+        let mut separator = "";
+        for flag in self.iter() {
+            write!(f, "{}{}", separator, flag.description())?;
+            separator = ", ";
+        }
+        if self.unknown_bits() != 0 {
+            write!(
+                f,
+                "{}Unknown error bits {:#x}",
+                separator,
+                self.unknown_bits()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the known flags of an `ErrorFlags` set.
+#[derive(Debug, Clone)]
+pub struct Flags<E> {
+    bits: u64,
+    index: usize,
+    flag: PhantomData<E>,
+}
+
+impl<E: ErrorFlag> Iterator for Flags<E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        while let Some(flag) = E::FLAGS.get(self.index) {
+            self.index += 1;
+            if self.bits & flag.bit() != 0 {
+                return Some(*flag);
+            }
+        }
+        None
+    }
+}
+
+/// The errors reported by a single axis and its motor, encoder and controller.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct AxisErrorReport {
+    pub axis: AxisErrors,
+    pub motor: MotorErrors,
+    pub encoder: EncoderErrors,
+    pub controller: ControllerErrors,
+}
+
+impl AxisErrorReport {
+    /// Decodes the raw values of the `error` properties of an axis.
+    pub fn from_bits(axis: u64, motor: u64, encoder: u64, controller: u64) -> Self {
+        Self {
+            axis: AxisErrors::from_bits(axis),
+            motor: MotorErrors::from_bits(motor),
+            encoder: EncoderErrors::from_bits(encoder),
+            controller: ControllerErrors::from_bits(controller),
+        }
+    }
+
+    /// Decodes the raw values of the `error` properties of an axis, as reported by the given
+    /// firmware. See `ErrorFlags::undecoded`.
+    pub fn for_firmware(
+        axis: u64,
+        motor: u64,
+        encoder: u64,
+        controller: u64,
+        capabilities: &Capabilities,
+    ) -> Self {
+        Self {
+            axis: AxisErrors::for_firmware(axis, capabilities),
+            motor: MotorErrors::for_firmware(motor, capabilities),
+            encoder: EncoderErrors::for_firmware(encoder, capabilities),
+            controller: ControllerErrors::for_firmware(controller, capabilities),
+        }
+    }
+
+    /// Returns true if none of the components of the axis reported an error.
+    pub fn is_empty(&self) -> bool {
+        self.axis.is_empty()
+            && self.motor.is_empty()
+            && self.encoder.is_empty()
+            && self.controller.is_empty()
+    }
+}
+
+impl fmt::Display for AxisErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Axis={}, Motor={}, Encoder={}, Controller={}",
+            self.axis, self.motor, self.encoder, self.controller
+        )
+    }
+}
+
+/// Every error reported by the ODrive, as read by `ODrive::read_all_errors`.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ErrorReport {
+    /// The raw value of the top-level `error` property.
+    pub system: u64,
+    pub axis0: AxisErrorReport,
+    pub axis1: AxisErrorReport,
+}
+
+impl ErrorReport {
+    /// Returns the errors reported by the given axis.
+    pub fn axis(&self, axis: AxisID) -> &AxisErrorReport {
+        match axis {
+            AxisID::Zero => &self.axis0,
+            AxisID::One => &self.axis1,
+        }
+    }
+
+    /// Returns true if the ODrive did not report any error.
+    pub fn is_empty(&self) -> bool {
+        self.system == 0 && self.axis0.is_empty() && self.axis1.is_empty()
+    }
+}
+
+impl fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Config={}\nAxis0: {}\nAxis1: {}",
+            self.system, self.axis0, self.axis1
+        )
+    }
+}
+
+/// Describes a state transition which did not complete, see `ODrive::run_state`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TransitionError {
+    pub axis: AxisID,
+    pub requested_state: AxisState,
+    /// The last state reported by the axis.
+    pub last_state: AxisState,
+    /// The errors read from the axis after the transition failed.
+    pub errors: AxisErrorReport,
+    /// True if the transition did not complete in time, false if the axis fell back to `Idle`.
+    pub timed_out: bool,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = if self.timed_out {
+            "timed out"
+        } else {
+            "returned to Idle"
+        };
+        write!(
+            f,
+            "axis {} {} while running {:?} (last state {:?}): {}",
+            self.axis as u8, reason, self.requested_state, self.last_state, self.errors
+        )
+    }
+}
@@ -6,9 +6,19 @@
 /// the ODrive will be caught.
 pub mod errors;
 
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod enumeration_tests;
+
 /// Used to indicate one of the two motors controlled by the ODrive.
 #[repr(u8)]
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AxisID {
     Zero = 0,
     One = 1,
@@ -30,6 +40,7 @@ pub enum AxisID {
 /// > 8. `ClosedLoopControl`
 #[repr(u8)]
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AxisState {
     /// Used for
     Undefined = 0,
@@ -71,6 +82,7 @@ pub enum AxisState {
 
 #[repr(u8)]
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MotorType {
     HighCurrent = 0,
     LowCurrent = 1,
@@ -79,6 +91,7 @@ pub enum MotorType {
 
 #[repr(u8)]
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ControlMode {
     VoltageControl = 0,
     CurrentControl = 1,
@@ -89,7 +102,145 @@ pub enum ControlMode {
 
 #[repr(u8)]
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EncoderMode {
     EncoderModeIncremental = 0,
     EncoderModeHall = 1,
 }
+
+/// How the controller follows the `input_pos`, `input_vel` and `input_torque` properties,
+/// set in `<axis>.controller.config.input_mode`. Introduced in firmware 0.5.0.
+#[repr(u8)]
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputMode {
+    /// Ignore the inputs.
+    Inactive = 0,
+    /// Pass the inputs directly to the setpoints.
+    Passthrough = 1,
+    /// Ramp the velocity setpoint to `input_vel` at `controller.config.vel_ramp_rate`.
+    VelRamp = 2,
+    /// Smooth `input_pos` with a second order filter of `controller.config.input_filter_bandwidth`.
+    PosFilter = 3,
+    /// Mix two input channels, as used by the hobby RC input.
+    MixChannels = 4,
+    /// Move to `input_pos` along a trapezoidal trajectory, configured in `<axis>.trap_traj.config`.
+    TrapTraj = 5,
+    /// Ramp the torque setpoint to `input_torque` at `controller.config.torque_ramp_rate`.
+    TorqueRamp = 6,
+    /// Follow the position of the axis `controller.config.axis_to_mirror`, scaled by
+    /// `controller.config.mirror_ratio`.
+    Mirror = 7,
+}
+
+/// Normalizes the name of an enumeration value so that `ClosedLoopControl`, `closed_loop_control`
+/// and `AXIS_STATE_CLOSED_LOOP_CONTROL` are all treated the same.
+fn normalize_name(name: &str, prefixes: &[&str]) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .filter(|ch| *ch != '_')
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    prefixes
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .map(str::to_owned)
+        .unwrap_or(name)
+}
+
+/// Implements `TryFrom<i32>` for the values used on the wire and `FromStr` for the names of the
+/// values, as well as for integers sent by the ODrive.
+macro_rules! impl_conversions {
+    ($name:ident, [$($prefix:expr),*], { $($variant:ident),* $(,)? }) => {
+        impl TryFrom<i32> for $name {
+            type Error = ODriveError;
+
+            fn try_from(value: i32) -> ODriveResult<Self> {
+                $(
+                    if value == $name::$variant as i32 {
+                        return Ok($name::$variant);
+                    }
+                )*
+                Err(ODriveError::InvalidEnumValue {
+                    enumeration: stringify!($name),
+                    value: value.to_string(),
+                })
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ODriveError;
+
+            fn from_str(s: &str) -> ODriveResult<Self> {
+                if let Ok(value) = s.trim().parse::<i32>() {
+                    return $name::try_from(value);
+                }
+
+                let prefixes = [$($prefix),*];
+                let name = normalize_name(s, &prefixes);
+                // A value after the prefix, as in `axis0`
+                if let Ok(Ok(variant)) = name.parse::<i32>().map($name::try_from) {
+                    return Ok(variant);
+                }
+                $(
+                    if name == normalize_name(stringify!($variant), &prefixes) {
+                        return Ok($name::$variant);
+                    }
+                )*
+                Err(ODriveError::InvalidEnumValue {
+                    enumeration: stringify!($name),
+                    value: s.to_owned(),
+                })
+            }
+        }
+    };
+}
+
+impl_conversions!(AxisID, ["axis"], {
+    Zero,
+    One,
+});
+
+impl_conversions!(AxisState, ["axisstate"], {
+    Undefined,
+    Idle,
+    StartupSequence,
+    FullCalibrationSequence,
+    MotorCalibration,
+    SensorlessControl,
+    EncoderIndexSearch,
+    EncoderOffsetCalibration,
+    ClosedLoopControl,
+});
+
+impl_conversions!(MotorType, ["motortype"], {
+    HighCurrent,
+    LowCurrent,
+    MotorTypeGimbal,
+});
+
+impl_conversions!(ControlMode, ["controlmode", "ctrlmode"], {
+    VoltageControl,
+    CurrentControl,
+    VelocityControl,
+    PositionControl,
+    TrajectoryControl,
+});
+
+impl_conversions!(EncoderMode, ["encodermode"], {
+    EncoderModeIncremental,
+    EncoderModeHall,
+});
+
+impl_conversions!(InputMode, ["inputmode"], {
+    Inactive,
+    Passthrough,
+    VelRamp,
+    PosFilter,
+    MixChannels,
+    TrapTraj,
+    TorqueRamp,
+    Mirror,
+});
@@ -0,0 +1,89 @@
+use std::thread::sleep;
+use std::time::Instant;
+
+use crate::commands::{
+    transition_error, Feedback, Transition, TransitionEnd, TransitionStep, TransitionWatch,
+};
+use crate::enumerations::errors::{AxisErrorReport, ODriveResult};
+use crate::enumerations::{AxisID, AxisState};
+
+/// The high-level operations which are available regardless of how the ODrive is connected.
+///
+/// It is implemented by `commands::ODrive` for the ASCII protocol and by `can::CanODrive` for CAN,
+/// so that control code can be written once for both.
+pub trait ODriveInterface {
+    /// Sets the velocity setpoint of an axis and switches it to velocity control.
+    fn set_velocity(
+        &mut self,
+        axis: AxisID,
+        velocity: f32,
+        current_feed_forward: Option<f32>,
+    ) -> ODriveResult<()>;
+
+    /// Sets the current setpoint of an axis and switches it to current control.
+    /// Firmware 0.5 and later interpret the setpoint as a torque, in Nm.
+    fn set_current(&mut self, axis: AxisID, current: f32) -> ODriveResult<()>;
+
+    /// Reads the position and velocity estimates of an axis.
+    fn get_feedback(&mut self, axis: AxisID) -> ODriveResult<Feedback>;
+
+    /// Retrieves the current state of an axis.
+    fn current_state(&mut self, axis: AxisID) -> ODriveResult<AxisState>;
+
+    /// Requests a new state for an axis without waiting for it.
+    fn request_state(&mut self, axis: AxisID, requested_state: AxisState) -> ODriveResult<()>;
+
+    /// Read the errors of an axis and its motor, encoder and controller.
+    fn read_axis_errors(&mut self, axis: AxisID) -> ODriveResult<AxisErrorReport>;
+
+    /// Clear errors.
+    fn clear_errors(&mut self) -> ODriveResult<()>;
+
+    /// Changes the state of an axis and waits as configured by `transition`.
+    ///
+    /// A transition fails with `ODriveError::StateTransition` if it does not complete before the
+    /// timeout, or if the axis is back in `Idle` with errors. The error contains the last observed
+    /// state and the errors read from the axis.
+    fn run_state(
+        &mut self,
+        axis: AxisID,
+        requested_state: AxisState,
+        transition: Transition,
+    ) -> ODriveResult<()> {
+        let deadline = Instant::now() + transition.timeout;
+        self.request_state(axis, requested_state)?;
+        if transition.end == TransitionEnd::NoWait {
+            return Ok(());
+        }
+
+        let mut watch = TransitionWatch::new(transition.end);
+        loop {
+            let state = self.current_state(axis)?;
+            let errors = if TransitionWatch::needs_errors(state) {
+                self.read_axis_errors(axis)?
+            } else {
+                AxisErrorReport::default()
+            };
+            match watch.step(state, &errors) {
+                TransitionStep::Complete => return Ok(()),
+                TransitionStep::Failed => {
+                    return Err(transition_error(
+                        axis,
+                        requested_state,
+                        state,
+                        errors,
+                        false,
+                    ))
+                }
+                TransitionStep::Continue => (),
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                let errors = self.read_axis_errors(axis)?;
+                return Err(transition_error(axis, requested_state, state, errors, true));
+            }
+            sleep(transition.poll_interval.min(deadline - now));
+        }
+    }
+}
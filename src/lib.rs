@@ -2,18 +2,66 @@
 /// protocol.
 pub mod commands;
 
+/// The `config` module contains `ODriveConfig`, a backup of the configuration of an ODrive.
+pub mod config;
+
+/// The `diff_drive` module contains the kinematics and odometry of differential drive robots.
+pub mod diff_drive;
+
 /// The `enumerations` module contains enums and constants related to different properties and
 /// errors.
 pub mod enumerations;
 
+/// The `async_commands` module contains `AsyncODrive`, an async version of the ODrive structure
+/// built on tokio. It is enabled by the `tokio` feature.
+#[cfg(feature = "tokio")]
+pub mod async_commands;
+
+/// The `can` module implements the CAN Simple protocol of the ODrive firmware 0.5.
+pub mod can;
+
+/// The `interface` module contains the operations shared by the ASCII and CAN connections.
+pub mod interface;
+
+/// The `native` module implements the native binary protocol, which reads and writes properties
+/// by endpoint ID.
+pub mod native;
+
+/// The `properties` module contains a typed tree of the property paths of the ODrive.
+pub mod properties;
+
+/// The `simulator` module contains a simulated ODrive, which can be used in place of a serial port
+/// to test applications without hardware. It is enabled by the `simulator` feature.
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
+
+/// The `trajectory` module plans trajectories on the host and streams them to the ODrive.
+pub mod trajectory;
+
+/// The `units` module contains typed units of position and their conversion per axis.
+pub mod units;
+
 #[cfg(test)]
 #[cfg_attr(tarpaulin, skip)]
 mod test_stream;
 
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod test_util;
+
 pub mod prelude {
-    pub use crate::commands::ODrive;
+    pub use crate::commands::{
+        Capabilities, Feedback, FirmwareVersion, ODrive, Transition, TransitionEnd, TrapTrajConfig,
+        WatchdogConfig, WatchdogGuard,
+    };
+    pub use crate::config::ODriveConfig;
     pub use crate::enumerations::errors::{
-        AxisError, ControllerError, EncoderError, MotorError, ODriveError, ODriveResult,
+        AxisError, AxisErrorReport, AxisErrors, ControllerError, ControllerErrors, EncoderError,
+        EncoderErrors, ErrorFlag, ErrorReport, MotorError, MotorErrors, ODriveError, ODriveResult,
+        TransitionError,
+    };
+    pub use crate::enumerations::{
+        AxisID, AxisState, ControlMode, EncoderMode, InputMode, MotorType,
     };
-    pub use crate::enumerations::{AxisID, AxisState, ControlMode, EncoderMode, MotorType};
+    pub use crate::interface::ODriveInterface;
 }
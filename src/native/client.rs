@@ -0,0 +1,245 @@
+use std::io;
+use std::io::{Read, Write};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::commands::DEFAULT_TIMEOUT;
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+use crate::native::{
+    encode_packet, json_crc, EndpointValue, PacketDecoder, MAX_PACKET_SIZE, PROTOCOL_VERSION,
+};
+
+/// The time to sleep between reads while the stream has no data available.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The number of times a request is sent before giving up on a response.
+const MAX_ATTEMPTS: usize = 3;
+
+/// The size of a request without input data: sequence number, endpoint ID, response length and
+/// trailer.
+const REQUEST_OVERHEAD: usize = 8;
+
+/// The largest output of a single request, which leaves room for the sequence number.
+const MAX_OUTPUT_LENGTH: usize = MAX_PACKET_SIZE - 2;
+
+/// Set in the endpoint ID of a request to ask for a response.
+const EXPECT_ACK: u16 = 0x8000;
+
+/// Set in the sequence number of a response.
+const RESPONSE_FLAG: u16 = 0x8000;
+
+/// The `NativeODrive` struct manages a connection with an ODrive over the native binary protocol.
+///
+/// Properties are addressed by endpoint ID, as listed in the JSON endpoint description. The
+/// description is fetched from the ODrive the first time another endpoint is used, since its CRC
+/// must be sent along with every request.
+#[derive(Debug)]
+pub struct NativeODrive<T>
+where
+    T: Read + Write,
+{
+    io_stream: T,
+    timeout: Duration,
+    decoder: PacketDecoder,
+    /// The sequence number of the last request.
+    sequence_number: u16,
+    json_crc: Option<u16>,
+}
+
+impl<T> NativeODrive<T>
+where
+    T: Read + Write,
+{
+    pub fn new(io_stream: T) -> Self {
+        Self {
+            io_stream,
+            timeout: DEFAULT_TIMEOUT,
+            decoder: PacketDecoder::new(),
+            sequence_number: 0,
+            json_crc: None,
+        }
+    }
+
+    /// Creates a `NativeODrive` for an ODrive whose JSON endpoint description has the given
+    /// CRC, which saves fetching the description.
+    pub fn with_json_crc(io_stream: T, json_crc: u16) -> Self {
+        Self {
+            json_crc: Some(json_crc),
+            ..Self::new(io_stream)
+        }
+    }
+
+    /// The time to wait for the response to each attempt of a request.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// The CRC of the JSON endpoint description, once it is known.
+    pub fn json_crc(&self) -> Option<u16> {
+        self.json_crc
+    }
+
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &T {
+        &self.io_stream
+    }
+
+    /// Gets a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io_stream
+    }
+
+    /// Sends a request to an endpoint and returns its output.
+    ///
+    /// If `expect_ack` is false, the request is sent once and an empty output is returned right
+    /// away. Otherwise it is sent up to three times until a response with its sequence number
+    /// arrives, and `NoMessageReceived` is returned if none does.
+    pub fn endpoint_operation(
+        &mut self,
+        endpoint_id: u16,
+        input: &[u8],
+        expect_ack: bool,
+        output_length: usize,
+    ) -> ODriveResult<Vec<u8>> {
+        if input.len() + REQUEST_OVERHEAD > MAX_PACKET_SIZE || output_length > MAX_OUTPUT_LENGTH {
+            return Err(ODriveError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the request does not fit in a packet",
+            )));
+        }
+        let trailer = if endpoint_id == 0 {
+            PROTOCOL_VERSION
+        } else {
+            match self.json_crc {
+                Some(crc) => crc,
+                None => self.fetch_json_crc()?,
+            }
+        };
+
+        self.sequence_number = (self.sequence_number + 1) & !RESPONSE_FLAG;
+        let sequence_number = self.sequence_number;
+        let endpoint = if expect_ack {
+            endpoint_id | EXPECT_ACK
+        } else {
+            endpoint_id
+        };
+
+        let mut payload = Vec::with_capacity(input.len() + REQUEST_OVERHEAD);
+        payload.extend_from_slice(&sequence_number.to_le_bytes());
+        payload.extend_from_slice(&endpoint.to_le_bytes());
+        payload.extend_from_slice(&(output_length as u16).to_le_bytes());
+        payload.extend_from_slice(input);
+        payload.extend_from_slice(&trailer.to_le_bytes());
+        let packet = encode_packet(&payload).expect("the request size was checked");
+
+        for _ in 0..MAX_ATTEMPTS {
+            self.io_stream.write_all(&packet).map_err(ODriveError::Io)?;
+            self.io_stream.flush().map_err(ODriveError::Io)?;
+            if !expect_ack {
+                return Ok(Vec::new());
+            }
+            if let Some(output) = self.receive_response(sequence_number)? {
+                return Ok(output);
+            }
+        }
+        Err(ODriveError::NoMessageReceived)
+    }
+
+    /// Waits up to the timeout for the response to the request with the given sequence number.
+    /// Responses to earlier requests are discarded.
+    fn receive_response(&mut self, sequence_number: u16) -> ODriveResult<Option<Vec<u8>>> {
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = [0; 256];
+        loop {
+            while let Some(packet) = self.decoder.next_packet() {
+                if packet.len() < 2 {
+                    continue;
+                }
+                let received = u16::from_le_bytes([packet[0], packet[1]]);
+                if received == sequence_number | RESPONSE_FLAG {
+                    return Ok(Some(packet[2..].to_vec()));
+                }
+            }
+
+            let count = match self.io_stream.read(&mut buffer) {
+                Ok(count) => count,
+                Err(error) => match error.kind() {
+                    io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted => 0,
+                    _ => return Err(ODriveError::Io(error)),
+                },
+            };
+            self.decoder.push(&buffer[..count]);
+
+            if count == 0 {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(None);
+                }
+                sleep(POLL_INTERVAL.min(deadline - now));
+            }
+        }
+    }
+
+    /// Reads the raw value of an endpoint, which must be `length` bytes long.
+    pub fn read_endpoint(&mut self, endpoint_id: u16, length: usize) -> ODriveResult<Vec<u8>> {
+        let output = self.endpoint_operation(endpoint_id, &[], true, length)?;
+        if output.len() != length {
+            return Err(ODriveError::InvalidMessageReceived(format!(
+                "expected {} bytes from endpoint {}, received {:?}",
+                length, endpoint_id, output
+            )));
+        }
+        Ok(output)
+    }
+
+    /// Writes the raw value of an endpoint and waits for the ODrive to acknowledge it.
+    pub fn write_endpoint(&mut self, endpoint_id: u16, value: &[u8]) -> ODriveResult<()> {
+        self.endpoint_operation(endpoint_id, value, true, 0)
+            .map(|_| ())
+    }
+
+    /// Reads a typed value from an endpoint.
+    pub fn read<V: EndpointValue>(&mut self, endpoint_id: u16) -> ODriveResult<V> {
+        let output = self.read_endpoint(endpoint_id, V::SIZE)?;
+        V::from_bytes(&output).ok_or_else(|| {
+            ODriveError::InvalidMessageReceived(format!(
+                "invalid value from endpoint {}: {:?}",
+                endpoint_id, output
+            ))
+        })
+    }
+
+    /// Writes a typed value to an endpoint.
+    pub fn write<V: EndpointValue>(&mut self, endpoint_id: u16, value: V) -> ODriveResult<()> {
+        self.write_endpoint(endpoint_id, &value.to_bytes())
+    }
+
+    /// Fetches the JSON endpoint description from endpoint 0 and remembers its CRC.
+    pub fn fetch_endpoint_description(&mut self) -> ODriveResult<String> {
+        let mut json = Vec::new();
+        loop {
+            let offset = (json.len() as u32).to_le_bytes();
+            let chunk = self.endpoint_operation(0, &offset, true, MAX_OUTPUT_LENGTH)?;
+            if chunk.is_empty() {
+                break;
+            }
+            json.extend_from_slice(&chunk);
+        }
+
+        self.json_crc = Some(json_crc(&json));
+        String::from_utf8(json).map_err(|error| {
+            ODriveError::InvalidMessageReceived(String::from_utf8_lossy(error.as_bytes()).into())
+        })
+    }
+
+    fn fetch_json_crc(&mut self) -> ODriveResult<u16> {
+        self.fetch_endpoint_description()?;
+        Ok(self.json_crc.unwrap_or_default())
+    }
+}
@@ -0,0 +1,193 @@
+//! Support for the native binary protocol of the ODrive, as spoken by `odrivetool` over USB and
+//! over UART.
+//!
+//! On a byte stream, every packet is framed as:
+//!
+//! | sync byte | length | CRC8 of sync byte and length | payload      | CRC16 of payload |
+//! |-----------|--------|------------------------------|--------------|------------------|
+//! | `0xAA`    | 1 byte | 1 byte                       | up to 127 B  | 2 B, big endian  |
+//!
+//! A request payload consists of a sequence number, the endpoint ID, the expected length of the
+//! response, the input data and a trailer, which is the protocol version for endpoint 0 and the
+//! CRC16 of the JSON endpoint description otherwise. The response repeats the sequence number,
+//! with the most significant bit set, followed by the output data. All integers are little
+//! endian.
+//!
+//! Endpoint 0 holds the JSON description of all other endpoints, which is read in chunks by
+//! sending the offset of each chunk as input.
+
+use std::convert::TryInto;
+
+pub use client::NativeODrive;
+
+mod client;
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod native_tests;
+
+/// The first byte of every packet on a byte stream.
+pub const SYNC_BYTE: u8 = 0xAA;
+/// The largest payload of a packet, since the length byte must not have its top bit set.
+pub const MAX_PACKET_SIZE: usize = 127;
+/// The protocol version, sent as the trailer of requests to endpoint 0.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+const CRC8_INIT: u8 = 0x42;
+const CRC8_POLYNOMIAL: u8 = 0x37;
+const CRC16_INIT: u16 = 0x1337;
+const CRC16_POLYNOMIAL: u16 = 0x3d65;
+
+/// Computes the CRC8 used for packet headers, starting from `init`.
+pub fn crc8(init: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(init, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ CRC8_POLYNOMIAL
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// Computes the CRC16 used for packet payloads and the JSON endpoint description, starting from
+/// `init`.
+pub fn crc16(init: u16, bytes: &[u8]) -> u16 {
+    bytes.iter().fold(init, |mut crc, byte| {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ CRC16_POLYNOMIAL
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// The CRC16 of the JSON endpoint description, which is the trailer of all requests to endpoints
+/// other than 0.
+pub fn json_crc(json: &[u8]) -> u16 {
+    crc16(PROTOCOL_VERSION, json)
+}
+
+/// Frames a payload for a byte stream. Returns `None` if it is longer than `MAX_PACKET_SIZE`.
+pub fn encode_packet(payload: &[u8]) -> Option<Vec<u8>> {
+    if payload.len() > MAX_PACKET_SIZE {
+        return None;
+    }
+
+    let mut packet = Vec::with_capacity(payload.len() + 5);
+    packet.push(SYNC_BYTE);
+    packet.push(payload.len() as u8);
+    packet.push(crc8(CRC8_INIT, &packet));
+    packet.extend_from_slice(payload);
+    packet.extend_from_slice(&crc16(CRC16_INIT, payload).to_be_bytes());
+    Some(packet)
+}
+
+/// Extracts the payloads of packets from a byte stream.
+///
+/// Bytes which do not start a valid packet, and packets with an invalid CRC, are skipped, so the
+/// decoder resynchronises on the next sync byte after garbage or lost bytes.
+#[derive(Debug, Default, Clone)]
+pub struct PacketDecoder {
+    buffer: Vec<u8>,
+}
+
+impl PacketDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends received bytes.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the payload of the next complete packet, if there is one.
+    pub fn next_packet(&mut self) -> Option<Vec<u8>> {
+        loop {
+            match self.buffer.iter().position(|byte| *byte == SYNC_BYTE) {
+                Some(start) => {
+                    self.buffer.drain(..start);
+                }
+                None => {
+                    self.buffer.clear();
+                    return None;
+                }
+            }
+            if self.buffer.len() < 3 {
+                return None;
+            }
+
+            let length = self.buffer[1] as usize;
+            if length > MAX_PACKET_SIZE || crc8(CRC8_INIT, &self.buffer[..3]) != 0 {
+                self.buffer.remove(0);
+                continue;
+            }
+            if self.buffer.len() < length + 5 {
+                return None;
+            }
+
+            if crc16(CRC16_INIT, &self.buffer[3..length + 5]) != 0 {
+                self.buffer.remove(0);
+                continue;
+            }
+            let payload = self.buffer[3..length + 3].to_vec();
+            self.buffer.drain(..length + 5);
+            return Some(payload);
+        }
+    }
+}
+
+/// A value which can be read from or written to an endpoint, in the little endian encoding of the
+/// native protocol.
+pub trait EndpointValue: Sized {
+    /// The number of bytes of the encoded value.
+    const SIZE: usize;
+
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Decodes a value, returning `None` if `bytes` does not have the right length.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl EndpointValue for bool {
+    const SIZE: usize = 1;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [value] => Some(*value != 0),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_endpoint_value {
+    ($($type:ty),*) => {
+        $(
+            impl EndpointValue for $type {
+                const SIZE: usize = std::mem::size_of::<$type>();
+
+                fn to_bytes(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+
+                fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                    bytes.try_into().ok().map(<$type>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_endpoint_value!(u8, i8, u16, i16, u32, i32, u64, i64, f32);
@@ -0,0 +1,286 @@
+use super::*;
+use crate::enumerations::errors::ODriveError;
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::TryInto;
+use std::io::{Error, Read, Write};
+use std::time::Duration;
+
+const JSON: &str = r#"[{"name":"","id":0,"type":"json","access":"r"},{"name":"vbus_voltage","id":1,"type":"float","access":"r"},{"name":"axis0","type":"object","members":[{"name":"error","id":2,"type":"uint32","access":"rw"},{"name":"current_state","id":3,"type":"int32","access":"r"},{"name":"requested_state","id":4,"type":"int32","access":"rw"}]},{"name":"enable_uart","id":5,"type":"bool","access":"rw"}]"#;
+
+/// An in-process stand-in for an ODrive speaking the native protocol.
+#[derive(Debug)]
+struct TestDevice {
+    json: Vec<u8>,
+    endpoints: BTreeMap<u16, Vec<u8>>,
+    decoder: PacketDecoder,
+    output: VecDeque<u8>,
+    /// The number of responses to drop, simulating a lossy link.
+    dropped_responses: usize,
+    /// The sequence number and endpoint ID of every valid request.
+    requests: Vec<(u16, u16)>,
+}
+
+impl TestDevice {
+    fn new() -> Self {
+        let mut endpoints = BTreeMap::new();
+        endpoints.insert(1, 24.5f32.to_le_bytes().to_vec());
+        endpoints.insert(2, 0u32.to_le_bytes().to_vec());
+        endpoints.insert(3, 1i32.to_le_bytes().to_vec());
+        endpoints.insert(4, 0i32.to_le_bytes().to_vec());
+        endpoints.insert(5, vec![1]);
+        Self {
+            json: JSON.as_bytes().to_vec(),
+            endpoints,
+            decoder: PacketDecoder::new(),
+            output: VecDeque::new(),
+            dropped_responses: 0,
+            requests: Vec::new(),
+        }
+    }
+
+    fn handle(&mut self, packet: &[u8]) {
+        if packet.len() < 8 {
+            return;
+        }
+        let word = |offset: usize| u16::from_le_bytes([packet[offset], packet[offset + 1]]);
+        let sequence_number = word(0);
+        let endpoint = word(2) & 0x7FFF;
+        let expect_ack = word(2) & 0x8000 != 0;
+        let output_length = word(4) as usize;
+        let input = &packet[6..packet.len() - 2];
+        let trailer = word(packet.len() - 2);
+
+        let expected_trailer = if endpoint == 0 {
+            PROTOCOL_VERSION
+        } else {
+            json_crc(&self.json)
+        };
+        if trailer != expected_trailer {
+            return;
+        }
+        self.requests.push((sequence_number, endpoint));
+
+        let output = if endpoint == 0 {
+            let offset = u32::from_le_bytes(input.try_into().unwrap()) as usize;
+            let start = offset.min(self.json.len());
+            let end = (offset + output_length).min(self.json.len());
+            self.json[start..end].to_vec()
+        } else {
+            match self.endpoints.get_mut(&endpoint) {
+                Some(value) if !input.is_empty() => {
+                    *value = input.to_vec();
+                    Vec::new()
+                }
+                Some(value) => value[..output_length.min(value.len())].to_vec(),
+                None => return,
+            }
+        };
+
+        if !expect_ack {
+            return;
+        }
+        if self.dropped_responses > 0 {
+            self.dropped_responses -= 1;
+            return;
+        }
+        let mut response = (sequence_number | 0x8000).to_le_bytes().to_vec();
+        response.extend_from_slice(&output);
+        self.output.extend(encode_packet(&response).unwrap());
+    }
+}
+
+impl Write for TestDevice {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.decoder.push(buf);
+        while let Some(packet) = self.decoder.next_packet() {
+            self.handle(&packet);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Read for TestDevice {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let count = buf.len().min(self.output.len());
+        for (target, byte) in buf.iter_mut().zip(self.output.drain(..count)) {
+            *target = byte;
+        }
+        Ok(count)
+    }
+}
+
+fn init_odrive() -> NativeODrive<TestDevice> {
+    let mut odrive = NativeODrive::with_json_crc(TestDevice::new(), json_crc(JSON.as_bytes()));
+    odrive.set_timeout(Duration::from_millis(5));
+    odrive
+}
+
+#[test]
+fn test_crc() {
+    assert_eq!(0x8C, crc8(CRC8_INIT, b"123456789"));
+    assert_eq!(0xAA01, crc16(CRC16_INIT, b"123456789"));
+    assert_eq!(0x74AB, json_crc(br#"[{"name":"vbus_voltage"}]"#));
+}
+
+#[test]
+fn test_encode_packet() {
+    let payload = [
+        0x01, 0x00, 0x00, 0x80, 0x7D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+    ];
+    assert_eq!(
+        vec![
+            0xAA, 0x0C, 0xE1, 0x01, 0x00, 0x00, 0x80, 0x7D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x6B, 0xC6
+        ],
+        encode_packet(&payload).unwrap()
+    );
+    assert!(encode_packet(&[0; MAX_PACKET_SIZE]).is_some());
+    assert!(encode_packet(&[0; MAX_PACKET_SIZE + 1]).is_none());
+}
+
+#[test]
+fn test_decode_split_packets() {
+    let first = encode_packet(b"first").unwrap();
+    let second = encode_packet(b"second").unwrap();
+    let mut decoder = PacketDecoder::new();
+    for byte in first[..first.len() - 1].iter() {
+        decoder.push(&[*byte]);
+        assert_eq!(None, decoder.next_packet());
+    }
+
+    decoder.push(&first[first.len() - 1..]);
+    decoder.push(&second[..4]);
+    assert_eq!(Some(b"first".to_vec()), decoder.next_packet());
+    assert_eq!(None, decoder.next_packet());
+    decoder.push(&second[4..]);
+    assert_eq!(Some(b"second".to_vec()), decoder.next_packet());
+}
+
+#[test]
+fn test_decode_resynchronises() {
+    let mut corrupted = encode_packet(b"lost").unwrap();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0xFF;
+
+    let mut stream = b"garbage\xAA\x7F".to_vec();
+    stream.extend(corrupted);
+    stream.extend(encode_packet(&[SYNC_BYTE, SYNC_BYTE]).unwrap());
+    let mut decoder = PacketDecoder::new();
+    decoder.push(&stream);
+    assert_eq!(Some(vec![SYNC_BYTE, SYNC_BYTE]), decoder.next_packet());
+    assert_eq!(None, decoder.next_packet());
+}
+
+#[test]
+fn test_endpoint_values() {
+    assert_eq!(vec![0, 0, 0xC4, 0x41], 24.5f32.to_bytes());
+    assert_eq!(Some(-2i16), i16::from_bytes(&[0xFE, 0xFF]));
+    assert_eq!(Some(true), bool::from_bytes(&[2]));
+    assert_eq!(None, u32::from_bytes(&[0, 0]));
+    assert_eq!(8, u64::SIZE);
+}
+
+#[test]
+fn test_read_and_write_endpoints() {
+    let mut odrive = init_odrive();
+    assert_eq!(24.5, odrive.read::<f32>(1).unwrap());
+    assert!(odrive.read::<bool>(5).unwrap());
+
+    odrive.write(4, 8i32).unwrap();
+    assert_eq!(8, odrive.read::<i32>(4).unwrap());
+    odrive.write(5, false).unwrap();
+    assert!(!odrive.read::<bool>(5).unwrap());
+}
+
+#[test]
+fn test_sequence_numbers() {
+    let mut odrive = init_odrive();
+    odrive.read::<f32>(1).unwrap();
+    odrive.write(2, 0u32).unwrap();
+    odrive.read::<u32>(2).unwrap();
+    assert_eq!(vec![(1, 1), (2, 2), (3, 2)], odrive.get_ref().requests);
+}
+
+#[test]
+fn test_retry_after_lost_response() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().dropped_responses = 2;
+    assert_eq!(1, odrive.read::<i32>(3).unwrap());
+    // The same request is sent again
+    assert_eq!(vec![(1, 3); 3], odrive.get_ref().requests);
+}
+
+#[test]
+fn test_discards_stale_responses() {
+    let mut odrive = init_odrive();
+    let stale = encode_packet(&[0x05, 0x80, 0xFF]).unwrap();
+    odrive.get_mut().output.extend(stale);
+    assert_eq!(24.5, odrive.read::<f32>(1).unwrap());
+}
+
+#[test]
+fn test_no_response() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().dropped_responses = 3;
+    match odrive.read::<f32>(1) {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_wrong_response_length() {
+    let mut odrive = init_odrive();
+    match odrive.read_endpoint(5, 4) {
+        Err(ODriveError::InvalidMessageReceived(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_request_too_large() {
+    let mut odrive = init_odrive();
+    match odrive.write_endpoint(1, &[0; MAX_PACKET_SIZE]) {
+        Err(ODriveError::Io(error)) => assert_eq!(std::io::ErrorKind::InvalidInput, error.kind()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(odrive.get_ref().requests.is_empty());
+}
+
+#[test]
+fn test_fetch_endpoint_description() {
+    let mut odrive = NativeODrive::new(TestDevice::new());
+    assert_eq!(None, odrive.json_crc());
+    assert_eq!(JSON, odrive.fetch_endpoint_description().unwrap());
+    assert_eq!(Some(json_crc(JSON.as_bytes())), odrive.json_crc());
+
+    // The description is read in chunks, until an empty one
+    let chunks = JSON.len().div_ceil(125) + 1;
+    assert_eq!(chunks, odrive.get_ref().requests.len());
+    assert!(odrive
+        .get_ref()
+        .requests
+        .iter()
+        .all(|(_, endpoint)| *endpoint == 0));
+}
+
+#[test]
+fn test_description_fetched_on_first_use() {
+    let mut odrive = NativeODrive::new(TestDevice::new());
+    assert_eq!(24.5, odrive.read::<f32>(1).unwrap());
+    assert!(odrive.json_crc().is_some());
+}
+
+#[test]
+fn test_wrong_json_crc_is_ignored_by_device() {
+    let mut odrive = NativeODrive::with_json_crc(TestDevice::new(), 0x1234);
+    odrive.set_timeout(Duration::from_millis(2));
+    match odrive.read::<f32>(1) {
+        Err(ODriveError::NoMessageReceived) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
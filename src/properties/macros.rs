@@ -0,0 +1,85 @@
+/// Generates the objects of the property tree.
+///
+/// Each object lists its child objects and its properties, with the value type and the access
+/// (`ro` or `rw`) of each property. Objects and properties which exist in only one firmware are
+/// marked with `@ v0_4` or `@ v0_5`.
+macro_rules! property_tree {
+    ($(
+        $(#[$meta:meta])*
+        $object:ident {
+            objects: [$($child:ident: $child_object:ident $(@ $child_firmware:ident)?),* $(,)?],
+            properties: [
+                $($property:ident: $value:ty = $access:ident $(@ $firmware:ident)?),* $(,)?
+            ] $(,)?
+        }
+    )*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug)]
+            pub struct $object<H> {
+                pub(super) handle: H,
+                pub(super) path: String,
+            }
+
+            #[allow(non_snake_case)]
+            impl<H> $object<H> {
+                /// The full path of this object.
+                pub fn path(&self) -> &str {
+                    &self.path
+                }
+
+                $(
+                    #[doc = property_tree!(@doc $($child_firmware)?)]
+                    pub fn $child(self) -> $child_object<H> {
+                        $child_object {
+                            handle: self.handle,
+                            path: join(&self.path, stringify!($child)),
+                        }
+                    }
+                )*
+
+                $(
+                    #[doc = property_tree!(@doc $($firmware)?)]
+                    pub fn $property(self) -> Property<H, $value, property_tree!(@access $access)> {
+                        Property::new(self.handle, join(&self.path, stringify!($property)))
+                    }
+                )*
+
+                /// Appends the properties of this object and of all objects below it, which exist
+                /// in the firmware of `availability`.
+                pub(crate) fn collect(
+                    path: &str,
+                    availability: Availability,
+                    properties: &mut Vec<PropertyInfo>,
+                ) {
+                    $(
+                        properties.push(PropertyInfo {
+                            path: join(path, stringify!($property)),
+                            value_type: <$value as PropertyValue>::VALUE_TYPE,
+                            writable: property_tree!(@writable $access),
+                            availability: availability
+                                .and(property_tree!(@availability $($firmware)?)),
+                        });
+                    )*
+                    $(
+                        $child_object::<()>::collect(
+                            &join(path, stringify!($child)),
+                            availability.and(property_tree!(@availability $($child_firmware)?)),
+                            properties,
+                        );
+                    )*
+                }
+            }
+        )*
+    };
+    (@access ro) => { ReadOnly };
+    (@access rw) => { ReadWrite };
+    (@writable ro) => { false };
+    (@writable rw) => { true };
+    (@availability) => { Availability::ALL };
+    (@availability v0_4) => { Availability::V0_4 };
+    (@availability v0_5) => { Availability::V0_5 };
+    (@doc) => { "In firmware 0.4.x and 0.5.x." };
+    (@doc v0_4) => { "Only in firmware 0.4.x." };
+    (@doc v0_5) => { "Only in firmware 0.5.x." };
+}
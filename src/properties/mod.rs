@@ -0,0 +1,332 @@
+//! A typed tree of the properties of the ODrive, for firmware 0.4.x and 0.5.x.
+//!
+//! Every object of the tree is a struct with one method per child object and per property, so
+//! misspelled paths do not compile. Each property carries its value type, and only writable
+//! properties can be set:
+//!
+//! ```
+//! use odrive_rs::enumerations::AxisID;
+//! use odrive_rs::properties;
+//!
+//! let current_lim = properties::root()
+//!     .axis(AxisID::Zero)
+//!     .motor()
+//!     .config()
+//!     .current_lim();
+//! assert_eq!("axis0.motor.config.current_lim", current_lim.path());
+//! ```
+//!
+//! Properties which exist in only one firmware are marked in their documentation, and `all` and
+//! `for_firmware` list the properties with the firmware which has them.
+//!
+//! Paths obtained from `ODrive::properties` or `ODrive::axis` are bound to the connection, and
+//! can be read and written directly, as in
+//! `odrive.axis(AxisID::Zero).motor().config().current_lim().set(20.0)`.
+//! Read-only properties have no setter:
+//!
+//! ```compile_fail
+//! use odrive_rs::commands::ODrive;
+//! use odrive_rs::enumerations::{AxisID, AxisState};
+//!
+//! let mut odrive = ODrive::new(std::io::Cursor::new(Vec::new()));
+//! odrive.axis(AxisID::Zero).current_state().set(AxisState::Idle);
+//! ```
+
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+use crate::commands::{
+    parse_bool, parse_enum, parse_response, Capabilities, FirmwareVersion, ODrive,
+};
+use crate::enumerations::errors::{ErrorFlag, ErrorFlags, ODriveResult};
+use crate::enumerations::{AxisID, AxisState, ControlMode, EncoderMode, InputMode, MotorType};
+
+pub use tree::*;
+
+#[macro_use]
+mod macros;
+mod tree;
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod property_tests;
+
+/// Marks a property which can only be read.
+#[derive(Debug)]
+pub enum ReadOnly {}
+
+/// Marks a property which can be read and written.
+#[derive(Debug)]
+pub enum ReadWrite {}
+
+/// The type of the value of a property, as listed by `all`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ValueType {
+    Bool,
+    Float,
+    Unsigned,
+    Signed,
+    /// An enumeration of this crate, sent as an integer.
+    Enumeration(&'static str),
+}
+
+/// A value which can be sent and received over the ASCII protocol.
+pub trait PropertyValue: Sized {
+    const VALUE_TYPE: ValueType;
+
+    /// Formats the value as it is sent by the `w` command to the firmware of `capabilities`.
+    fn format_value(&self, capabilities: &Capabilities) -> ODriveResult<String>;
+
+    /// Parses a response of the firmware of `capabilities` to the `r` command.
+    fn parse_value(response: String, capabilities: &Capabilities) -> ODriveResult<Self>;
+}
+
+impl PropertyValue for bool {
+    const VALUE_TYPE: ValueType = ValueType::Bool;
+
+    fn format_value(&self, _capabilities: &Capabilities) -> ODriveResult<String> {
+        Ok((*self as u8).to_string())
+    }
+
+    fn parse_value(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+        parse_bool(response)
+    }
+}
+
+macro_rules! impl_number_value {
+    ($value_type:ident: $($type:ty),*) => {
+        $(
+            impl PropertyValue for $type {
+                const VALUE_TYPE: ValueType = ValueType::$value_type;
+
+                fn format_value(&self, _capabilities: &Capabilities) -> ODriveResult<String> {
+                    Ok(self.to_string())
+                }
+
+                fn parse_value(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+                    parse_response(response)
+                }
+            }
+        )*
+    };
+}
+
+impl_number_value!(Float: f32);
+impl_number_value!(Unsigned: u8, u16, u32, u64);
+impl_number_value!(Signed: i8, i16, i32, i64);
+
+/// Implements `PropertyValue` for enumerations whose values are the same in every firmware.
+macro_rules! impl_enumeration_value {
+    ($($type:ident),*) => {
+        $(
+            impl PropertyValue for $type {
+                const VALUE_TYPE: ValueType = ValueType::Enumeration(stringify!($type));
+
+                fn format_value(&self, _capabilities: &Capabilities) -> ODriveResult<String> {
+                    Ok((*self as i32).to_string())
+                }
+
+                fn parse_value(response: String, _capabilities: &Capabilities) -> ODriveResult<Self> {
+                    parse_enum(response)
+                }
+            }
+        )*
+    };
+}
+
+impl_enumeration_value!(AxisState, EncoderMode, InputMode);
+
+impl PropertyValue for ControlMode {
+    const VALUE_TYPE: ValueType = ValueType::Enumeration("ControlMode");
+
+    fn format_value(&self, capabilities: &Capabilities) -> ODriveResult<String> {
+        Ok(capabilities.control_mode_value(*self).to_string())
+    }
+
+    fn parse_value(response: String, capabilities: &Capabilities) -> ODriveResult<Self> {
+        capabilities.control_mode_from_value(parse_response(response)?)
+    }
+}
+
+impl PropertyValue for MotorType {
+    const VALUE_TYPE: ValueType = ValueType::Enumeration("MotorType");
+
+    fn format_value(&self, capabilities: &Capabilities) -> ODriveResult<String> {
+        Ok(capabilities.motor_type_value(*self)?.to_string())
+    }
+
+    fn parse_value(response: String, capabilities: &Capabilities) -> ODriveResult<Self> {
+        capabilities.motor_type_from_value(parse_response(response)?)
+    }
+}
+
+impl<E: ErrorFlag> PropertyValue for ErrorFlags<E> {
+    const VALUE_TYPE: ValueType = ValueType::Unsigned;
+
+    fn format_value(&self, _capabilities: &Capabilities) -> ODriveResult<String> {
+        Ok(self.bits().to_string())
+    }
+
+    fn parse_value(response: String, capabilities: &Capabilities) -> ODriveResult<Self> {
+        parse_response(response).map(|bits| ErrorFlags::for_firmware(bits, capabilities))
+    }
+}
+
+/// A property at the end of a path, whose value has type `V` and whose access is `A`.
+/// When `H` is a connection, the property can be read with `get` and, if writable, written with
+/// `set`.
+#[derive(Debug)]
+pub struct Property<H, V, A> {
+    handle: H,
+    path: String,
+    marker: PhantomData<fn() -> (V, A)>,
+}
+
+impl<H, V, A> Property<H, V, A> {
+    fn new(handle: H, path: String) -> Self {
+        Self {
+            handle,
+            path,
+            marker: PhantomData,
+        }
+    }
+
+    /// The full path of the property, for example `axis0.motor.config.current_lim`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl<T, V, A> Property<&mut ODrive<T>, V, A>
+where
+    T: Read + Write,
+    V: PropertyValue,
+{
+    /// Reads the property, with enumeration values interpreted for the connected firmware.
+    pub fn get(self) -> ODriveResult<V> {
+        let capabilities = self.handle.capabilities();
+        let response = self.handle.read_property_response(&self.path)?;
+        V::parse_value(response, &capabilities)
+    }
+}
+
+impl<T, V> Property<&mut ODrive<T>, V, ReadWrite>
+where
+    T: Read + Write,
+    V: PropertyValue,
+{
+    /// Writes the property with `ODrive::write_property`, which returns `InvalidProperty` if the
+    /// ODrive rejects the write. Enumeration values are mapped for the connected firmware.
+    pub fn set(self, value: V) -> ODriveResult<()> {
+        let value = value.format_value(&self.handle.capabilities())?;
+        self.handle.write_property(&self.path, value)
+    }
+}
+
+/// The firmware versions which have a property.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Availability {
+    v0_4: bool,
+    v0_5: bool,
+}
+
+impl Availability {
+    pub const ALL: Self = Self {
+        v0_4: true,
+        v0_5: true,
+    };
+    pub const V0_4: Self = Self {
+        v0_4: true,
+        v0_5: false,
+    };
+    pub const V0_5: Self = Self {
+        v0_4: false,
+        v0_5: true,
+    };
+
+    /// Whether the firmware of `capabilities` has the property.
+    pub fn includes(&self, capabilities: &Capabilities) -> bool {
+        if capabilities.firmware() >= FirmwareVersion::new(0, 5, 0) {
+            self.v0_5
+        } else {
+            self.v0_4
+        }
+    }
+
+    /// The firmware versions which have both a property and the object it belongs to.
+    fn and(self, other: Self) -> Self {
+        Self {
+            v0_4: self.v0_4 && other.v0_4,
+            v0_5: self.v0_5 && other.v0_5,
+        }
+    }
+}
+
+/// Describes a property of the tree, as listed by `all`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PropertyInfo {
+    pub path: String,
+    pub value_type: ValueType,
+    pub writable: bool,
+    pub availability: Availability,
+}
+
+/// Appends the name of a child to the path of an object.
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// The root of the property tree, which is not bound to a connection.
+pub fn root() -> Root<()> {
+    Root {
+        handle: (),
+        path: String::new(),
+    }
+}
+
+/// Lists every property of the tree, of any firmware, for both axes.
+pub fn all() -> Vec<PropertyInfo> {
+    let mut properties = Vec::new();
+    Root::<()>::collect("", Availability::ALL, &mut properties);
+    properties
+}
+
+/// Lists the properties of the firmware of `capabilities`, with their paths in that firmware.
+pub fn for_firmware(capabilities: &Capabilities) -> Vec<PropertyInfo> {
+    all()
+        .into_iter()
+        .filter(|info| info.availability.includes(capabilities))
+        .collect()
+}
+
+impl<H> Root<H> {
+    pub fn axis(self, axis: AxisID) -> Axis<H> {
+        match axis {
+            AxisID::Zero => self.axis0(),
+            AxisID::One => self.axis1(),
+        }
+    }
+}
+
+/// Typed property paths.
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// The root of the property tree, bound to this connection.
+    pub fn properties(&mut self) -> Root<&mut Self> {
+        Root {
+            handle: self,
+            path: String::new(),
+        }
+    }
+
+    /// The properties of an axis, bound to this connection.
+    pub fn axis(&mut self, axis: AxisID) -> Axis<&mut Self> {
+        self.properties().axis(axis)
+    }
+}
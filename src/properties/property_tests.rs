@@ -0,0 +1,244 @@
+use super::*;
+use crate::enumerations::errors::{AxisError, AxisErrors, ODriveError};
+use crate::test_stream::MockStream;
+
+fn init_odrive() -> ODrive<MockStream> {
+    ODrive::new(MockStream::new())
+}
+
+#[test]
+fn test_paths() {
+    assert_eq!("vbus_voltage", root().vbus_voltage().path());
+    assert_eq!("config.enable_uart", root().config().enable_uart().path());
+    assert_eq!("axis1", root().axis(AxisID::One).path());
+    assert_eq!(
+        "axis0.motor.current_control.Iq_measured",
+        root()
+            .axis(AxisID::Zero)
+            .motor()
+            .current_control()
+            .Iq_measured()
+            .path()
+    );
+    assert_eq!(
+        "axis1.trap_traj.config.A_per_css",
+        root().axis1().trap_traj().config().A_per_css().path()
+    );
+}
+
+#[test]
+fn test_set() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().push_response(b"");
+    odrive.get_mut().push_response(b"20.000000\n");
+    odrive
+        .axis(AxisID::Zero)
+        .motor()
+        .config()
+        .current_lim()
+        .set(20.0)
+        .unwrap();
+    assert_eq!(
+        b"w axis0.motor.config.current_lim 20\nr axis0.motor.config.current_lim\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+    assert!(odrive.get_mut().flushed);
+}
+
+#[test]
+fn test_set_rejected() {
+    let mut odrive = init_odrive();
+    odrive.set_capabilities(Capabilities::new(FirmwareVersion::new(0, 5, 6), None));
+    odrive.get_mut().push_response(b"invalid property\n");
+    odrive.get_mut().push_response(b"invalid property\n");
+    match odrive
+        .axis(AxisID::Zero)
+        .controller()
+        .config()
+        .setpoints_in_cpr()
+        .set(true)
+    {
+        Err(ODriveError::InvalidProperty { path, .. }) => {
+            assert_eq!("axis0.controller.config.setpoints_in_cpr", path)
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    // Both replies have been consumed
+    assert!(odrive.get_mut().read_buffer.is_empty());
+}
+
+#[test]
+fn test_enumerations_follow_the_firmware() {
+    let mut odrive = init_odrive();
+    odrive.set_capabilities(Capabilities::new(FirmwareVersion::new(0, 5, 6), None));
+    odrive.get_mut().push_response(b"");
+    odrive.get_mut().push_response(b"3\n");
+    odrive
+        .axis(AxisID::Zero)
+        .controller()
+        .config()
+        .control_mode()
+        .set(ControlMode::TrajectoryControl)
+        .unwrap();
+    assert_eq!(
+        b"w axis0.controller.config.control_mode 3\nr axis0.controller.config.control_mode\n"
+            .to_vec(),
+        odrive.get_mut().write_buffer
+    );
+
+    match odrive
+        .axis(AxisID::Zero)
+        .motor()
+        .config()
+        .motor_type()
+        .set(MotorType::LowCurrent)
+    {
+        Err(ODriveError::Unsupported(_)) => (),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    odrive.get_mut().read_buffer = b"\n4".to_vec();
+    assert!(odrive
+        .axis(AxisID::Zero)
+        .controller()
+        .config()
+        .control_mode()
+        .get()
+        .is_err());
+}
+
+#[test]
+fn test_set_values() {
+    let mut odrive = init_odrive();
+    for response in [b"" as &[u8], b"1\n", b"", b"8\n", b"", b"0\n"].iter() {
+        odrive.get_mut().push_response(response);
+    }
+    odrive
+        .axis(AxisID::One)
+        .config()
+        .enable_watchdog()
+        .set(true)
+        .unwrap();
+    odrive
+        .axis(AxisID::One)
+        .requested_state()
+        .set(AxisState::ClosedLoopControl)
+        .unwrap();
+    odrive
+        .axis(AxisID::One)
+        .error()
+        .set(AxisErrors::empty())
+        .unwrap();
+    assert_eq!(
+        b"w axis1.config.enable_watchdog 1\nr axis1.config.enable_watchdog\n\
+w axis1.requested_state 8\nr axis1.requested_state\nw axis1.error 0\nr axis1.error\n"
+            .to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_get() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().read_buffer = b"\n24.5".to_vec();
+    let current_lim = odrive
+        .axis(AxisID::Zero)
+        .motor()
+        .config()
+        .current_lim()
+        .get()
+        .unwrap();
+    assert_eq!(5.42, current_lim);
+    assert_eq!(
+        b"r axis0.motor.config.current_lim\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_get_values() {
+    let mut odrive = init_odrive();
+    odrive.get_mut().read_buffer = b"\n1".to_vec();
+    assert!(odrive
+        .axis(AxisID::Zero)
+        .encoder()
+        .is_ready()
+        .get()
+        .unwrap());
+
+    odrive.get_mut().read_buffer = b"\n8".to_vec();
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        odrive.axis(AxisID::Zero).current_state().get().unwrap()
+    );
+
+    odrive.get_mut().read_buffer = b"\n2".to_vec();
+    let errors = odrive.axis(AxisID::Zero).error().get().unwrap();
+    assert!(errors.contains(AxisError::ErrorDcBusUnderVoltage));
+
+    odrive.get_mut().read_buffer = b"\n0.31".to_vec();
+    assert!(odrive.properties().serial_number().get().is_err());
+}
+
+#[test]
+fn test_all() {
+    let all = all();
+    let current_lim = all
+        .iter()
+        .find(|info| info.path == "axis1.motor.config.current_lim")
+        .unwrap();
+    assert_eq!(ValueType::Float, current_lim.value_type);
+    assert!(current_lim.writable);
+
+    let current_state = all
+        .iter()
+        .find(|info| info.path == "axis0.current_state")
+        .unwrap();
+    assert_eq!(
+        ValueType::Enumeration("AxisState"),
+        current_state.value_type
+    );
+    assert!(!current_state.writable);
+
+    let axis0 = all.iter().filter(|info| info.path.starts_with("axis0."));
+    let axis1 = all.iter().filter(|info| info.path.starts_with("axis1."));
+    assert_eq!(axis0.count(), axis1.count());
+}
+
+#[test]
+fn test_for_firmware() {
+    let has =
+        |properties: &[PropertyInfo], path: &str| properties.iter().any(|info| info.path == path);
+    let firmware_0_4 = for_firmware(&Capabilities::default());
+    let firmware_0_5 = for_firmware(&Capabilities::new(FirmwareVersion::new(0, 5, 6), None));
+
+    for path in [
+        "axis0.controller.config.setpoints_in_cpr",
+        "axis0.controller.vel_ramp_enable",
+        "axis0.trap_traj.config.A_per_css",
+    ]
+    .iter()
+    {
+        assert!(has(&firmware_0_4, path), "{} is missing from 0.4", path);
+        assert!(!has(&firmware_0_5, path), "{} is listed for 0.5", path);
+    }
+    for path in [
+        "axis0.controller.config.input_mode",
+        "axis0.controller.input_pos",
+        "axis0.controller.config.inertia",
+        "axis1.min_endstop.config.gpio_num",
+    ]
+    .iter()
+    {
+        assert!(!has(&firmware_0_4, path), "{} is listed for 0.4", path);
+        assert!(has(&firmware_0_5, path), "{} is missing from 0.5", path);
+    }
+    for path in [
+        "axis0.config.calibration_lockin.current",
+        "axis1.motor.config.current_lim",
+    ]
+    .iter()
+    {
+        assert!(has(&firmware_0_4, path) && has(&firmware_0_5, path));
+    }
+}
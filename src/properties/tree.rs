@@ -0,0 +1,629 @@
+//! The property trees of firmware 0.4.12 and 0.5.6, merged into one.
+//!
+//! `scripts/generate_property_tree.py` writes this file from the JSON definition of 0.4.12 and
+//! `Firmware/odrive-interface.yaml` of 0.5.6. The tree below predates the script and was written by
+//! hand from those definitions; it has not been regenerated yet. Change the script, not this file.
+//!
+//! Properties renamed in 0.5.0 are listed under their name in each firmware, and
+//! `Capabilities::property_path` translates either name for the connected board. The read-only
+//! `controller.pos_setpoint` and `controller.vel_setpoint` of 0.5.x are not listed, because their
+//! paths are translated to `controller.input_pos` and `controller.input_vel`.
+
+use super::*;
+use crate::enumerations::errors::{AxisErrors, ControllerErrors, EncoderErrors, MotorErrors};
+
+property_tree! {
+    /// The root of the ODrive object tree.
+    Root {
+        objects: [
+            config: Config,
+            system_stats: SystemStats,
+            can: Can,
+            axis0: Axis,
+            axis1: Axis,
+        ],
+        properties: [
+            error: u8 = rw,
+            vbus_voltage: f32 = ro,
+            ibus: f32 = ro,
+            ibus_report_filter_k: f32 = rw @ v0_5,
+            serial_number: u64 = ro,
+            hw_version_major: u8 = ro,
+            hw_version_minor: u8 = ro,
+            hw_version_variant: u8 = ro,
+            fw_version_major: u8 = ro,
+            fw_version_minor: u8 = ro,
+            fw_version_revision: u8 = ro,
+            fw_version_unreleased: u8 = ro,
+            brake_resistor_armed: bool = ro,
+            brake_resistor_saturated: bool = ro,
+            brake_resistor_current: f32 = ro @ v0_5,
+            n_evt_sampling: u32 = ro @ v0_5,
+            n_evt_control_loop: u32 = ro @ v0_5,
+            task_timers_armed: bool = rw @ v0_5,
+            user_config_loaded: bool = ro,
+            misconfigured: bool = ro @ v0_5,
+            otp_valid: bool = ro @ v0_5,
+            test_property: u32 = rw,
+        ],
+    }
+
+    /// The board configuration, stored by `save_configuration`.
+    Config {
+        objects: [
+            gpio1_pwm_mapping: Mapping,
+            gpio2_pwm_mapping: Mapping,
+            gpio3_pwm_mapping: Mapping,
+            gpio4_pwm_mapping: Mapping,
+            gpio3_analog_mapping: Mapping,
+            gpio4_analog_mapping: Mapping,
+        ],
+        properties: [
+            brake_resistance: f32 = rw,
+            enable_brake_resistor: bool = rw @ v0_5,
+            enable_uart: bool = rw @ v0_4,
+            uart_baudrate: u32 = rw @ v0_4,
+            enable_uart_a: bool = rw @ v0_5,
+            enable_uart_b: bool = rw @ v0_5,
+            enable_uart_c: bool = rw @ v0_5,
+            uart_a_baudrate: u32 = rw @ v0_5,
+            uart_b_baudrate: u32 = rw @ v0_5,
+            uart_c_baudrate: u32 = rw @ v0_5,
+            uart0_protocol: u8 = rw @ v0_5,
+            uart1_protocol: u8 = rw @ v0_5,
+            uart2_protocol: u8 = rw @ v0_5,
+            usb_cdc_protocol: u8 = rw @ v0_5,
+            enable_i2c_instead_of_can: bool = rw @ v0_4,
+            enable_can_a: bool = rw @ v0_5,
+            enable_i2c_a: bool = rw @ v0_5,
+            enable_ascii_protocol_on_usb: bool = rw @ v0_4,
+            max_regen_current: f32 = rw,
+            dc_bus_undervoltage_trip_level: f32 = rw,
+            dc_bus_overvoltage_trip_level: f32 = rw,
+            enable_dc_bus_overvoltage_ramp: bool = rw,
+            dc_bus_overvoltage_ramp_start: f32 = rw,
+            dc_bus_overvoltage_ramp_end: f32 = rw,
+            dc_max_positive_current: f32 = rw,
+            dc_max_negative_current: f32 = rw,
+            error_gpio_pin: u32 = rw @ v0_5,
+            gpio1_mode: u8 = rw @ v0_5,
+            gpio2_mode: u8 = rw @ v0_5,
+            gpio3_mode: u8 = rw @ v0_5,
+            gpio4_mode: u8 = rw @ v0_5,
+            gpio5_mode: u8 = rw @ v0_5,
+            gpio6_mode: u8 = rw @ v0_5,
+            gpio7_mode: u8 = rw @ v0_5,
+            gpio8_mode: u8 = rw @ v0_5,
+            gpio9_mode: u8 = rw @ v0_5,
+            gpio10_mode: u8 = rw @ v0_5,
+            gpio11_mode: u8 = rw @ v0_5,
+            gpio12_mode: u8 = rw @ v0_5,
+            gpio13_mode: u8 = rw @ v0_5,
+            gpio14_mode: u8 = rw @ v0_5,
+            gpio15_mode: u8 = rw @ v0_5,
+            gpio16_mode: u8 = rw @ v0_5,
+        ],
+    }
+
+    /// The range a PWM or analog input is mapped to. The endpoint the input drives is a reference,
+    /// which cannot be read or written over the ASCII protocol.
+    Mapping {
+        objects: [],
+        properties: [
+            min: f32 = rw,
+            max: f32 = rw,
+        ],
+    }
+
+    SystemStats {
+        objects: [usb: UsbStats, i2c: I2cStats],
+        properties: [
+            uptime: u32 = ro,
+            min_heap_space: u32 = ro,
+            max_stack_usage_axis: u32 = ro @ v0_5,
+            max_stack_usage_usb: u32 = ro @ v0_5,
+            max_stack_usage_uart: u32 = ro @ v0_5,
+            max_stack_usage_startup: u32 = ro @ v0_5,
+            max_stack_usage_can: u32 = ro @ v0_5,
+            min_stack_space_axis0: u32 = ro @ v0_4,
+            min_stack_space_axis1: u32 = ro @ v0_4,
+            min_stack_space_comms: u32 = ro @ v0_4,
+            min_stack_space_usb: u32 = ro @ v0_4,
+            min_stack_space_uart: u32 = ro @ v0_4,
+            min_stack_space_usb_irq: u32 = ro @ v0_4,
+            min_stack_space_startup: u32 = ro @ v0_4,
+            min_stack_space_can: u32 = ro @ v0_4,
+            stack_size_axis: u32 = ro @ v0_5,
+            stack_size_usb: u32 = ro @ v0_5,
+            stack_size_uart: u32 = ro @ v0_5,
+            stack_size_startup: u32 = ro @ v0_5,
+            stack_size_can: u32 = ro @ v0_5,
+            prio_axis: i32 = ro @ v0_5,
+            prio_usb: i32 = ro @ v0_5,
+            prio_uart: i32 = ro @ v0_5,
+            prio_startup: i32 = ro @ v0_5,
+            prio_can: i32 = ro @ v0_5,
+        ],
+    }
+
+    UsbStats {
+        objects: [],
+        properties: [
+            rx_cnt: u32 = ro,
+            tx_cnt: u32 = ro,
+            tx_overrun_cnt: u32 = ro,
+        ],
+    }
+
+    I2cStats {
+        objects: [],
+        properties: [
+            addr: u8 = ro,
+            addr_match_cnt: u32 = ro,
+            rx_cnt: u32 = ro,
+            error_cnt: u32 = ro,
+        ],
+    }
+
+    Can {
+        objects: [config: CanConfig],
+        properties: [
+            error: u8 = ro,
+        ],
+    }
+
+    CanConfig {
+        objects: [],
+        properties: [
+            baud_rate: u32 = rw,
+            protocol: u8 = rw,
+            r120_gpio_num: u32 = rw @ v0_5,
+            enable_r120: bool = rw @ v0_5,
+        ],
+    }
+
+    /// One of the two axes.
+    Axis {
+        objects: [
+            config: AxisConfig,
+            motor: Motor,
+            encoder: Encoder,
+            sensorless_estimator: SensorlessEstimator,
+            controller: Controller,
+            trap_traj: TrapTraj,
+            min_endstop: Endstop @ v0_5,
+            max_endstop: Endstop @ v0_5,
+            mechanical_brake: MechanicalBrake @ v0_5,
+        ],
+        properties: [
+            error: AxisErrors = rw,
+            step_dir_active: bool = ro,
+            last_drv_fault: u32 = ro @ v0_5,
+            steps: i64 = ro @ v0_5,
+            current_state: AxisState = ro,
+            requested_state: AxisState = rw,
+            is_homed: bool = rw @ v0_5,
+            loop_counter: u32 = ro @ v0_4,
+            lockin_state: u8 = ro @ v0_4,
+        ],
+    }
+
+    AxisConfig {
+        objects: [
+            calibration_lockin: CalibrationLockin,
+            sensorless_ramp: LockinConfig,
+            general_lockin: LockinConfig,
+            can: AxisCanConfig @ v0_5,
+        ],
+        properties: [
+            startup_motor_calibration: bool = rw,
+            startup_encoder_index_search: bool = rw,
+            startup_encoder_offset_calibration: bool = rw,
+            startup_closed_loop_control: bool = rw,
+            startup_sensorless_control: bool = rw,
+            startup_homing: bool = rw @ v0_5,
+            enable_step_dir: bool = rw,
+            step_dir_always_on: bool = rw,
+            enable_sensorless_mode: bool = rw @ v0_5,
+            counts_per_step: f32 = rw @ v0_4,
+            turns_per_step: f32 = rw @ v0_5,
+            watchdog_timeout: f32 = rw,
+            enable_watchdog: bool = rw,
+            step_gpio_pin: u16 = rw,
+            dir_gpio_pin: u16 = rw,
+            can_node_id: u32 = rw @ v0_4,
+            can_node_id_extended: bool = rw @ v0_4,
+            can_heartbeat_rate_ms: u32 = rw @ v0_4,
+        ],
+    }
+
+    /// The open loop spin of the encoder offset calibration.
+    CalibrationLockin {
+        objects: [],
+        properties: [
+            current: f32 = rw,
+            ramp_time: f32 = rw,
+            ramp_distance: f32 = rw,
+            accel: f32 = rw,
+            vel: f32 = rw,
+        ],
+    }
+
+    /// An open loop spin, as used by the index search and the sensorless ramp.
+    LockinConfig {
+        objects: [],
+        properties: [
+            current: f32 = rw,
+            ramp_time: f32 = rw,
+            ramp_distance: f32 = rw,
+            accel: f32 = rw,
+            vel: f32 = rw,
+            finish_distance: f32 = rw,
+            finish_on_vel: bool = rw,
+            finish_on_distance: bool = rw,
+            finish_on_enc_idx: bool = rw,
+        ],
+    }
+
+    /// The CAN Simple settings of an axis, which replace `can_node_id` and its siblings in 0.5.x.
+    AxisCanConfig {
+        objects: [],
+        properties: [
+            node_id: u32 = rw,
+            is_extended: bool = rw,
+            heartbeat_rate_ms: u32 = rw,
+            encoder_rate_ms: u32 = rw,
+            motor_error_rate_ms: u32 = rw,
+            encoder_error_rate_ms: u32 = rw,
+            controller_error_rate_ms: u32 = rw,
+            sensorless_error_rate_ms: u32 = rw,
+            encoder_count_rate_ms: u32 = rw,
+            iq_rate_ms: u32 = rw,
+            sensorless_rate_ms: u32 = rw,
+            bus_vi_rate_ms: u32 = rw,
+        ],
+    }
+
+    Motor {
+        objects: [
+            current_control: CurrentControl,
+            gate_driver: GateDriver @ v0_4,
+            fet_thermistor: FetThermistor @ v0_5,
+            motor_thermistor: MotorThermistor @ v0_5,
+            config: MotorConfig,
+        ],
+        properties: [
+            error: MotorErrors = rw,
+            last_error_time: f32 = ro @ v0_5,
+            armed_state: u8 = ro @ v0_4,
+            is_armed: bool = ro @ v0_5,
+            is_calibrated: bool = ro,
+            current_meas_phB: f32 = ro @ v0_4,
+            current_meas_phC: f32 = ro @ v0_4,
+            DC_calib_phB: f32 = rw @ v0_4,
+            DC_calib_phC: f32 = rw @ v0_4,
+            current_meas_ph_a: f32 = ro @ v0_5,
+            current_meas_ph_b: f32 = ro @ v0_5,
+            current_meas_ph_c: f32 = ro @ v0_5,
+            DC_calib_ph_a: f32 = rw @ v0_5,
+            DC_calib_ph_b: f32 = rw @ v0_5,
+            DC_calib_ph_c: f32 = rw @ v0_5,
+            I_bus: f32 = ro @ v0_5,
+            phase_current_rev_gain: f32 = rw,
+            effective_current_lim: f32 = ro,
+            max_allowed_current: f32 = ro @ v0_5,
+            max_dc_calib: f32 = ro @ v0_5,
+            n_evt_current_measurement: u32 = ro @ v0_5,
+            n_evt_pwm_update: u32 = ro @ v0_5,
+        ],
+    }
+
+    CurrentControl {
+        objects: [],
+        properties: [
+            p_gain: f32 = rw,
+            i_gain: f32 = rw,
+            v_current_control_integral_d: f32 = rw,
+            v_current_control_integral_q: f32 = rw,
+            Ibus: f32 = ro @ v0_4,
+            final_v_alpha: f32 = ro,
+            final_v_beta: f32 = ro,
+            Id_setpoint: f32 = ro,
+            Iq_setpoint: f32 = ro,
+            Vd_setpoint: f32 = ro @ v0_5,
+            Vq_setpoint: f32 = ro @ v0_5,
+            phase: f32 = ro @ v0_5,
+            phase_vel: f32 = ro @ v0_5,
+            Ialpha_measured: f32 = ro @ v0_5,
+            Ibeta_measured: f32 = ro @ v0_5,
+            Iq_measured: f32 = ro,
+            Id_measured: f32 = ro,
+            power: f32 = ro @ v0_5,
+            I_measured_report_filter_k: f32 = rw,
+            max_allowed_current: f32 = ro @ v0_4,
+            overcurrent_trip_level: f32 = ro @ v0_4,
+        ],
+    }
+
+    GateDriver {
+        objects: [],
+        properties: [
+            drv_fault: u16 = ro,
+        ],
+    }
+
+    FetThermistor {
+        objects: [config: FetThermistorConfig],
+        properties: [
+            temperature: f32 = ro,
+        ],
+    }
+
+    FetThermistorConfig {
+        objects: [],
+        properties: [
+            enabled: bool = rw,
+            temp_limit_lower: f32 = rw,
+            temp_limit_upper: f32 = rw,
+        ],
+    }
+
+    MotorThermistor {
+        objects: [config: MotorThermistorConfig],
+        properties: [
+            temperature: f32 = ro,
+        ],
+    }
+
+    MotorThermistorConfig {
+        objects: [],
+        properties: [
+            gpio_pin: u16 = rw,
+            poly_coefficient_0: f32 = rw,
+            poly_coefficient_1: f32 = rw,
+            poly_coefficient_2: f32 = rw,
+            poly_coefficient_3: f32 = rw,
+            temp_limit_lower: f32 = rw,
+            temp_limit_upper: f32 = rw,
+            enabled: bool = rw,
+        ],
+    }
+
+    MotorConfig {
+        objects: [],
+        properties: [
+            pre_calibrated: bool = rw,
+            pole_pairs: u16 = rw,
+            calibration_current: f32 = rw,
+            resistance_calib_max_voltage: f32 = rw,
+            phase_inductance: f32 = rw,
+            phase_resistance: f32 = rw,
+            torque_constant: f32 = rw @ v0_5,
+            direction: i32 = rw @ v0_4,
+            motor_type: MotorType = rw,
+            current_lim: f32 = rw,
+            current_lim_tolerance: f32 = rw @ v0_4,
+            current_lim_margin: f32 = rw @ v0_5,
+            torque_lim: f32 = rw @ v0_5,
+            inverter_temp_limit_lower: f32 = rw @ v0_4,
+            inverter_temp_limit_upper: f32 = rw @ v0_4,
+            requested_current_range: f32 = rw,
+            current_control_bandwidth: f32 = rw,
+            acim_gain_min_flux: f32 = rw @ v0_5,
+            acim_autoflux_min_Id: f32 = rw @ v0_5,
+            acim_autoflux_enable: bool = rw @ v0_5,
+            acim_autoflux_attack_gain: f32 = rw @ v0_5,
+            acim_autoflux_decay_gain: f32 = rw @ v0_5,
+            R_wL_FF_enable: bool = rw @ v0_5,
+            bEMF_FF_enable: bool = rw @ v0_5,
+            I_bus_hard_min: f32 = rw @ v0_5,
+            I_bus_hard_max: f32 = rw @ v0_5,
+            I_leak_max: f32 = rw @ v0_5,
+            dc_calib_tau: f32 = rw @ v0_5,
+        ],
+    }
+
+    Encoder {
+        objects: [config: EncoderConfig],
+        properties: [
+            error: EncoderErrors = rw,
+            is_ready: bool = ro,
+            index_found: bool = ro,
+            shadow_count: i32 = ro,
+            count_in_cpr: i32 = ro,
+            interpolation: f32 = ro,
+            phase: f32 = ro,
+            pos_estimate: f32 = ro,
+            pos_estimate_counts: f32 = ro @ v0_5,
+            pos_cpr: f32 = ro @ v0_4,
+            pos_circular: f32 = ro @ v0_5,
+            pos_cpr_counts: f32 = ro @ v0_5,
+            delta_pos_cpr_counts: f32 = ro @ v0_5,
+            hall_state: u8 = ro,
+            vel_estimate: f32 = ro,
+            vel_estimate_counts: f32 = ro @ v0_5,
+            calib_scan_response: f32 = ro,
+            pos_abs: i32 = rw,
+            spi_error_rate: f32 = ro,
+        ],
+    }
+
+    EncoderConfig {
+        objects: [],
+        properties: [
+            mode: EncoderMode = rw,
+            use_index: bool = rw,
+            index_offset: f32 = rw @ v0_5,
+            use_index_offset: bool = rw @ v0_5,
+            find_idx_on_lockin_only: bool = rw,
+            abs_spi_cs_gpio_pin: u16 = rw,
+            pre_calibrated: bool = rw,
+            zero_count_on_find_idx: bool = rw,
+            cpr: i32 = rw,
+            offset: i32 = rw @ v0_4,
+            offset_float: f32 = rw @ v0_4,
+            phase_offset: i32 = rw @ v0_5,
+            phase_offset_float: f32 = rw @ v0_5,
+            direction: i32 = rw @ v0_5,
+            enable_phase_interpolation: bool = rw,
+            bandwidth: f32 = rw,
+            calib_range: f32 = rw,
+            calib_scan_distance: f32 = rw,
+            calib_scan_omega: f32 = rw,
+            idx_search_unidirectional: bool = rw,
+            ignore_illegal_hall_state: bool = rw,
+            sincos_gpio_pin_sin: u16 = rw,
+            sincos_gpio_pin_cos: u16 = rw,
+            hall_polarity: u8 = rw @ v0_5,
+            hall_polarity_calibrated: bool = rw @ v0_5,
+        ],
+    }
+
+    SensorlessEstimator {
+        objects: [config: SensorlessEstimatorConfig],
+        properties: [
+            error: u8 = rw,
+            phase: f32 = ro,
+            pll_pos: f32 = ro,
+            phase_vel: f32 = ro @ v0_5,
+            vel_estimate: f32 = ro,
+        ],
+    }
+
+    SensorlessEstimatorConfig {
+        objects: [],
+        properties: [
+            observer_gain: f32 = rw,
+            pll_bandwidth: f32 = rw,
+            pm_flux_linkage: f32 = rw,
+        ],
+    }
+
+    Controller {
+        objects: [config: ControllerConfig, autotuning: Autotuning @ v0_5],
+        properties: [
+            error: ControllerErrors = rw,
+            pos_setpoint: f32 = rw @ v0_4,
+            vel_setpoint: f32 = rw @ v0_4,
+            current_setpoint: f32 = rw @ v0_4,
+            vel_integrator_current: f32 = rw @ v0_4,
+            vel_ramp_target: f32 = rw @ v0_4,
+            vel_ramp_enable: bool = rw @ v0_4,
+            input_pos: f32 = rw @ v0_5,
+            input_vel: f32 = rw @ v0_5,
+            input_torque: f32 = rw @ v0_5,
+            torque_setpoint: f32 = ro @ v0_5,
+            vel_integrator_torque: f32 = rw @ v0_5,
+            trajectory_done: bool = ro,
+            anticogging_valid: bool = ro @ v0_5,
+            autotuning_phase: f32 = rw @ v0_5,
+            mechanical_power: f32 = ro @ v0_5,
+            electrical_power: f32 = ro @ v0_5,
+        ],
+    }
+
+    ControllerConfig {
+        objects: [anticogging: Anticogging],
+        properties: [
+            control_mode: ControlMode = rw,
+            input_mode: InputMode = rw @ v0_5,
+            pos_gain: f32 = rw,
+            vel_gain: f32 = rw,
+            vel_integrator_gain: f32 = rw,
+            vel_integrator_limit: f32 = rw @ v0_5,
+            vel_limit: f32 = rw,
+            vel_limit_tolerance: f32 = rw,
+            vel_ramp_rate: f32 = rw,
+            torque_ramp_rate: f32 = rw @ v0_5,
+            setpoints_in_cpr: bool = rw @ v0_4,
+            circular_setpoints: bool = rw @ v0_5,
+            circular_setpoint_range: f32 = rw @ v0_5,
+            steps_per_circular_range: i32 = rw @ v0_5,
+            homing_speed: f32 = rw @ v0_5,
+            inertia: f32 = rw @ v0_5,
+            axis_to_mirror: u8 = rw @ v0_5,
+            mirror_ratio: f32 = rw @ v0_5,
+            torque_mirror_ratio: f32 = rw @ v0_5,
+            load_encoder_axis: u8 = rw @ v0_5,
+            input_filter_bandwidth: f32 = rw @ v0_5,
+            enable_gain_scheduling: bool = rw,
+            gain_scheduling_width: f32 = rw,
+            enable_vel_limit: bool = rw @ v0_5,
+            enable_torque_mode_vel_limit: bool = rw @ v0_5,
+            enable_overspeed_error: bool = rw @ v0_5,
+            mechanical_power_bandwidth: f32 = rw @ v0_5,
+            electrical_power_bandwidth: f32 = rw @ v0_5,
+            spinout_mechanical_power_threshold: f32 = rw @ v0_5,
+            spinout_electrical_power_threshold: f32 = rw @ v0_5,
+        ],
+    }
+
+    Anticogging {
+        objects: [],
+        properties: [
+            index: u32 = rw,
+            pre_calibrated: bool = rw,
+            calib_anticogging: bool = rw,
+            calib_pos_threshold: f32 = rw,
+            calib_vel_threshold: f32 = rw,
+            cogging_ratio: f32 = ro,
+            anticogging_enabled: bool = rw,
+        ],
+    }
+
+    Autotuning {
+        objects: [],
+        properties: [
+            frequency: f32 = rw,
+            pos_amplitude: f32 = rw,
+            vel_amplitude: f32 = rw,
+            torque_amplitude: f32 = rw,
+        ],
+    }
+
+    TrapTraj {
+        objects: [config: TrapTrajConfig],
+        properties: [],
+    }
+
+    TrapTrajConfig {
+        objects: [],
+        properties: [
+            vel_limit: f32 = rw,
+            accel_limit: f32 = rw,
+            decel_limit: f32 = rw,
+            A_per_css: f32 = rw @ v0_4,
+        ],
+    }
+
+    Endstop {
+        objects: [config: EndstopConfig],
+        properties: [
+            endstop_state: bool = ro,
+        ],
+    }
+
+    EndstopConfig {
+        objects: [],
+        properties: [
+            gpio_num: u16 = rw,
+            enabled: bool = rw,
+            offset: f32 = rw,
+            debounce_ms: u32 = rw,
+            is_active_high: bool = rw,
+        ],
+    }
+
+    MechanicalBrake {
+        objects: [config: MechanicalBrakeConfig],
+        properties: [],
+    }
+
+    MechanicalBrakeConfig {
+        objects: [],
+        properties: [
+            gpio_num: u16 = rw,
+            is_active_low: bool = rw,
+        ],
+    }
+}
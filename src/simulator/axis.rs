@@ -0,0 +1,388 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use crate::enumerations::errors::AxisError;
+use crate::enumerations::{AxisState, ControlMode};
+use crate::simulator::properties::{Properties, Value};
+
+/// The time it takes to measure the phase resistance and inductance.
+const MOTOR_CALIBRATION_TIME: Duration = Duration::from_millis(100);
+/// The time it takes to turn the motor back and forth during the encoder offset calibration.
+const ENCODER_OFFSET_CALIBRATION_TIME: Duration = Duration::from_millis(200);
+/// The time it takes to find the encoder index.
+const ENCODER_INDEX_SEARCH_TIME: Duration = Duration::from_millis(100);
+
+/// The distance from the trajectory target at which a trajectory is complete, in counts.
+const TRAJECTORY_TOLERANCE: f32 = 1.0;
+
+/// The acceleration caused by one amp of motor current, in counts/s².
+const ACCELERATION_PER_AMP: f32 = 10_000.0;
+/// The time constant with which the motor follows the velocity setpoint, in seconds.
+const VELOCITY_TIME_CONSTANT: f32 = 0.02;
+/// The time constant with which an unpowered motor coasts to a stop, in seconds.
+const COAST_TIME_CONSTANT: f32 = 0.1;
+
+/// The state machine and motor model of one simulated axis.
+#[derive(Debug, Clone)]
+pub(crate) struct AxisModel {
+    index: u8,
+    /// The time spent in the current state.
+    state_time: Duration,
+    /// The states to run once the current one completes.
+    sequence: VecDeque<AxisState>,
+    /// The target of the `t` command, if a trajectory is being executed.
+    trajectory_target: Option<f32>,
+    /// The time since the watchdog was last fed.
+    watchdog_time: Duration,
+}
+
+impl AxisModel {
+    pub fn new(index: u8) -> Self {
+        Self {
+            index,
+            state_time: Duration::from_secs(0),
+            sequence: VecDeque::new(),
+            trajectory_target: None,
+            watchdog_time: Duration::from_secs(0),
+        }
+    }
+
+    fn path(&self, name: &str) -> String {
+        format!("axis{}.{}", self.index, name)
+    }
+
+    fn current_state(&self, properties: &Properties) -> AxisState {
+        AxisState::try_from(properties.int(&self.path("current_state")) as i32)
+            .unwrap_or(AxisState::Undefined)
+    }
+
+    fn set_state(&mut self, properties: &mut Properties, state: AxisState) {
+        properties.set(&self.path("current_state"), Value::Int(state as i64));
+        self.state_time = Duration::from_secs(0);
+        // Like the firmware, arming an axis feeds the watchdog
+        self.feed_watchdog();
+    }
+
+    fn raise(&mut self, properties: &mut Properties, error: AxisError) {
+        let path = self.path("error");
+        let errors = properties.int(&path) | error as i64;
+        properties.set(&path, Value::Int(errors));
+    }
+
+    fn has_errors(&self, properties: &Properties) -> bool {
+        ["error", "motor.error", "encoder.error", "controller.error"]
+            .iter()
+            .any(|name| properties.int(&self.path(name)) != 0)
+    }
+
+    /// Resets the dynamic state after a reboot and applies the pre-calibration settings.
+    pub fn reboot(&mut self, properties: &mut Properties) {
+        *self = Self::new(self.index);
+        if properties.bool(&self.path("motor.config.pre_calibrated")) {
+            properties.set(&self.path("motor.is_calibrated"), Value::Bool(true));
+        }
+        if properties.bool(&self.path("encoder.config.pre_calibrated"))
+            && !properties.bool(&self.path("encoder.config.use_index"))
+        {
+            properties.set(&self.path("encoder.is_ready"), Value::Bool(true));
+        }
+        if self.startup_enabled(properties) {
+            properties.set(
+                &self.path("requested_state"),
+                Value::Int(AxisState::StartupSequence as i64),
+            );
+        }
+    }
+
+    fn startup_enabled(&self, properties: &Properties) -> bool {
+        [
+            "config.startup_motor_calibration",
+            "config.startup_encoder_index_search",
+            "config.startup_encoder_offset_calibration",
+            "config.startup_closed_loop_control",
+            "config.startup_sensorless_control",
+        ]
+        .iter()
+        .any(|name| properties.bool(&self.path(name)))
+    }
+
+    /// Called when the `t` command starts a new trajectory.
+    pub fn start_trajectory(&mut self, properties: &mut Properties, target: f32) {
+        self.trajectory_target = Some(target);
+        properties.set(&self.path("controller.trajectory_done"), Value::Bool(false));
+    }
+
+    fn end_trajectory(&mut self, properties: &mut Properties) {
+        self.trajectory_target = None;
+        properties.set(&self.path("controller.trajectory_done"), Value::Bool(true));
+    }
+
+    /// Called when the `u` command feeds the watchdog.
+    pub fn feed_watchdog(&mut self) {
+        self.watchdog_time = Duration::from_secs(0);
+    }
+
+    fn watchdog_expired(&self, properties: &Properties) -> bool {
+        let timeout = properties.float(&self.path("config.watchdog_timeout"));
+        properties.bool(&self.path("config.enable_watchdog"))
+            && timeout > 0.0
+            && self.watchdog_time.as_secs_f32() > timeout
+    }
+
+    /// Advances the simulation of this axis by `dt`.
+    pub fn step(&mut self, properties: &mut Properties, dt: Duration) {
+        self.watchdog_time += dt;
+        self.handle_request(properties);
+        self.check_errors(properties);
+        self.run_state(properties, dt);
+        self.move_motor(properties, dt);
+    }
+
+    fn handle_request(&mut self, properties: &mut Properties) {
+        let path = self.path("requested_state");
+        let requested = properties.int(&path);
+        if requested == AxisState::Undefined as i64 {
+            return;
+        }
+        properties.set(&path, Value::Int(AxisState::Undefined as i64));
+        self.sequence.clear();
+
+        let sequence = match AxisState::try_from(requested as i32) {
+            Ok(AxisState::Idle) => vec![AxisState::Idle],
+            Ok(AxisState::StartupSequence) => self.startup_sequence(properties),
+            Ok(AxisState::FullCalibrationSequence) => {
+                let mut sequence = vec![AxisState::MotorCalibration];
+                if properties.bool(&self.path("encoder.config.use_index")) {
+                    sequence.push(AxisState::EncoderIndexSearch);
+                }
+                sequence.push(AxisState::EncoderOffsetCalibration);
+                sequence
+            }
+            Ok(state) => vec![state],
+            Err(_) => {
+                self.raise(properties, AxisError::ErrorInvalidState);
+                vec![AxisState::Idle]
+            }
+        };
+
+        self.sequence.extend(sequence);
+        self.next_state(properties);
+    }
+
+    fn startup_sequence(&self, properties: &Properties) -> Vec<AxisState> {
+        let steps = [
+            (
+                "config.startup_motor_calibration",
+                AxisState::MotorCalibration,
+            ),
+            (
+                "config.startup_encoder_index_search",
+                AxisState::EncoderIndexSearch,
+            ),
+            (
+                "config.startup_encoder_offset_calibration",
+                AxisState::EncoderOffsetCalibration,
+            ),
+            (
+                "config.startup_closed_loop_control",
+                AxisState::ClosedLoopControl,
+            ),
+            (
+                "config.startup_sensorless_control",
+                AxisState::SensorlessControl,
+            ),
+        ];
+        steps
+            .iter()
+            .filter(|(name, _)| properties.bool(&self.path(name)))
+            .map(|(_, state)| *state)
+            .collect()
+    }
+
+    /// Enters the next state of the sequence, or `Idle` if it is empty.
+    fn next_state(&mut self, properties: &mut Properties) {
+        let state = match self.sequence.pop_front() {
+            Some(state) => state,
+            None => {
+                self.set_state(properties, AxisState::Idle);
+                return;
+            }
+        };
+
+        let motor_calibrated = properties.bool(&self.path("motor.is_calibrated"));
+        let encoder_ready = properties.bool(&self.path("encoder.is_ready"));
+        let allowed = match state {
+            AxisState::Idle | AxisState::MotorCalibration => true,
+            AxisState::EncoderIndexSearch => {
+                properties.bool(&self.path("encoder.config.use_index"))
+            }
+            AxisState::EncoderOffsetCalibration | AxisState::SensorlessControl => motor_calibrated,
+            AxisState::ClosedLoopControl => motor_calibrated && encoder_ready,
+            _ => false,
+        };
+
+        if !allowed {
+            self.raise(properties, AxisError::ErrorInvalidState);
+            self.sequence.clear();
+            self.set_state(properties, AxisState::Idle);
+        } else if state != AxisState::Idle && self.has_errors(properties) {
+            self.sequence.clear();
+            self.set_state(properties, AxisState::Idle);
+        } else {
+            if state == AxisState::ClosedLoopControl {
+                // Hold the current position when the loop is closed
+                let position = properties.float(&self.path("encoder.pos_estimate"));
+                properties.set(
+                    &self.path("controller.pos_setpoint"),
+                    Value::Float(position),
+                );
+                self.end_trajectory(properties);
+            }
+            self.set_state(properties, state);
+        }
+    }
+
+    /// Disarms the axis if an error occurred while it was active.
+    fn check_errors(&mut self, properties: &mut Properties) {
+        if self.current_state(properties) == AxisState::Idle {
+            return;
+        }
+
+        let vbus = properties.float("vbus_voltage");
+        if vbus < properties.float("config.dc_bus_undervoltage_trip_level") {
+            self.raise(properties, AxisError::ErrorDcBusUnderVoltage);
+        } else if vbus > properties.float("config.dc_bus_overvoltage_trip_level") {
+            self.raise(properties, AxisError::ErrorDcBusOverVoltage);
+        }
+        if self.watchdog_expired(properties) {
+            self.raise(properties, AxisError::ErrorWatchdogTimerExpired);
+        }
+
+        if self.has_errors(properties) {
+            self.sequence.clear();
+            self.set_state(properties, AxisState::Idle);
+        }
+    }
+
+    fn run_state(&mut self, properties: &mut Properties, dt: Duration) {
+        self.state_time += dt;
+        let (duration, result) = match self.current_state(properties) {
+            AxisState::MotorCalibration => (MOTOR_CALIBRATION_TIME, "motor.is_calibrated"),
+            AxisState::EncoderOffsetCalibration => {
+                (ENCODER_OFFSET_CALIBRATION_TIME, "encoder.is_ready")
+            }
+            AxisState::EncoderIndexSearch => (ENCODER_INDEX_SEARCH_TIME, "encoder.index_found"),
+            _ => return,
+        };
+
+        if self.state_time >= duration {
+            properties.set(&self.path(result), Value::Bool(true));
+            if result == "motor.is_calibrated" {
+                properties.set(
+                    &self.path("motor.config.phase_resistance"),
+                    Value::Float(0.05),
+                );
+                properties.set(
+                    &self.path("motor.config.phase_inductance"),
+                    Value::Float(0.000_02),
+                );
+            }
+            if result == "encoder.is_ready" {
+                properties.set(&self.path("encoder.config.offset"), Value::Int(1234));
+                properties.set(
+                    &self.path("encoder.config.offset_float"),
+                    Value::Float(0.25),
+                );
+            }
+            self.next_state(properties);
+        }
+    }
+
+    fn move_motor(&mut self, properties: &mut Properties, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        let position = properties.float(&self.path("encoder.pos_estimate"));
+        let velocity = properties.float(&self.path("encoder.vel_estimate"));
+        let vel_limit = properties.float(&self.path("controller.config.vel_limit"));
+        let current_lim = properties.float(&self.path("motor.config.current_lim"));
+        let control_mode = ControlMode::try_from(
+            properties.int(&self.path("controller.config.control_mode")) as i32,
+        );
+
+        let closed_loop = self.current_state(properties) == AxisState::ClosedLoopControl;
+        let velocity = match control_mode {
+            _ if !closed_loop => velocity * (-dt / COAST_TIME_CONSTANT).exp(),
+            Ok(ControlMode::CurrentControl) => {
+                let current = properties
+                    .float(&self.path("controller.current_setpoint"))
+                    .max(-current_lim)
+                    .min(current_lim);
+                velocity + current * ACCELERATION_PER_AMP * dt
+            }
+            Ok(ControlMode::VelocityControl) => {
+                let setpoint = properties
+                    .float(&self.path("controller.vel_setpoint"))
+                    .max(-vel_limit)
+                    .min(vel_limit);
+                follow(velocity, setpoint, dt)
+            }
+            Ok(ControlMode::PositionControl) => {
+                let error = properties.float(&self.path("controller.pos_setpoint")) - position;
+                let setpoint = (properties.float(&self.path("controller.config.pos_gain")) * error
+                    + properties.float(&self.path("controller.vel_setpoint")))
+                .max(-vel_limit)
+                .min(vel_limit);
+                follow(velocity, setpoint, dt)
+            }
+            Ok(ControlMode::TrajectoryControl) => self.trajectory_velocity(properties, dt),
+            _ => 0.0,
+        };
+
+        // Finishing a trajectory places the motor on its target
+        let position = properties.float(&self.path("encoder.pos_estimate"));
+        properties.set(&self.path("encoder.vel_estimate"), Value::Float(velocity));
+        properties.set(
+            &self.path("encoder.pos_estimate"),
+            Value::Float(position + velocity * dt),
+        );
+    }
+
+    /// Moves towards the trajectory target within the limits of the trajectory planner.
+    fn trajectory_velocity(&mut self, properties: &mut Properties, dt: f32) -> f32 {
+        let position = properties.float(&self.path("encoder.pos_estimate"));
+        let velocity = properties.float(&self.path("encoder.vel_estimate"));
+        let target = match self.trajectory_target {
+            Some(target) => target,
+            None => return follow(velocity, 0.0, dt),
+        };
+
+        let vel_limit = properties.float(&self.path("trap_traj.config.vel_limit"));
+        let accel_limit = properties.float(&self.path("trap_traj.config.accel_limit"));
+        let decel_limit = properties.float(&self.path("trap_traj.config.decel_limit"));
+
+        let error = target - position;
+        if error.abs() <= (velocity.abs() * dt).max(TRAJECTORY_TOLERANCE) {
+            // Snap onto the target for the last step
+            properties.set(&self.path("encoder.pos_estimate"), Value::Float(target));
+            properties.set(&self.path("controller.pos_setpoint"), Value::Float(target));
+            self.end_trajectory(properties);
+            return 0.0;
+        }
+
+        let stopping_velocity = (2.0 * decel_limit * error.abs()).sqrt();
+        let desired = error.signum() * stopping_velocity.min(vel_limit);
+        let change = (desired - velocity)
+            .max(-accel_limit.max(decel_limit) * dt)
+            .min(accel_limit.max(decel_limit) * dt);
+        properties.set(
+            &self.path("controller.pos_setpoint"),
+            Value::Float(position),
+        );
+        velocity + change
+    }
+}
+
+/// Lets the velocity follow its setpoint with a first order lag.
+fn follow(velocity: f32, setpoint: f32, dt: f32) -> f32 {
+    setpoint + (velocity - setpoint) * (-dt / VELOCITY_TIME_CONSTANT).exp()
+}
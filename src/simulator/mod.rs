@@ -0,0 +1,277 @@
+//! A simulated ODrive which speaks the ASCII protocol, so that applications can be tested against
+//! the real `ODrive` type without hardware:
+//!
+//! ```
+//! use odrive_rs::commands::ODrive;
+//! use odrive_rs::enumerations::AxisID;
+//! use odrive_rs::simulator::SimulatedODrive;
+//!
+//! let mut odrive = ODrive::new(SimulatedODrive::new());
+//! odrive.set_motor_pole_pairs(AxisID::Zero, 15).unwrap();
+//! assert_eq!(15, odrive.read_motor_pole_pairs(AxisID::Zero).unwrap());
+//! ```
+//!
+//! The simulation does not follow the wall clock. Instead, every command which is received
+//! advances the simulated time by a fixed time step, which keeps tests deterministic.
+
+use std::collections::VecDeque;
+use std::io::{Error, Read, Write};
+use std::time::Duration;
+
+use crate::enumerations::ControlMode;
+use crate::simulator::axis::AxisModel;
+use crate::simulator::properties::{Properties, WriteError};
+
+pub use crate::simulator::properties::Value;
+
+mod axis;
+mod properties;
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod simulator_tests;
+
+/// The simulated time which passes for every command received.
+pub const DEFAULT_TIME_STEP: Duration = Duration::from_millis(10);
+
+/// A simulated ODrive v3.6 running firmware 0.4.12, connected over the ASCII protocol.
+///
+/// It keeps a property tree which can be read and written with the `r` and `w` commands, runs
+/// the state machine of both axes and moves a simple model of each motor when the loop is closed.
+#[derive(Debug, Clone)]
+pub struct SimulatedODrive {
+    properties: Properties,
+    axes: [AxisModel; 2],
+    /// Bytes of a command which has not been terminated yet.
+    input: Vec<u8>,
+    /// Responses which have not been read yet.
+    output: VecDeque<u8>,
+    time_step: Duration,
+    elapsed: Duration,
+    commands: Vec<String>,
+}
+
+impl Default for SimulatedODrive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedODrive {
+    pub fn new() -> Self {
+        Self::with_time_step(DEFAULT_TIME_STEP)
+    }
+
+    /// Creates a simulated ODrive which advances by `time_step` for every command received.
+    pub fn with_time_step(time_step: Duration) -> Self {
+        let mut odrive = Self {
+            properties: Properties::default(),
+            axes: [AxisModel::new(0), AxisModel::new(1)],
+            input: Vec::new(),
+            output: VecDeque::new(),
+            time_step,
+            elapsed: Duration::from_secs(0),
+            commands: Vec::new(),
+        };
+        odrive.boot();
+        odrive
+    }
+
+    /// The simulated time since the ODrive was created.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Every command received so far, without the line terminator.
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Reads a property, for example `axis0.encoder.pos_estimate`.
+    pub fn property(&self, path: &str) -> Option<Value> {
+        self.properties.get(path)
+    }
+
+    /// Changes a property, including read-only ones like `vbus_voltage` or `axis0.error`.
+    /// Returns false if the property does not exist.
+    pub fn set_property(&mut self, path: &str, value: Value) -> bool {
+        self.properties.set(path, value)
+    }
+
+    /// Advances the simulation by `duration`, in steps of at most the configured time step.
+    pub fn advance(&mut self, duration: Duration) {
+        let mut remaining = duration;
+        while remaining > Duration::from_secs(0) {
+            let dt = remaining.min(self.time_step);
+            for axis in self.axes.iter_mut() {
+                axis.step(&mut self.properties, dt);
+            }
+            self.elapsed += dt;
+            remaining -= dt;
+        }
+    }
+
+    fn boot(&mut self) {
+        self.properties.reboot();
+        for axis in self.axes.iter_mut() {
+            axis.reboot(&mut self.properties);
+        }
+    }
+
+    fn reply<D: std::fmt::Display>(&mut self, message: D) {
+        self.output.extend(format!("{}\n", message).bytes());
+    }
+
+    /// Handles a single command and advances the simulation by one time step.
+    fn execute(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        self.commands.push(line.to_owned());
+
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        match command {
+            "p" | "q" | "v" | "c" | "t" | "f" => self.motion_command(command, &args),
+            "u" => match args.as_slice() {
+                [axis] => match axis.parse::<usize>() {
+                    Ok(axis) if axis < self.axes.len() => self.axes[axis].feed_watchdog(),
+                    Ok(_) => self.reply("invalid motor"),
+                    Err(_) => self.reply("invalid command format"),
+                },
+                _ => self.reply("invalid command format"),
+            },
+            "r" => match args.as_slice() {
+                [path] => match self.properties.get(path) {
+                    Some(value) => self.reply(value),
+                    None => self.reply("invalid property"),
+                },
+                _ => self.reply("invalid command format"),
+            },
+            "w" => match args.as_slice() {
+                [path, value] => match self.properties.write(path, value) {
+                    Ok(()) => {}
+                    Err(WriteError::InvalidProperty) => self.reply("invalid property"),
+                    Err(WriteError::InvalidValue) => self.reply("invalid value"),
+                },
+                _ => self.reply("invalid command format"),
+            },
+            "ss" => self.properties.save(),
+            "se" => self.properties.erase(),
+            "sc" => self.clear_errors(),
+            "sr" => self.boot(),
+            _ => self.reply("unknown command"),
+        }
+
+        let time_step = self.time_step;
+        self.advance(time_step);
+    }
+
+    fn clear_errors(&mut self) {
+        for path in ["error", "axis0.error", "axis1.error"].iter() {
+            self.properties.set(path, Value::Int(0));
+        }
+        for axis in 0..2 {
+            for component in ["motor", "encoder", "controller"].iter() {
+                let path = format!("axis{}.{}.error", axis, component);
+                self.properties.set(&path, Value::Int(0));
+            }
+        }
+    }
+
+    /// Handles the commands which control the motion of an axis.
+    fn motion_command(&mut self, command: &str, args: &[&str]) {
+        let axis = match args.first().map(|axis| axis.parse::<usize>()) {
+            Some(Ok(axis)) if axis < self.axes.len() => axis,
+            Some(Ok(_)) => return self.reply("invalid motor"),
+            _ => return self.reply("invalid command format"),
+        };
+        let values: Result<Vec<f32>, _> = args[1..].iter().map(|arg| arg.parse()).collect();
+        let values = match values {
+            Ok(values) => values,
+            Err(_) => return self.reply("invalid command format"),
+        };
+        let value = |index: usize| values.get(index).copied().unwrap_or_default();
+        let required = if command == "f" { 0 } else { 1 };
+        if values.len() < required {
+            return self.reply("invalid command format");
+        }
+
+        let path = |name: &str| format!("axis{}.{}", axis, name);
+        let mut set = |name: &str, value: f32| {
+            self.properties.set(&path(name), Value::Float(value));
+        };
+        let control_mode = match command {
+            "p" => {
+                set("controller.pos_setpoint", value(0));
+                set("controller.vel_setpoint", value(1));
+                set("controller.current_setpoint", value(2));
+                ControlMode::PositionControl
+            }
+            "q" => {
+                set("controller.pos_setpoint", value(0));
+                if values.len() > 1 {
+                    set("controller.config.vel_limit", value(1));
+                }
+                if values.len() > 2 {
+                    set("motor.config.current_lim", value(2));
+                }
+                ControlMode::PositionControl
+            }
+            "v" => {
+                set("controller.vel_setpoint", value(0));
+                set("controller.current_setpoint", value(1));
+                ControlMode::VelocityControl
+            }
+            "c" => {
+                set("controller.current_setpoint", value(0));
+                ControlMode::CurrentControl
+            }
+            "t" => {
+                self.axes[axis].start_trajectory(&mut self.properties, value(0));
+                ControlMode::TrajectoryControl
+            }
+            _ => {
+                let position = self.properties.float(&path("encoder.pos_estimate"));
+                let velocity = self.properties.float(&path("encoder.vel_estimate"));
+                return self.reply(format!("{} {}", position, velocity));
+            }
+        };
+        self.properties.set(
+            &path("controller.config.control_mode"),
+            Value::Int(control_mode as i64),
+        );
+    }
+}
+
+impl Write for SimulatedODrive {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        for byte in buf {
+            match byte {
+                b'\n' | b'\r' => {
+                    let line = String::from_utf8_lossy(&self.input).into_owned();
+                    self.input.clear();
+                    self.execute(&line);
+                }
+                _ => self.input.push(*byte),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Read for SimulatedODrive {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let count = buf.len().min(self.output.len());
+        for (target, byte) in buf.iter_mut().zip(self.output.drain(..count)) {
+            *target = byte;
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The value of a property of the simulated ODrive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+}
+
+impl Value {
+    /// Parses `text` as a value of the same type as `self`.
+    fn parse_like(&self, text: &str) -> Option<Value> {
+        match self {
+            Value::Bool(_) => match text {
+                "0" | "false" | "False" => Some(Value::Bool(false)),
+                "1" | "true" | "True" => Some(Value::Bool(true)),
+                _ => None,
+            },
+            Value::Int(_) => text.parse().ok().map(Value::Int),
+            Value::Float(_) => text.parse().ok().map(Value::Float),
+        }
+    }
+
+    pub fn as_bool(&self) -> bool {
+        match *self {
+            Value::Bool(value) => value,
+            Value::Int(value) => value != 0,
+            Value::Float(value) => value != 0.0,
+        }
+    }
+
+    pub fn as_i64(&self) -> i64 {
+        match *self {
+            Value::Bool(value) => value as i64,
+            Value::Int(value) => value,
+            Value::Float(value) => value as i64,
+        }
+    }
+
+    pub fn as_f32(&self) -> f32 {
+        match *self {
+            Value::Bool(value) => value as u8 as f32,
+            Value::Int(value) => value as f32,
+            Value::Float(value) => value,
+        }
+    }
+}
+
+/// Formats values the way the ODrive does, with booleans sent as `0` or `1` and floats printed
+/// with six decimals, like `%f`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", *value as u8),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{:.6}", value),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Access {
+    /// Reported by the ODrive, cannot be written over the protocol.
+    ReadOnly,
+    /// Can be written, but is not stored by `ss`.
+    ReadWrite,
+    /// Can be written and is stored in non-volatile memory by `ss`.
+    Config,
+}
+
+#[derive(Debug, Clone)]
+struct Property {
+    value: Value,
+    default: Value,
+    access: Access,
+}
+
+/// Why a write over the protocol was rejected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum WriteError {
+    InvalidProperty,
+    InvalidValue,
+}
+
+/// The property tree of the simulated ODrive, addressed by dotted paths like
+/// `axis0.motor.config.pole_pairs`.
+#[derive(Debug, Clone)]
+pub(crate) struct Properties {
+    map: BTreeMap<String, Property>,
+    /// The configuration stored in the simulated non-volatile memory.
+    saved: BTreeMap<String, Value>,
+}
+
+impl Properties {
+    fn add(&mut self, path: &str, access: Access, value: Value) {
+        let property = Property {
+            value,
+            default: value,
+            access,
+        };
+        self.map.insert(path.to_owned(), property);
+    }
+
+    pub fn get(&self, path: &str) -> Option<Value> {
+        self.map.get(path).map(|property| property.value)
+    }
+
+    /// Lists every property with its value, and whether it can be written over the protocol.
+    #[cfg(test)]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Value, bool)> {
+        self.map.iter().map(|(path, property)| {
+            let writable = property.access != Access::ReadOnly;
+            (path.as_str(), property.value, writable)
+        })
+    }
+
+    /// Changes a property from inside the simulator, ignoring its access rights.
+    /// Returns false if there is no such property.
+    pub fn set(&mut self, path: &str, value: Value) -> bool {
+        match self.map.get_mut(path) {
+            Some(property) => {
+                property.value = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Changes a property as requested by a `w` command.
+    pub fn write(&mut self, path: &str, text: &str) -> Result<(), WriteError> {
+        let property = match self.map.get_mut(path) {
+            Some(property) if property.access != Access::ReadOnly => property,
+            _ => return Err(WriteError::InvalidProperty),
+        };
+        property.value = property
+            .value
+            .parse_like(text)
+            .ok_or(WriteError::InvalidValue)?;
+        Ok(())
+    }
+
+    pub fn bool(&self, path: &str) -> bool {
+        self.get(path).map(|value| value.as_bool()).unwrap_or(false)
+    }
+
+    pub fn int(&self, path: &str) -> i64 {
+        self.get(path).map(|value| value.as_i64()).unwrap_or(0)
+    }
+
+    pub fn float(&self, path: &str) -> f32 {
+        self.get(path).map(|value| value.as_f32()).unwrap_or(0.0)
+    }
+
+    /// Stores the current configuration in non-volatile memory.
+    pub fn save(&mut self) {
+        self.saved = self
+            .map
+            .iter()
+            .filter(|(_, property)| property.access == Access::Config)
+            .map(|(path, property)| (path.clone(), property.value))
+            .collect();
+    }
+
+    /// Resets the configuration to the factory defaults and clears the non-volatile memory.
+    pub fn erase(&mut self) {
+        self.saved.clear();
+        for property in self.map.values_mut() {
+            if property.access == Access::Config {
+                property.value = property.default;
+            }
+        }
+    }
+
+    /// Resets every property as a reboot does, loading the configuration from non-volatile memory.
+    pub fn reboot(&mut self) {
+        for (path, property) in self.map.iter_mut() {
+            property.value = match self.saved.get(path) {
+                Some(value) => *value,
+                None => property.default,
+            };
+        }
+    }
+}
+
+impl Default for Properties {
+    /// Builds the property tree of an ODrive v3.6 running firmware 0.4.12, with factory defaults.
+    fn default() -> Self {
+        use Access::*;
+        use Value::*;
+
+        let mut properties = Properties {
+            map: BTreeMap::new(),
+            saved: BTreeMap::new(),
+        };
+
+        properties.add("error", ReadOnly, Int(0));
+        properties.add("vbus_voltage", ReadOnly, Float(24.0));
+        properties.add("serial_number", ReadOnly, Int(0x2068_3848_4D4B));
+        properties.add("hw_version_major", ReadOnly, Int(3));
+        properties.add("hw_version_minor", ReadOnly, Int(6));
+        properties.add("hw_version_variant", ReadOnly, Int(56));
+        properties.add("fw_version_major", ReadOnly, Int(0));
+        properties.add("fw_version_minor", ReadOnly, Int(4));
+        properties.add("fw_version_revision", ReadOnly, Int(12));
+        properties.add("config.brake_resistance", Config, Float(2.0));
+        properties.add("config.dc_bus_undervoltage_trip_level", Config, Float(8.0));
+        properties.add("config.dc_bus_overvoltage_trip_level", Config, Float(56.0));
+        properties.add("config.enable_uart", Config, Bool(true));
+
+        for axis in 0..2 {
+            let add = |properties: &mut Properties, name: &str, access, value| {
+                properties.add(&format!("axis{}.{}", axis, name), access, value)
+            };
+
+            add(&mut properties, "error", ReadOnly, Int(0));
+            add(&mut properties, "current_state", ReadOnly, Int(1));
+            add(&mut properties, "requested_state", ReadWrite, Int(0));
+            add(
+                &mut properties,
+                "config.startup_motor_calibration",
+                Config,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "config.startup_encoder_index_search",
+                Config,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "config.startup_encoder_offset_calibration",
+                Config,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "config.startup_closed_loop_control",
+                Config,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "config.startup_sensorless_control",
+                Config,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "config.watchdog_timeout",
+                Config,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "config.enable_watchdog",
+                Config,
+                Bool(false),
+            );
+
+            add(&mut properties, "motor.error", ReadOnly, Int(0));
+            add(
+                &mut properties,
+                "motor.is_calibrated",
+                ReadOnly,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "motor.current_control.Iq_measured",
+                ReadOnly,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "motor.config.pre_calibrated",
+                Config,
+                Bool(false),
+            );
+            add(&mut properties, "motor.config.pole_pairs", Config, Int(7));
+            add(
+                &mut properties,
+                "motor.config.calibration_current",
+                Config,
+                Float(10.0),
+            );
+            add(
+                &mut properties,
+                "motor.config.resistance_calib_max_voltage",
+                Config,
+                Float(2.0),
+            );
+            add(
+                &mut properties,
+                "motor.config.phase_inductance",
+                Config,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "motor.config.phase_resistance",
+                Config,
+                Float(0.0),
+            );
+            add(&mut properties, "motor.config.motor_type", Config, Int(0));
+            add(
+                &mut properties,
+                "motor.config.current_lim",
+                Config,
+                Float(10.0),
+            );
+            add(
+                &mut properties,
+                "motor.config.requested_current_range",
+                Config,
+                Float(60.0),
+            );
+            add(
+                &mut properties,
+                "motor.config.current_control_bandwidth",
+                Config,
+                Float(1000.0),
+            );
+
+            add(&mut properties, "encoder.error", ReadOnly, Int(0));
+            add(&mut properties, "encoder.is_ready", ReadOnly, Bool(false));
+            add(
+                &mut properties,
+                "encoder.index_found",
+                ReadOnly,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "encoder.pos_estimate",
+                ReadOnly,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "encoder.vel_estimate",
+                ReadOnly,
+                Float(0.0),
+            );
+            add(&mut properties, "encoder.config.mode", Config, Int(0));
+            add(
+                &mut properties,
+                "encoder.config.use_index",
+                Config,
+                Bool(false),
+            );
+            add(
+                &mut properties,
+                "encoder.config.pre_calibrated",
+                Config,
+                Bool(false),
+            );
+            add(&mut properties, "encoder.config.cpr", Config, Int(8192));
+            add(&mut properties, "encoder.config.offset", Config, Int(0));
+            add(
+                &mut properties,
+                "encoder.config.offset_float",
+                Config,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "encoder.config.bandwidth",
+                Config,
+                Float(1000.0),
+            );
+
+            add(&mut properties, "controller.error", ReadOnly, Int(0));
+            add(
+                &mut properties,
+                "controller.pos_setpoint",
+                ReadWrite,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "controller.vel_setpoint",
+                ReadWrite,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "controller.current_setpoint",
+                ReadWrite,
+                Float(0.0),
+            );
+            add(
+                &mut properties,
+                "controller.trajectory_done",
+                ReadOnly,
+                Bool(true),
+            );
+            add(
+                &mut properties,
+                "controller.config.control_mode",
+                Config,
+                Int(3),
+            );
+            add(
+                &mut properties,
+                "controller.config.pos_gain",
+                Config,
+                Float(20.0),
+            );
+            add(
+                &mut properties,
+                "controller.config.vel_gain",
+                Config,
+                Float(0.0005),
+            );
+            add(
+                &mut properties,
+                "controller.config.vel_integrator_gain",
+                Config,
+                Float(0.001),
+            );
+            add(
+                &mut properties,
+                "controller.config.vel_limit",
+                Config,
+                Float(20000.0),
+            );
+
+            add(
+                &mut properties,
+                "trap_traj.config.vel_limit",
+                Config,
+                Float(20000.0),
+            );
+            add(
+                &mut properties,
+                "trap_traj.config.accel_limit",
+                Config,
+                Float(5000.0),
+            );
+            add(
+                &mut properties,
+                "trap_traj.config.decel_limit",
+                Config,
+                Float(5000.0),
+            );
+            add(
+                &mut properties,
+                "trap_traj.config.A_per_css",
+                Config,
+                Float(0.0),
+            );
+        }
+
+        properties
+    }
+}
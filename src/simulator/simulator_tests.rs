@@ -0,0 +1,602 @@
+use super::*;
+use crate::commands::{ODrive, Transition, TrapTrajConfig, WatchdogConfig, WatchdogGuard};
+use crate::config::ODriveConfig;
+use crate::enumerations::errors::{AxisError, ODriveError};
+use crate::enumerations::{AxisID, AxisState, EncoderMode};
+use std::io::BufRead;
+use std::io::BufReader;
+use std::sync::{Arc, Mutex};
+
+fn send(odrive: &mut SimulatedODrive, command: &str) -> Vec<String> {
+    writeln!(odrive, "{}", command).unwrap();
+    BufReader::new(odrive).lines().map(Result::unwrap).collect()
+}
+
+fn calibrate(odrive: &mut ODrive<SimulatedODrive>, axis: AxisID) {
+    for state in [
+        AxisState::MotorCalibration,
+        AxisState::EncoderOffsetCalibration,
+    ]
+    .iter()
+    {
+        odrive
+            .run_state(axis, *state, Transition::for_state(*state))
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_read_property() {
+    let mut odrive = SimulatedODrive::new();
+    assert_eq!(vec!["24.000000"], send(&mut odrive, "r vbus_voltage"));
+    assert_eq!(
+        vec!["8192"],
+        send(&mut odrive, "r axis1.encoder.config.cpr")
+    );
+    assert_eq!(vec!["invalid property"], send(&mut odrive, "r axis2.error"));
+}
+
+#[test]
+fn test_write_property() {
+    let mut odrive = SimulatedODrive::new();
+    assert!(send(&mut odrive, "w axis0.motor.config.pole_pairs 15").is_empty());
+    assert_eq!(
+        vec!["15"],
+        send(&mut odrive, "r axis0.motor.config.pole_pairs")
+    );
+    assert_eq!(
+        vec!["invalid value"],
+        send(&mut odrive, "w axis0.motor.config.pole_pairs many")
+    );
+    assert_eq!(
+        vec!["invalid property"],
+        send(&mut odrive, "w vbus_voltage 12")
+    );
+    assert_eq!(
+        vec!["invalid command format"],
+        send(&mut odrive, "w axis0.motor.config.pole_pairs")
+    );
+}
+
+#[test]
+fn test_unknown_command() {
+    let mut odrive = SimulatedODrive::new();
+    assert_eq!(vec!["unknown command"], send(&mut odrive, "x 1 2"));
+    assert_eq!(vec!["invalid motor"], send(&mut odrive, "v 2 10"));
+    assert_eq!(
+        vec!["invalid command format"],
+        send(&mut odrive, "v 0 fast")
+    );
+}
+
+#[test]
+fn test_commands_advance_time() {
+    let mut odrive = SimulatedODrive::with_time_step(Duration::from_millis(5));
+    send(&mut odrive, "r vbus_voltage");
+    send(&mut odrive, "r vbus_voltage");
+    assert_eq!(Duration::from_millis(10), odrive.elapsed());
+    assert_eq!(
+        vec!["r vbus_voltage".to_owned(), "r vbus_voltage".to_owned()],
+        odrive.commands()
+    );
+}
+
+#[test]
+fn test_save_and_reboot() {
+    let mut odrive = SimulatedODrive::new();
+    send(&mut odrive, "w axis0.encoder.config.cpr 90");
+    send(&mut odrive, "ss");
+    send(&mut odrive, "w axis0.encoder.config.cpr 100");
+    send(&mut odrive, "sr");
+    assert_eq!(vec!["90"], send(&mut odrive, "r axis0.encoder.config.cpr"));
+    send(&mut odrive, "se");
+    assert_eq!(
+        vec!["8192"],
+        send(&mut odrive, "r axis0.encoder.config.cpr")
+    );
+    send(&mut odrive, "sr");
+    assert_eq!(
+        vec!["8192"],
+        send(&mut odrive, "r axis0.encoder.config.cpr")
+    );
+}
+
+#[test]
+fn test_configuration_round_trip() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    odrive
+        .set_encoder_mode(AxisID::One, EncoderMode::EncoderModeHall)
+        .unwrap();
+    odrive.set_velocity_gain(AxisID::One, 0.02).unwrap();
+    odrive
+        .set_startup_closed_loop_control(AxisID::One, true)
+        .unwrap();
+    assert_eq!(
+        EncoderMode::EncoderModeHall,
+        odrive.read_encoder_mode(AxisID::One).unwrap()
+    );
+    assert_eq!(0.02, odrive.read_velocity_gain(AxisID::One).unwrap());
+    assert!(odrive
+        .read_startup_closed_loop_control(AxisID::One)
+        .unwrap());
+}
+
+#[test]
+fn test_calibration_and_velocity_control() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    calibrate(&mut odrive, AxisID::Zero);
+    odrive
+        .run_state(
+            AxisID::Zero,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+
+    odrive.set_velocity(AxisID::Zero, 1000.0, None).unwrap();
+    odrive.get_mut().advance(Duration::from_millis(500));
+    let velocity = odrive.get_velocity(AxisID::Zero).unwrap().unwrap();
+    assert!((velocity - 1000.0).abs() < 1.0, "velocity {}", velocity);
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        odrive.current_state(AxisID::Zero).unwrap()
+    );
+    assert_eq!(AxisState::Idle, odrive.current_state(AxisID::One).unwrap());
+}
+
+#[test]
+fn test_position_and_trajectory_control() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    calibrate(&mut odrive, AxisID::One);
+    odrive
+        .run_state(
+            AxisID::One,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+
+    odrive
+        .set_position_p(AxisID::One, 500.0, None, None)
+        .unwrap();
+    odrive.get_mut().advance(Duration::from_secs(1));
+    let position = odrive.get_ref().property("axis1.encoder.pos_estimate");
+    assert!((position.unwrap().as_f32() - 500.0).abs() < 1.0);
+
+    odrive.set_trajectory(AxisID::One, 5000.0).unwrap();
+    odrive.get_mut().advance(Duration::from_secs(3));
+    assert_eq!(
+        Some(Value::Float(5000.0)),
+        odrive.get_ref().property("axis1.encoder.pos_estimate")
+    );
+}
+
+#[test]
+fn test_move_to() {
+    let mut odrive = ODrive::new(SimulatedODrive::with_time_step(Duration::from_millis(50)));
+    calibrate(&mut odrive, AxisID::Zero);
+    odrive
+        .run_state(
+            AxisID::Zero,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+    odrive
+        .set_trap_traj_config(
+            AxisID::Zero,
+            TrapTrajConfig {
+                vel_limit: 4000.0,
+                ..TrapTrajConfig::default()
+            },
+        )
+        .unwrap();
+
+    odrive
+        .move_to(AxisID::Zero, 8000.0, Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(
+        Some(Value::Float(8000.0)),
+        odrive.get_ref().property("axis0.encoder.pos_estimate")
+    );
+    // Limited to 4000 counts/s, the move cannot take less than two seconds
+    assert!(odrive.get_ref().elapsed() > Duration::from_secs(2));
+    assert!(odrive.read_trajectory_done(AxisID::Zero).unwrap());
+}
+
+#[test]
+fn test_stream_trajectory() {
+    use crate::trajectory::{SCurveLimits, Trajectory};
+
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    calibrate(&mut odrive, AxisID::One);
+    odrive
+        .run_state(
+            AxisID::One,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+
+    let limits = SCurveLimits {
+        velocity: 10000.0,
+        acceleration: 100000.0,
+        jerk: 2000000.0,
+    };
+    let trajectory = Trajectory::s_curve(0.0, 300.0, limits).unwrap();
+    odrive
+        .stream_trajectory(AxisID::One, &trajectory, Duration::from_millis(5), 0.0)
+        .unwrap();
+    odrive.get_mut().advance(Duration::from_secs(1));
+    let position = odrive.get_ref().property("axis1.encoder.pos_estimate");
+    assert!((position.unwrap().as_f32() - 300.0).abs() < 1.0);
+}
+
+#[test]
+fn test_feedback() {
+    let mut odrive = SimulatedODrive::new();
+    odrive.set_property("axis0.encoder.pos_estimate", Value::Float(12.5));
+    assert_eq!(vec!["12.5 0"], send(&mut odrive, "f 0"));
+}
+
+#[test]
+fn test_closed_loop_requires_calibration() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    match odrive.run_state(
+        AxisID::Zero,
+        AxisState::ClosedLoopControl,
+        Transition::for_state(AxisState::ClosedLoopControl),
+    ) {
+        Err(ODriveError::StateTransition(error)) => {
+            assert!(error.errors.axis.contains(AxisError::ErrorInvalidState))
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    odrive.clear_errors().unwrap();
+    assert!(odrive.read_all_errors().unwrap().is_empty());
+}
+
+#[test]
+fn test_undervoltage_disarms_axis() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    calibrate(&mut odrive, AxisID::Zero);
+    odrive
+        .run_state(
+            AxisID::Zero,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+
+    odrive
+        .get_mut()
+        .set_property("vbus_voltage", Value::Float(6.0));
+    odrive.get_mut().advance(Duration::from_millis(10));
+    let report = odrive.read_all_errors().unwrap();
+    assert!(report
+        .axis(AxisID::Zero)
+        .axis
+        .contains(AxisError::ErrorDcBusUnderVoltage));
+    assert_eq!(AxisState::Idle, odrive.current_state(AxisID::Zero).unwrap());
+}
+
+#[test]
+fn test_startup_sequence_after_reboot() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    odrive.set_motor_pre_calibrated(AxisID::Zero, true).unwrap();
+    odrive
+        .set_startup_encoder_offset_calibration(AxisID::Zero, true)
+        .unwrap();
+    odrive
+        .set_startup_closed_loop_control(AxisID::Zero, true)
+        .unwrap();
+    odrive.save_configuration().unwrap();
+    odrive.reboot().unwrap();
+
+    assert_eq!(
+        AxisState::EncoderOffsetCalibration,
+        odrive.current_state(AxisID::Zero).unwrap()
+    );
+    odrive.get_mut().advance(Duration::from_secs(1));
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        odrive.current_state(AxisID::Zero).unwrap()
+    );
+}
+
+#[test]
+fn test_pipelined_feedback() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    odrive
+        .get_mut()
+        .set_property("axis1.encoder.pos_estimate", Value::Float(-4.0));
+    odrive.request_feedback(AxisID::Zero).unwrap();
+    odrive.request_feedback(AxisID::One).unwrap();
+    odrive.flush().unwrap();
+
+    let (axis, feedback) = odrive.read_feedback().unwrap();
+    assert_eq!((AxisID::Zero, 0.0), (axis, feedback.position));
+    let (axis, feedback) = odrive.read_feedback().unwrap();
+    assert_eq!((AxisID::One, -4.0), (axis, feedback.position));
+    assert_eq!(-4.0, odrive.get_feedback(AxisID::One).unwrap().position);
+}
+
+#[test]
+fn test_watchdog_disarms_axis_once_guard_is_dropped() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    calibrate(&mut odrive, AxisID::Zero);
+    let config = WatchdogConfig::enabled(Duration::from_millis(100));
+    odrive.configure_watchdog(AxisID::Zero, config).unwrap();
+    odrive
+        .run_state(
+            AxisID::Zero,
+            AxisState::ClosedLoopControl,
+            Transition::for_state(AxisState::ClosedLoopControl),
+        )
+        .unwrap();
+
+    let odrive = Arc::new(Mutex::new(odrive));
+    let guard = WatchdogGuard::new(odrive.clone(), &[AxisID::Zero], Duration::from_millis(1));
+    // Every feed advances the simulated time, well past the timeout
+    while odrive.lock().unwrap().get_ref().elapsed() < Duration::from_secs(2) {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let mut locked = odrive.lock().unwrap();
+    assert_eq!(
+        AxisState::ClosedLoopControl,
+        locked.current_state(AxisID::Zero).unwrap()
+    );
+    drop(locked);
+    drop(guard);
+
+    let mut odrive = odrive.lock().unwrap();
+    odrive.get_mut().advance(Duration::from_millis(200));
+    assert_eq!(AxisState::Idle, odrive.current_state(AxisID::Zero).unwrap());
+    assert!(odrive
+        .read_axis_errors(AxisID::Zero)
+        .unwrap()
+        .axis
+        .contains(AxisError::ErrorWatchdogTimerExpired));
+}
+
+#[test]
+fn test_watchdog_command() {
+    let mut odrive = SimulatedODrive::new();
+    assert!(send(&mut odrive, "u 0").is_empty());
+    assert_eq!(vec!["invalid motor"], send(&mut odrive, "u 2"));
+    assert_eq!(vec!["invalid command format"], send(&mut odrive, "u"));
+}
+
+#[test]
+fn test_properties_are_in_the_property_tree() {
+    use crate::commands::Capabilities;
+    use crate::properties::{self, ValueType};
+
+    // The simulator implements firmware 0.4.12
+    let tree = properties::for_firmware(&Capabilities::default());
+    let odrive = SimulatedODrive::new();
+    for (path, value, writable) in odrive.properties.iter() {
+        let info = tree
+            .iter()
+            .find(|info| info.path == path)
+            .unwrap_or_else(|| panic!("{} is missing from the property tree", path));
+        let compatible = match value {
+            Value::Bool(_) => info.value_type == ValueType::Bool,
+            Value::Float(_) => info.value_type == ValueType::Float,
+            Value::Int(_) => !matches!(info.value_type, ValueType::Bool | ValueType::Float),
+        };
+        assert!(compatible, "{} has type {:?}", path, info.value_type);
+        assert!(!writable || info.writable, "{} is not writable", path);
+    }
+}
+
+#[test]
+fn test_read_and_write_properties() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    odrive
+        .write_property_verified("axis0.motor.config.current_lim", 20.0)
+        .unwrap();
+    assert_eq!(
+        20.0,
+        odrive
+            .read_property::<f32>("axis0.motor.config.current_lim")
+            .unwrap()
+    );
+
+    match odrive.write_property("vbus_voltage", 12.0) {
+        Err(ODriveError::InvalidProperty { path, reply }) => {
+            assert_eq!("vbus_voltage", path);
+            assert_eq!("invalid property", reply);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    match odrive.write_property("axis0.motor.config.pole_pairs", "many") {
+        Err(ODriveError::InvalidProperty { reply, .. }) => assert_eq!("invalid value", reply),
+        result => panic!("unexpected result {:?}", result),
+    }
+    match odrive.read_property::<f32>("axis0.motor.config.current_limit") {
+        Err(ODriveError::InvalidProperty { reply, .. }) => assert_eq!("invalid property", reply),
+        result => panic!("unexpected result {:?}", result),
+    }
+    // The connection is still in sync
+    assert_eq!(
+        7,
+        odrive
+            .read_property::<i32>("axis0.motor.config.pole_pairs")
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_detect_firmware() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    let capabilities = odrive.detect_firmware().unwrap();
+    assert_eq!("0.4.12", capabilities.firmware().to_string());
+    assert_eq!("v3.6-56V", capabilities.hardware().unwrap().to_string());
+}
+
+#[test]
+fn test_backup_and_restore_config() {
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    assert_eq!(ODriveConfig::default(), odrive.backup_config().unwrap());
+
+    let mut config = ODriveConfig::default();
+    config.system.brake_resistance = 0.5;
+    config.axis1.startup.closed_loop_control = true;
+    config.axis1.motor.pole_pairs = 15;
+    config.axis1.encoder.mode = EncoderMode::EncoderModeHall;
+    config.axis1.encoder.cpr = 90;
+    config.axis1.controller.vel_gain = 0.02;
+    config.axis1.trap_traj.inertia = 0.25;
+    odrive.restore_config(&config, true).unwrap();
+    assert_eq!(config, odrive.backup_config().unwrap());
+
+    // The restored configuration was saved
+    odrive.reboot().unwrap();
+    assert_eq!(config, odrive.backup_config().unwrap());
+
+    // Restoring without saving is lost on reboot
+    odrive
+        .restore_config(&ODriveConfig::default(), false)
+        .unwrap();
+    odrive.reboot().unwrap();
+    assert_eq!(config, odrive.backup_config().unwrap());
+}
+
+#[test]
+fn test_restore_encoder_offset() {
+    let mut source = ODrive::new(SimulatedODrive::new());
+    source
+        .run_state(
+            AxisID::Zero,
+            AxisState::FullCalibrationSequence,
+            Transition::for_state(AxisState::FullCalibrationSequence)
+                .with_timeout(Duration::from_secs(60)),
+        )
+        .unwrap();
+    source
+        .write_property("axis0.encoder.config.pre_calibrated", 1)
+        .unwrap();
+    let config = source.backup_config().unwrap();
+    assert!(config.axis0.encoder.pre_calibrated);
+    assert_eq!(1234, config.axis0.encoder.offset);
+
+    // A replacement board with its own stale offset
+    let mut simulator = SimulatedODrive::new();
+    simulator.set_property("axis0.encoder.config.offset", Value::Int(99));
+    let mut target = ODrive::new(simulator);
+    target.restore_config(&config, true).unwrap();
+    target.reboot().unwrap();
+    assert_eq!(config, target.backup_config().unwrap());
+    assert_eq!(
+        Some(Value::Int(1234)),
+        target.get_ref().property("axis0.encoder.config.offset")
+    );
+    assert_eq!(
+        Some(Value::Float(0.25)),
+        target
+            .get_ref()
+            .property("axis0.encoder.config.offset_float")
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_odrivetool_config() {
+    use crate::config::OdrivetoolConfig;
+
+    let mut source = ODrive::new(SimulatedODrive::new());
+    source.set_motor_pole_pairs(AxisID::One, 15).unwrap();
+    source.set_velocity_gain(AxisID::One, 0.02).unwrap();
+    let json = source.export_odrivetool_config().unwrap();
+
+    let mut target = ODrive::new(SimulatedODrive::new());
+    let config = OdrivetoolConfig::from_json(&json).unwrap();
+    assert!(config.unknown_keys.is_empty());
+    target.apply_odrivetool_config(&config).unwrap();
+    assert_eq!(
+        source.backup_config().unwrap(),
+        target.backup_config().unwrap()
+    );
+}
+
+#[test]
+fn test_diff_config() {
+    use crate::config::{ConfigValue, Tolerance};
+
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    let mut config = ODriveConfig::default();
+    config.axis0.encoder.cpr = 90;
+    config.axis1.controller.pos_gain = 20.01;
+    // Below the six decimals the ODrive prints, so never a difference.
+    config.axis1.controller.vel_gain = 0.000_500_1;
+    let expected = config.properties(&odrive.capabilities()).unwrap();
+
+    let differences = odrive.diff_config(&expected, Tolerance::default()).unwrap();
+    assert_eq!(2, differences.len());
+    assert_eq!("axis0.encoder.config.cpr", differences[0].path);
+    assert_eq!(ConfigValue::Int(8192), differences[0].device);
+    assert_eq!(ConfigValue::Int(90), differences[0].expected);
+    assert_eq!("axis1.controller.config.pos_gain", differences[1].path);
+
+    let differences = odrive
+        .diff_config(&expected, Tolerance::relative(0.001))
+        .unwrap();
+    assert_eq!(1, differences.len());
+
+    odrive.restore_config(&config, false).unwrap();
+    assert!(odrive
+        .diff_config(&expected, Tolerance::default())
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_apply_config() {
+    use crate::config::{ConfigProperty, ConfigValue};
+
+    let mut odrive = ODrive::new(SimulatedODrive::new());
+    let mut config = ODriveConfig::default();
+    config.axis0.motor.pole_pairs = 15;
+    config.axis1.encoder.cpr = 90;
+    let properties = config.properties(&odrive.capabilities()).unwrap();
+
+    let report = odrive.apply_config(&properties, true).unwrap();
+    assert_eq!(properties, report.applied);
+    assert!(report.rolled_back.is_empty());
+    odrive.reboot().unwrap();
+    assert_eq!(config, odrive.backup_config().unwrap());
+
+    // The last write is rejected, so the first one is rolled back
+    let properties = vec![
+        ConfigProperty {
+            path: "axis0.motor.config.pole_pairs".to_owned(),
+            value: ConfigValue::Int(20),
+        },
+        ConfigProperty {
+            path: "axis0.encoder.config.cpr".to_owned(),
+            value: ConfigValue::Float(0.5),
+        },
+    ];
+    let error = match odrive.apply_config(&properties, true) {
+        Err(ODriveError::ConfigApply(error)) => error,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!(Some("axis0.encoder.config.cpr"), error.path.as_deref());
+    assert_eq!(
+        vec![ConfigProperty {
+            path: "axis0.motor.config.pole_pairs".to_owned(),
+            value: ConfigValue::Int(15),
+        }],
+        error.report.rolled_back
+    );
+    assert!(error.rollback_errors.is_empty());
+    assert_eq!(config, odrive.backup_config().unwrap());
+    let commands = odrive.get_ref().commands();
+    let rejected = commands
+        .iter()
+        .position(|command| command == "w axis0.encoder.config.cpr 0.5")
+        .unwrap();
+    assert!(!commands[rejected..].iter().any(|command| command == "ss"));
+}
@@ -1,10 +1,17 @@
-use std::io::{Error, Read, Write};
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Read, Write};
 
 #[derive(Eq, PartialEq, Ord, PartialOrd, Default, Debug, Clone)]
 pub struct MockStream {
     pub read_buffer: Vec<u8>,
     pub write_buffer: Vec<u8>,
     pub flushed: bool,
+    /// Responses which are made readable one at a time, each time a line is written.
+    pub responses: VecDeque<Vec<u8>>,
+    /// When set, every read fails with this kind of error.
+    pub read_error: Option<ErrorKind>,
+    /// When set, every write of a buffer starting with these bytes fails with this kind of error.
+    pub write_error: Option<(&'static [u8], ErrorKind)>,
 }
 
 impl MockStream {
@@ -13,14 +20,36 @@ impl MockStream {
             read_buffer: Vec::new(),
             write_buffer: Vec::new(),
             flushed: false,
+            responses: VecDeque::new(),
+            read_error: None,
+            write_error: None,
         }
     }
+
+    /// Queues a response which becomes readable once the next line has been written.
+    pub fn push_response(&mut self, response: &[u8]) {
+        self.responses.push_back(response.to_vec());
+    }
 }
 
 impl Write for MockStream {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if let Some((prefix, kind)) = self.write_error {
+            if buf.starts_with(prefix) {
+                return Err(Error::from(kind));
+            }
+        }
+
         for e in buf {
             self.write_buffer.push(*e);
+            if *e == b'\n' {
+                if let Some(mut response) = self.responses.pop_front() {
+                    // the read buffer is consumed from the back
+                    response.reverse();
+                    response.append(&mut self.read_buffer);
+                    self.read_buffer = response;
+                }
+            }
         }
         Ok(buf.len())
     }
@@ -33,6 +62,10 @@ impl Write for MockStream {
 
 impl Read for MockStream {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if let Some(kind) = self.read_error {
+            return Err(Error::from(kind));
+        }
+
         let mut count = 0;
         while count < buf.len() {
             if let Some(res) = self.read_buffer.pop() {
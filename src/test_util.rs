@@ -0,0 +1,11 @@
+/// Asserts that `actual` is within `tolerance` of `expected`.
+#[track_caller]
+pub fn assert_close(expected: f32, actual: f32, tolerance: f32) {
+    assert!(
+        (expected - actual).abs() <= tolerance,
+        "expected {} within {}, got {}",
+        expected,
+        tolerance,
+        actual
+    );
+}
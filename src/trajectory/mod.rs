@@ -0,0 +1,378 @@
+//! Plans point-to-point moves on the host, to be streamed to the ODrive with `set_position_p`.
+//!
+//! A `Trajectory` starts and ends at rest. It is either trapezoidal, with a limited velocity and
+//! acceleration like the planner of the firmware, or an S-curve which also limits the jerk.
+//! Trajectories are sampled at fixed times, so the setpoints which are sent are deterministic:
+//!
+//! ```
+//! use std::time::Duration;
+//! use odrive_rs::trajectory::{TrapezoidalLimits, Trajectory};
+//!
+//! let limits = TrapezoidalLimits {
+//!     velocity: 2000.0,
+//!     acceleration: 4000.0,
+//!     deceleration: 4000.0,
+//! };
+//! let trajectory = Trajectory::trapezoidal(0.0, 1000.0, limits).unwrap();
+//! assert_eq!(Duration::from_secs(1), trajectory.duration());
+//!
+//! let samples: Vec<_> = trajectory.samples(Duration::from_millis(10)).collect();
+//! assert_eq!(101, samples.len());
+//! assert_eq!(1000.0, samples[100].position);
+//! ```
+//!
+//! Positions are in the unit of the firmware, see `Capabilities::position_unit`.
+
+use std::io::{Read, Write};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::commands::{ODrive, TrapTrajConfig};
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+use crate::enumerations::AxisID;
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod trajectory_tests;
+
+/// The limits of a trapezoidal trajectory, which are all positive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrapezoidalLimits {
+    pub velocity: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
+}
+
+/// Uses the same limits as the trajectory planner of the firmware.
+impl From<TrapTrajConfig> for TrapezoidalLimits {
+    fn from(config: TrapTrajConfig) -> Self {
+        Self {
+            velocity: config.vel_limit,
+            acceleration: config.accel_limit,
+            deceleration: config.decel_limit,
+        }
+    }
+}
+
+/// The limits of an S-curve trajectory, which are all positive. The deceleration is limited like
+/// the acceleration.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SCurveLimits {
+    pub velocity: f32,
+    pub acceleration: f32,
+    pub jerk: f32,
+}
+
+/// The state of a trajectory at a point in time.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Sample {
+    /// The time since the start of the trajectory.
+    pub time: Duration,
+    pub position: f32,
+    pub velocity: f32,
+    pub acceleration: f32,
+}
+
+impl Sample {
+    /// The feed forward needed to accelerate a load of `inertia`, in the units of
+    /// `TrapTrajConfig::inertia`: a current in A with `inertia` in A per count/s² on firmware
+    /// 0.4.x, and a torque in Nm with `inertia` in Nm per turn/s² since firmware 0.5.0, where
+    /// positions are in turns.
+    pub fn current_feed_forward(&self, inertia: f32) -> f32 {
+        self.acceleration * inertia
+    }
+}
+
+/// A part of a trajectory with a constant jerk. The state is the one at the start of the segment.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Segment {
+    start: f64,
+    duration: f64,
+    position: f64,
+    velocity: f64,
+    acceleration: f64,
+    jerk: f64,
+}
+
+impl Segment {
+    /// Returns the position, velocity and acceleration `dt` after the start of the segment.
+    fn state(&self, dt: f64) -> (f64, f64, f64) {
+        let position = self.position
+            + self.velocity * dt
+            + self.acceleration * dt * dt / 2.0
+            + self.jerk * dt * dt * dt / 6.0;
+        let velocity = self.velocity + self.acceleration * dt + self.jerk * dt * dt / 2.0;
+        let acceleration = self.acceleration + self.jerk * dt;
+        (position, velocity, acceleration)
+    }
+}
+
+/// A move from rest at `start` to rest at `end`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    start: f32,
+    end: f32,
+    /// The segments of the move, relative to `start` and in the direction of `end`.
+    segments: Vec<Segment>,
+    duration: f64,
+}
+
+impl Trajectory {
+    /// Plans a move with a trapezoidal velocity profile. If the velocity limit cannot be reached
+    /// within the distance, the profile is a triangle.
+    /// Returns `None` if a limit is not positive and finite.
+    pub fn trapezoidal(start: f32, end: f32, limits: TrapezoidalLimits) -> Option<Self> {
+        if !valid_limits(&[limits.velocity, limits.acceleration, limits.deceleration]) {
+            return None;
+        }
+        let distance = f64::from((end - start).abs());
+        let accel = f64::from(limits.acceleration);
+        let decel = f64::from(limits.deceleration);
+
+        let mut velocity = f64::from(limits.velocity);
+        let ramps = velocity * velocity / (2.0 * accel) + velocity * velocity / (2.0 * decel);
+        let cruise = if ramps <= distance {
+            (distance - ramps) / velocity
+        } else {
+            velocity = (2.0 * distance * accel * decel / (accel + decel)).sqrt();
+            0.0
+        };
+
+        let phases = [
+            (velocity / accel, accel),
+            (cruise, 0.0),
+            (velocity / decel, -decel),
+        ];
+        let phases = phases.iter().map(|&(duration, acceleration)| {
+            // A constant acceleration is a step in acceleration followed by zero jerk
+            (duration, Some(acceleration), 0.0)
+        });
+        Some(Self::from_phases(start, end, phases))
+    }
+
+    /// Plans a move with a jerk limited S-curve profile: the acceleration ramps up and down
+    /// linearly, so the velocity has no corners. The acceleration and velocity limits are only
+    /// reached if the distance allows it.
+    /// Returns `None` if a limit is not positive and finite.
+    pub fn s_curve(start: f32, end: f32, limits: SCurveLimits) -> Option<Self> {
+        if !valid_limits(&[limits.velocity, limits.acceleration, limits.jerk]) {
+            return None;
+        }
+        let distance = f64::from((end - start).abs());
+        let accel = f64::from(limits.acceleration);
+        let jerk = f64::from(limits.jerk);
+
+        // The distance covered while accelerating to `velocity` and decelerating back to rest
+        let ramps = |velocity: f64| {
+            let ramp_time = if velocity * jerk < accel * accel {
+                2.0 * (velocity / jerk).sqrt()
+            } else {
+                velocity / accel + accel / jerk
+            };
+            velocity * ramp_time
+        };
+
+        let mut velocity = f64::from(limits.velocity);
+        let cruise = if ramps(velocity) <= distance {
+            (distance - ramps(velocity)) / velocity
+        } else {
+            // Solve ramps(velocity) == distance, assuming the acceleration limit is reached
+            velocity = accel / 2.0
+                * (-accel / jerk + (accel * accel / (jerk * jerk) + 4.0 * distance / accel).sqrt());
+            if velocity * jerk < accel * accel {
+                velocity = (distance * jerk.sqrt() / 2.0).powf(2.0 / 3.0);
+            }
+            0.0
+        };
+
+        let (jerk_time, accel_time) = if velocity * jerk < accel * accel {
+            ((velocity / jerk).sqrt(), 0.0)
+        } else {
+            (accel / jerk, velocity / accel - accel / jerk)
+        };
+
+        let phases = [
+            (jerk_time, jerk),
+            (accel_time, 0.0),
+            (jerk_time, -jerk),
+            (cruise, 0.0),
+            (jerk_time, -jerk),
+            (accel_time, 0.0),
+            (jerk_time, jerk),
+        ];
+        let phases = phases
+            .iter()
+            .map(|&(duration, jerk)| (duration, None, jerk));
+        Some(Self::from_phases(start, end, phases))
+    }
+
+    /// Integrates phases of `(duration, acceleration, jerk)` into segments. The acceleration is
+    /// carried over from the previous phase when it is `None`.
+    fn from_phases<I>(start: f32, end: f32, phases: I) -> Self
+    where
+        I: Iterator<Item = (f64, Option<f64>, f64)>,
+    {
+        let mut segments = Vec::new();
+        let mut time = 0.0;
+        let (mut position, mut velocity, mut acceleration) = (0.0, 0.0, 0.0);
+        for (duration, set_acceleration, jerk) in phases {
+            if let Some(set_acceleration) = set_acceleration {
+                acceleration = set_acceleration;
+            }
+            if duration <= 0.0 {
+                continue;
+            }
+            let segment = Segment {
+                start: time,
+                duration,
+                position,
+                velocity,
+                acceleration,
+                jerk,
+            };
+            let (next_position, next_velocity, next_acceleration) = segment.state(duration);
+            position = next_position;
+            velocity = next_velocity;
+            acceleration = next_acceleration;
+            time += duration;
+            segments.push(segment);
+        }
+
+        Self {
+            start,
+            end,
+            segments,
+            duration: time,
+        }
+    }
+
+    pub fn start(&self) -> f32 {
+        self.start
+    }
+
+    pub fn end(&self) -> f32 {
+        self.end
+    }
+
+    /// The time the move takes, rounded to the nearest nanosecond.
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos((self.duration * 1e9).round() as u64)
+    }
+
+    /// Returns the state of the move at `time`. Before the start and after the end of the move,
+    /// it is at rest.
+    pub fn sample(&self, time: Duration) -> Sample {
+        let seconds = time.as_secs_f64();
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| seconds < segment.start + segment.duration);
+        let (position, velocity, acceleration) = match segment {
+            Some(segment) if time < self.duration() => {
+                segment.state((seconds - segment.start).max(0.0))
+            }
+            _ => {
+                return Sample {
+                    time,
+                    position: self.end,
+                    ..Sample::default()
+                }
+            }
+        };
+
+        let direction = if self.end < self.start { -1.0 } else { 1.0 };
+        Sample {
+            time,
+            position: self.start + direction * position as f32,
+            velocity: direction * velocity as f32,
+            acceleration: direction * acceleration as f32,
+        }
+    }
+
+    /// Samples the move every `period`, from its start up to and including its end.
+    ///
+    /// # Panics
+    /// Panics if `period` is zero.
+    pub fn samples(&self, period: Duration) -> Samples<'_> {
+        assert!(
+            period > Duration::from_secs(0),
+            "the period must not be zero"
+        );
+        Samples {
+            trajectory: self,
+            period,
+            index: 0,
+            done: false,
+        }
+    }
+}
+
+fn valid_limits(limits: &[f32]) -> bool {
+    limits.iter().all(|limit| limit.is_finite() && *limit > 0.0)
+}
+
+/// An iterator over the samples of a trajectory at a fixed period, created by
+/// `Trajectory::samples`.
+#[derive(Debug, Clone)]
+pub struct Samples<'a> {
+    trajectory: &'a Trajectory,
+    period: Duration,
+    index: u32,
+    done: bool,
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        if self.done {
+            return None;
+        }
+        let duration = self.trajectory.duration();
+        let mut time = self.period * self.index;
+        if time >= duration {
+            time = duration;
+            self.done = true;
+        }
+        self.index += 1;
+        Some(self.trajectory.sample(time))
+    }
+}
+
+/// # Streaming trajectories
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Sends the samples of `trajectory` with `set_position_p` every `period`, with velocity and
+    /// current feed forward terms, which are torques since firmware 0.5.0. `inertia` converts the
+    /// acceleration into the current feed forward, see `Sample::current_feed_forward`.
+    ///
+    /// The axis must be in closed loop position control. This blocks until the last setpoint,
+    /// the end of the move, has been sent.
+    pub fn stream_trajectory(
+        &mut self,
+        axis: AxisID,
+        trajectory: &Trajectory,
+        period: Duration,
+        inertia: f32,
+    ) -> ODriveResult<()> {
+        let start = Instant::now();
+        for sample in trajectory.samples(period) {
+            // Sleep until the time of the sample, so that delays do not add up
+            let now = Instant::now();
+            let due = start + sample.time;
+            if due > now {
+                sleep(due - now);
+            }
+            self.set_position_p(
+                axis,
+                sample.position,
+                Some(sample.velocity),
+                Some(sample.current_feed_forward(inertia)),
+            )
+            .map_err(ODriveError::Io)?;
+        }
+        Ok(())
+    }
+}
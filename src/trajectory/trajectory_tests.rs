@@ -0,0 +1,187 @@
+use super::*;
+use crate::test_stream::MockStream;
+use crate::test_util::assert_close;
+
+const TRAPEZOIDAL: TrapezoidalLimits = TrapezoidalLimits {
+    velocity: 1000.0,
+    acceleration: 2000.0,
+    deceleration: 4000.0,
+};
+
+const S_CURVE: SCurveLimits = SCurveLimits {
+    velocity: 1000.0,
+    acceleration: 2000.0,
+    jerk: 10000.0,
+};
+
+/// Some expected values, such as the peak velocity of the triangle, are rounded.
+const TOLERANCE: f32 = 1e-2;
+
+/// Checks that a fine sampling of `trajectory` stays within the limits and is continuous.
+fn assert_within_limits(trajectory: &Trajectory, velocity: f32, acceleration: f32) {
+    let period = Duration::from_micros(500);
+    let samples: Vec<Sample> = trajectory.samples(period).collect();
+    for pair in samples.windows(2) {
+        assert!(pair[1].velocity.abs() <= velocity + 1e-2);
+        assert!(pair[1].acceleration.abs() <= acceleration + 1e-2);
+        let max_step = velocity * period.as_secs_f32() + 1e-2;
+        assert!((pair[1].position - pair[0].position).abs() <= max_step);
+    }
+}
+
+#[test]
+fn test_trapezoidal_with_cruise() {
+    let trajectory = Trajectory::trapezoidal(0.0, 1000.0, TRAPEZOIDAL).unwrap();
+    // 0.5 s to accelerate over 250 counts, 0.25 s to decelerate over 125 counts
+    // and 0.625 s to cruise over the remaining 625 counts
+    assert_eq!(Duration::from_millis(1375), trajectory.duration());
+
+    let cruising = trajectory.sample(Duration::from_millis(800));
+    assert_close(1000.0, cruising.velocity, TOLERANCE);
+    assert_close(0.0, cruising.acceleration, TOLERANCE);
+    assert_close(550.0, cruising.position, TOLERANCE);
+
+    let decelerating = trajectory.sample(Duration::from_millis(1300));
+    assert_close(-4000.0, decelerating.acceleration, TOLERANCE);
+    assert_within_limits(&trajectory, 1000.0, 4000.0);
+}
+
+#[test]
+fn test_trapezoidal_triangle() {
+    let limits = TrapezoidalLimits {
+        deceleration: 2000.0,
+        ..TRAPEZOIDAL
+    };
+    let trajectory = Trajectory::trapezoidal(0.0, 200.0, limits).unwrap();
+    // The velocity peaks at sqrt(200 * 2000) = 632 counts/s halfway
+    let middle = trajectory.duration() / 2;
+    assert_close(632.456, trajectory.sample(middle).velocity, TOLERANCE);
+    assert_close(100.0, trajectory.sample(middle).position, TOLERANCE);
+    assert_within_limits(&trajectory, 1000.0, 2000.0);
+}
+
+#[test]
+fn test_backwards() {
+    let trajectory = Trajectory::trapezoidal(500.0, -500.0, TRAPEZOIDAL).unwrap();
+    let cruising = trajectory.sample(Duration::from_millis(800));
+    assert_close(-1000.0, cruising.velocity, TOLERANCE);
+    assert_close(-50.0, cruising.position, TOLERANCE);
+    assert_eq!(-500.0, trajectory.sample(trajectory.duration()).position);
+}
+
+#[test]
+fn test_s_curve_with_cruise() {
+    let trajectory = Trajectory::s_curve(0.0, 2000.0, S_CURVE).unwrap();
+    // 0.2 s of jerk, 0.3 s of constant acceleration and 0.2 s of jerk reach the velocity limit
+    // after 350 counts, and the same for stopping, so 1300 counts remain for 1.3 s of cruise
+    assert_eq!(Duration::from_millis(2700), trajectory.duration());
+
+    let start = trajectory.sample(Duration::from_millis(100));
+    assert_close(1000.0, start.acceleration, TOLERANCE);
+    assert_close(50.0, start.velocity, TOLERANCE);
+    let cruising = trajectory.sample(Duration::from_millis(1350));
+    assert_close(1000.0, cruising.velocity, TOLERANCE);
+    assert_close(1000.0, cruising.position, TOLERANCE);
+    assert_within_limits(&trajectory, 1000.0, 2000.0);
+}
+
+#[test]
+fn test_s_curve_limits_jerk() {
+    let trajectory = Trajectory::s_curve(0.0, 300.0, S_CURVE).unwrap();
+    let period = Duration::from_millis(1);
+    let samples: Vec<Sample> = trajectory.samples(period).collect();
+    for pair in samples.windows(2) {
+        let change = (pair[1].acceleration - pair[0].acceleration).abs();
+        assert!(change <= 10000.0 * period.as_secs_f32() + 1e-2);
+    }
+    assert_eq!(0.0, samples[0].velocity);
+    assert_eq!(300.0, samples.last().unwrap().position);
+    assert_within_limits(&trajectory, 1000.0, 2000.0);
+}
+
+#[test]
+fn test_s_curve_without_constant_acceleration() {
+    // Too short to reach the acceleration limit
+    let trajectory = Trajectory::s_curve(0.0, 10.0, S_CURVE).unwrap();
+    let middle = trajectory.sample(trajectory.duration() / 2);
+    assert_close(5.0, middle.position, TOLERANCE);
+    assert_close(0.0, middle.acceleration, TOLERANCE);
+    assert_within_limits(&trajectory, 1000.0, 2000.0);
+}
+
+#[test]
+fn test_zero_distance() {
+    let trajectory = Trajectory::s_curve(42.0, 42.0, S_CURVE).unwrap();
+    assert_eq!(Duration::from_secs(0), trajectory.duration());
+    let samples: Vec<Sample> = trajectory.samples(Duration::from_millis(10)).collect();
+    assert_eq!(1, samples.len());
+    assert_eq!(42.0, samples[0].position);
+}
+
+#[test]
+fn test_invalid_limits() {
+    let limits = TrapezoidalLimits {
+        velocity: 0.0,
+        ..TRAPEZOIDAL
+    };
+    assert!(Trajectory::trapezoidal(0.0, 1.0, limits).is_none());
+    let limits = SCurveLimits {
+        jerk: f32::INFINITY,
+        ..S_CURVE
+    };
+    assert!(Trajectory::s_curve(0.0, 1.0, limits).is_none());
+}
+
+#[test]
+fn test_samples() {
+    let trajectory = Trajectory::trapezoidal(0.0, 1000.0, TRAPEZOIDAL).unwrap();
+    let samples: Vec<Sample> = trajectory.samples(Duration::from_millis(100)).collect();
+    // Every 100 ms up to 1.3 s, then the end at 1.375 s
+    assert_eq!(15, samples.len());
+    assert_eq!(Duration::from_millis(1300), samples[13].time);
+    assert_eq!(
+        Sample {
+            time: Duration::from_millis(1375),
+            position: 1000.0,
+            velocity: 0.0,
+            acceleration: 0.0,
+        },
+        samples[14]
+    );
+    // Sampling is deterministic
+    assert_eq!(
+        samples,
+        trajectory
+            .samples(Duration::from_millis(100))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_limits_from_trap_traj_config() {
+    let limits = TrapezoidalLimits::from(TrapTrajConfig::default());
+    assert_eq!(20000.0, limits.velocity);
+    assert_eq!(5000.0, limits.deceleration);
+}
+
+#[test]
+fn test_stream_trajectory() {
+    let limits = TrapezoidalLimits {
+        velocity: 100.0,
+        acceleration: 1000.0,
+        deceleration: 1000.0,
+    };
+    let trajectory = Trajectory::trapezoidal(0.0, 2.0, limits).unwrap();
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive
+        .stream_trajectory(AxisID::One, &trajectory, Duration::from_millis(10), 0.01)
+        .unwrap();
+
+    let written = String::from_utf8(odrive.get_mut().write_buffer.clone()).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    // The move takes 2 * sqrt(2 / 1000) = 89 ms
+    assert_eq!(10, lines.len());
+    assert_eq!("p 1 0 0 10", lines[0]);
+    assert_eq!("p 1 0.05 10 10", lines[1]);
+    assert_eq!("p 1 2 0 0", lines[9]);
+}
@@ -0,0 +1,347 @@
+//! Units of position for the motion commands, converted to the unit of the firmware with the
+//! `AxisScaling` of each axis.
+//!
+//! Firmware 0.4.x expects positions in encoder counts and firmware 0.5.x in turns of the motor.
+//! The newtypes of this module make the unit of a value explicit, and `ScaledAxis` converts them
+//! for the connected firmware:
+//!
+//! ```
+//! use odrive_rs::units::{AxisScaling, Counts, Radians, Turns, Unit};
+//!
+//! let scaling = AxisScaling::new(8192.0).with_gear_ratio(2.0);
+//! assert_eq!(Some(Counts(4096.0)), Turns(0.5).convert(&scaling));
+//! // Half a turn of the motor is a quarter turn of the output
+//! assert_eq!(Some(Radians(std::f32::consts::FRAC_PI_2)), Turns(0.5).convert(&scaling));
+//! ```
+//!
+//! Velocities use the same types, per second.
+//!
+//! `ScaledAxis` wraps the motion commands `set_position_p`, `set_position_q`, `set_velocity`,
+//! `set_trajectory` and `move_to` of `ODrive`, as well as `get_feedback` and `get_velocity`.
+//! The feedback of the other clients is typed by `try_read_both_scaled_velocities`, and by
+//! `get_scaled_feedback` of `AsyncODrive` and `CanODrive`. Every other method taking or returning
+//! an `f32` position or velocity, including the methods of `ODriveInterface`, stays in the unit of
+//! the firmware given by `Capabilities::position_unit`; `AxisScaling::to_native` and
+//! `AxisScaling::from_native` convert their values.
+
+use std::f32::consts::PI;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use crate::commands::{Feedback, ODrive, PositionUnit};
+use crate::enumerations::errors::{ODriveError, ODriveResult};
+use crate::enumerations::AxisID;
+
+#[cfg(test)]
+#[cfg_attr(tarpaulin, skip)]
+mod units_tests;
+
+/// Encoder counts.
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Counts(pub f32);
+
+/// Turns of the motor.
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Turns(pub f32);
+
+/// Radians at the output of the gearbox.
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Radians(pub f32);
+
+/// Distance travelled by a wheel at the output of the gearbox.
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Meters(pub f32);
+
+/// Describes how the motor of an axis relates to the encoder and to the driven load.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AxisScaling {
+    /// Encoder counts per turn of the motor, `<axis>.encoder.config.cpr`.
+    pub counts_per_turn: f32,
+    /// Turns of the motor per turn of the output.
+    pub gear_ratio: f32,
+    /// The radius of a wheel on the output, in meters, which is needed to convert `Meters`.
+    pub wheel_radius: Option<f32>,
+}
+
+impl AxisScaling {
+    /// A motor driving its load directly, with `counts_per_turn` encoder counts per turn.
+    pub fn new(counts_per_turn: f32) -> Self {
+        Self {
+            counts_per_turn,
+            gear_ratio: 1.0,
+            wheel_radius: None,
+        }
+    }
+
+    pub fn with_gear_ratio(self, gear_ratio: f32) -> Self {
+        Self { gear_ratio, ..self }
+    }
+
+    pub fn with_wheel_radius(self, wheel_radius: f32) -> Self {
+        Self {
+            wheel_radius: Some(wheel_radius),
+            ..self
+        }
+    }
+
+    /// Converts a value into `unit`, the unit used by the firmware.
+    pub fn to_native<U: Unit>(&self, value: U, unit: PositionUnit) -> ODriveResult<f32> {
+        let turns = value.to_turns(self).ok_or_else(missing_radius)?;
+        Ok(match unit {
+            PositionUnit::Counts => turns * self.counts_per_turn,
+            PositionUnit::Turns => turns,
+        })
+    }
+
+    /// Converts a value in `unit`, the unit used by the firmware.
+    pub fn from_native<U: Unit>(&self, value: f32, unit: PositionUnit) -> ODriveResult<U> {
+        let turns = match unit {
+            PositionUnit::Counts => value / self.counts_per_turn,
+            PositionUnit::Turns => value,
+        };
+        U::from_turns(turns, self).ok_or_else(missing_radius)
+    }
+}
+
+/// The default encoder of firmware 0.4.12, with 8192 counts per turn.
+impl Default for AxisScaling {
+    fn default() -> Self {
+        Self::new(8192.0)
+    }
+}
+
+/// A unit of position which can be converted to turns of the motor.
+/// Conversions return `None` when the scaling lacks the information they need.
+pub trait Unit: Copy {
+    fn to_turns(self, scaling: &AxisScaling) -> Option<f32>;
+
+    fn from_turns(turns: f32, scaling: &AxisScaling) -> Option<Self>;
+
+    /// Converts the value into another unit.
+    fn convert<U: Unit>(self, scaling: &AxisScaling) -> Option<U> {
+        U::from_turns(self.to_turns(scaling)?, scaling)
+    }
+}
+
+impl Unit for Counts {
+    fn to_turns(self, scaling: &AxisScaling) -> Option<f32> {
+        Some(self.0 / scaling.counts_per_turn)
+    }
+
+    fn from_turns(turns: f32, scaling: &AxisScaling) -> Option<Self> {
+        Some(Counts(turns * scaling.counts_per_turn))
+    }
+}
+
+impl Unit for Turns {
+    fn to_turns(self, _scaling: &AxisScaling) -> Option<f32> {
+        Some(self.0)
+    }
+
+    fn from_turns(turns: f32, _scaling: &AxisScaling) -> Option<Self> {
+        Some(Turns(turns))
+    }
+}
+
+impl Unit for Radians {
+    fn to_turns(self, scaling: &AxisScaling) -> Option<f32> {
+        Some(self.0 / (2.0 * PI) * scaling.gear_ratio)
+    }
+
+    fn from_turns(turns: f32, scaling: &AxisScaling) -> Option<Self> {
+        Some(Radians(turns / scaling.gear_ratio * 2.0 * PI))
+    }
+}
+
+impl Unit for Meters {
+    fn to_turns(self, scaling: &AxisScaling) -> Option<f32> {
+        let circumference = 2.0 * PI * scaling.wheel_radius?;
+        Some(self.0 / circumference * scaling.gear_ratio)
+    }
+
+    fn from_turns(turns: f32, scaling: &AxisScaling) -> Option<Self> {
+        let circumference = 2.0 * PI * scaling.wheel_radius?;
+        Some(Meters(turns / scaling.gear_ratio * circumference))
+    }
+}
+
+/// The position and velocity of an axis in a unit of this module.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ScaledFeedback<U> {
+    pub position: U,
+    pub velocity: U,
+}
+
+impl<U: Unit> ScaledFeedback<U> {
+    /// Converts feedback in `unit`, the unit used by the firmware.
+    pub fn from_native(
+        feedback: Feedback,
+        scaling: &AxisScaling,
+        unit: PositionUnit,
+    ) -> ODriveResult<Self> {
+        Ok(Self {
+            position: scaling.from_native(feedback.position, unit)?,
+            velocity: scaling.from_native(feedback.velocity, unit)?,
+        })
+    }
+}
+
+/// An axis of an ODrive whose motion commands take positions and velocities in the units of this
+/// module, created by `ODrive::scaled`.
+///
+/// Currents are not scaled: they are in A, or torques in Nm since firmware 0.5.0.
+#[derive(Debug)]
+pub struct ScaledAxis<'a, T: Read> {
+    odrive: &'a mut ODrive<T>,
+    axis: AxisID,
+    scaling: AxisScaling,
+}
+
+impl<'a, T> ScaledAxis<'a, T>
+where
+    T: Read + Write,
+{
+    pub fn axis(&self) -> AxisID {
+        self.axis
+    }
+
+    pub fn scaling(&self) -> AxisScaling {
+        self.scaling
+    }
+
+    /// Converts a value into the unit used by the firmware.
+    pub fn to_native<U: Unit>(&self, value: U) -> ODriveResult<f32> {
+        let unit = self.odrive.capabilities().position_unit();
+        self.scaling.to_native(value, unit)
+    }
+
+    /// Converts a value in the unit used by the firmware.
+    pub fn from_native<U: Unit>(&self, value: f32) -> ODriveResult<U> {
+        let unit = self.odrive.capabilities().position_unit();
+        self.scaling.from_native(value, unit)
+    }
+
+    /// See `ODrive::set_position_p`.
+    pub fn set_position_p<U: Unit>(
+        &mut self,
+        position: U,
+        velocity_feed_forward: Option<U>,
+        current_feed_forward: Option<f32>,
+    ) -> ODriveResult<()> {
+        let position = self.to_native(position)?;
+        let velocity_feed_forward = velocity_feed_forward
+            .map(|velocity| self.to_native(velocity))
+            .transpose()?;
+        self.odrive
+            .set_position_p(
+                self.axis,
+                position,
+                velocity_feed_forward,
+                current_feed_forward,
+            )
+            .map_err(ODriveError::Io)
+    }
+
+    /// See `ODrive::set_position_q`.
+    pub fn set_position_q<U: Unit>(
+        &mut self,
+        position: U,
+        velocity_limit: Option<U>,
+        current_limit: Option<f32>,
+    ) -> ODriveResult<()> {
+        let position = self.to_native(position)?;
+        let velocity_limit = velocity_limit
+            .map(|velocity| self.to_native(velocity))
+            .transpose()?;
+        self.odrive
+            .set_position_q(self.axis, position, velocity_limit, current_limit)
+            .map_err(ODriveError::Io)
+    }
+
+    /// See `ODrive::set_velocity`.
+    pub fn set_velocity<U: Unit>(
+        &mut self,
+        velocity: U,
+        current_feed_forward: Option<f32>,
+    ) -> ODriveResult<()> {
+        let velocity = self.to_native(velocity)?;
+        self.odrive
+            .set_velocity(self.axis, velocity, current_feed_forward)
+            .map_err(ODriveError::Io)
+    }
+
+    /// See `ODrive::set_trajectory`.
+    pub fn set_trajectory<U: Unit>(&mut self, position: U) -> ODriveResult<()> {
+        let position = self.to_native(position)?;
+        self.odrive
+            .set_trajectory(self.axis, position)
+            .map_err(ODriveError::Io)
+    }
+
+    /// See `ODrive::move_to`.
+    pub fn move_to<U: Unit>(&mut self, position: U, timeout: Duration) -> ODriveResult<()> {
+        let position = self.to_native(position)?;
+        self.odrive.move_to(self.axis, position, timeout)
+    }
+
+    /// See `ODrive::get_feedback`.
+    pub fn get_feedback<U: Unit>(&mut self) -> ODriveResult<ScaledFeedback<U>> {
+        let feedback = self.odrive.get_feedback(self.axis)?;
+        let unit = self.odrive.capabilities().position_unit();
+        ScaledFeedback::from_native(feedback, &self.scaling, unit)
+    }
+
+    /// See `ODrive::get_velocity`.
+    pub fn get_velocity<U: Unit>(&mut self) -> ODriveResult<Option<U>> {
+        let velocity = self
+            .odrive
+            .get_velocity(self.axis)
+            .map_err(ODriveError::Io)?;
+        velocity
+            .map(|velocity| self.from_native(velocity))
+            .transpose()
+    }
+}
+
+fn missing_radius() -> ODriveError {
+    ODriveError::Unsupported("meters without a wheel radius".to_owned())
+}
+
+/// # Units
+impl<T> ODrive<T>
+where
+    T: Read + Write,
+{
+    /// Returns a view of an axis whose motion commands take typed units, converted with `scaling`.
+    pub fn scaled(&mut self, axis: AxisID, scaling: AxisScaling) -> ScaledAxis<'_, T> {
+        ScaledAxis {
+            odrive: self,
+            axis,
+            scaling,
+        }
+    }
+
+    /// Reads the scaling of a directly driven axis from `<axis>.encoder.config.cpr`.
+    pub fn read_axis_scaling(&mut self, axis: AxisID) -> ODriveResult<AxisScaling> {
+        let cpr = self.read_encoder_cpr(axis)?;
+        Ok(AxisScaling::new(cpr as f32))
+    }
+
+    /// Same as `try_read_both_velocities`, but converts the velocities with the scaling of each
+    /// axis, in the order axis 0, axis 1.
+    pub fn try_read_both_scaled_velocities<U: Unit>(
+        &mut self,
+        scalings: &[AxisScaling; 2],
+    ) -> ODriveResult<(Option<U>, Option<U>)> {
+        let unit = self.capabilities().position_unit();
+        let (velocity0, velocity1) = self.try_read_both_velocities();
+        Ok((
+            velocity0
+                .map(|velocity| scalings[0].from_native(velocity, unit))
+                .transpose()?,
+            velocity1
+                .map(|velocity| scalings[1].from_native(velocity, unit))
+                .transpose()?,
+        ))
+    }
+}
@@ -0,0 +1,156 @@
+use super::*;
+use crate::commands::{Capabilities, FirmwareVersion};
+use crate::test_stream::MockStream;
+use crate::test_util::assert_close;
+
+const TOLERANCE: f32 = 1e-4;
+
+fn init_odrive_0_5() -> ODrive<MockStream> {
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.set_capabilities(Capabilities::new(FirmwareVersion::new(0, 5, 6), None));
+    odrive
+}
+
+#[test]
+fn test_conversions() {
+    let scaling = AxisScaling::new(4000.0).with_gear_ratio(10.0);
+    assert_eq!(Some(2.5), Counts(10000.0).to_turns(&scaling));
+    assert_eq!(Some(Counts(2000.0)), Turns(0.5).convert(&scaling));
+    // Ten turns of the motor are one turn of the output
+    assert_close(
+        2.0 * PI,
+        Turns(10.0).convert::<Radians>(&scaling).unwrap().0,
+        TOLERANCE,
+    );
+    assert_close(
+        40000.0,
+        Radians(2.0 * PI).convert::<Counts>(&scaling).unwrap().0,
+        TOLERANCE,
+    );
+}
+
+#[test]
+fn test_meters() {
+    let scaling = AxisScaling::default()
+        .with_gear_ratio(2.0)
+        .with_wheel_radius(0.1);
+    let meters: Meters = Turns(2.0).convert(&scaling).unwrap();
+    assert_close(2.0 * PI * 0.1, meters.0, TOLERANCE);
+    assert_close(2.0, meters.to_turns(&scaling).unwrap(), TOLERANCE);
+
+    assert_eq!(None, Meters(1.0).to_turns(&AxisScaling::default()));
+    assert_eq!(None, Turns(1.0).convert::<Meters>(&AxisScaling::default()));
+}
+
+#[test]
+fn test_scaled_commands_on_0_4() {
+    let mut odrive = ODrive::new(MockStream::new());
+    let mut axis = odrive.scaled(AxisID::Zero, AxisScaling::new(8192.0));
+    axis.set_position_p(Turns(0.5), Some(Turns(1.0)), Some(0.5))
+        .unwrap();
+    axis.set_velocity(Counts(100.0), None).unwrap();
+    axis.set_trajectory(Turns(-2.0)).unwrap();
+    assert_eq!(
+        b"p 0 4096 8192 0.5\nv 0 100 0\nt 0 -16384\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_scaled_commands_on_0_5() {
+    let mut odrive = init_odrive_0_5();
+    let scaling = AxisScaling::new(8192.0).with_wheel_radius(0.5 / PI);
+    let mut axis = odrive.scaled(AxisID::One, scaling);
+    axis.set_position_q(Counts(4096.0), Some(Counts(8192.0)), None)
+        .unwrap();
+    // The wheel has a circumference of one meter
+    axis.set_velocity(Meters(3.0), Some(0.1)).unwrap();
+    assert_eq!(
+        b"q 1 0.5 1 0\nv 1 3 0.1\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+}
+
+#[test]
+fn test_meters_without_wheel_radius() {
+    let mut odrive = ODrive::new(MockStream::new());
+    let mut axis = odrive.scaled(AxisID::Zero, AxisScaling::default());
+    match axis.set_trajectory(Meters(1.0)) {
+        Err(ODriveError::Unsupported(_)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(odrive.get_mut().write_buffer.is_empty());
+}
+
+#[test]
+fn test_scaled_feedback() {
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"2048 -8192\n");
+    let feedback: ScaledFeedback<Turns> = odrive
+        .scaled(AxisID::Zero, AxisScaling::new(8192.0))
+        .get_feedback()
+        .unwrap();
+    assert_eq!(
+        ScaledFeedback {
+            position: Turns(0.25),
+            velocity: Turns(-1.0),
+        },
+        feedback
+    );
+
+    let mut odrive = init_odrive_0_5();
+    odrive.get_mut().push_response(b"0.25 -1\n");
+    let feedback: ScaledFeedback<Counts> = odrive
+        .scaled(AxisID::Zero, AxisScaling::new(4000.0))
+        .get_feedback()
+        .unwrap();
+    assert_eq!(Counts(1000.0), feedback.position);
+    assert_eq!(Counts(-4000.0), feedback.velocity);
+}
+
+#[test]
+fn test_scaled_velocities() {
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"4096.0\n");
+    let velocity: Option<Turns> = odrive
+        .scaled(AxisID::One, AxisScaling::new(8192.0))
+        .get_velocity()
+        .unwrap();
+    assert_eq!(Some(Turns(0.5)), velocity);
+
+    let mut odrive = init_odrive_0_5();
+    odrive.get_mut().push_response(b"0 2\n");
+    odrive.get_mut().push_response(b"0 -0.5\n");
+    odrive.request_feedback(AxisID::Zero).unwrap();
+    odrive.request_feedback(AxisID::One).unwrap();
+    let scalings = [
+        AxisScaling::new(4000.0),
+        AxisScaling::new(4000.0).with_gear_ratio(2.0),
+    ];
+    let (velocity0, velocity1) = odrive
+        .try_read_both_scaled_velocities::<Counts>(&scalings)
+        .unwrap();
+    assert_eq!(Some(Counts(8000.0)), velocity0);
+    assert_eq!(Some(Counts(-2000.0)), velocity1);
+}
+
+#[test]
+fn test_read_axis_scaling() {
+    let mut odrive = ODrive::new(MockStream::new());
+    odrive.get_mut().push_response(b"2048\n");
+    assert_eq!(
+        AxisScaling::new(2048.0),
+        odrive.read_axis_scaling(AxisID::One).unwrap()
+    );
+    assert_eq!(
+        b"r axis1.encoder.config.cpr\n".to_vec(),
+        odrive.get_mut().write_buffer
+    );
+
+    // A 17 bit absolute encoder does not fit in a u16
+    odrive.get_mut().push_response(b"131072\n");
+    assert_eq!(
+        AxisScaling::new(131072.0),
+        odrive.read_axis_scaling(AxisID::One).unwrap()
+    );
+}